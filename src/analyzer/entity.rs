@@ -0,0 +1,134 @@
+use crate::parser::{Annotation, ClassStructure, FieldStructure, JavaStructurePreview};
+use serde::{Deserialize, Serialize};
+
+/// Table mapping of a JPA entity class
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityMapping {
+    pub class_name: String,
+    pub fqn: String,
+    /// @Table(name), else @Entity(name), else the class name
+    pub table: String,
+    pub columns: Vec<ColumnMapping>,
+}
+
+/// Column mapping of a persistent entity field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub field: String,
+    /// @Column(name), else the field name
+    pub column: String,
+}
+
+/// Extracts table and column mappings from @Entity classes
+pub struct EntityMappingExtractor;
+
+impl EntityMappingExtractor {
+    /// Collect the mappings of every entity in a parsed file, nested classes included
+    pub fn extract(java_structure: &JavaStructurePreview) -> Vec<EntityMapping> {
+        let mut mappings = Vec::new();
+        for class in &java_structure.top_level_classes {
+            Self::extract_class(class, &mut mappings);
+        }
+        mappings
+    }
+
+    fn extract_class(class: &ClassStructure, mappings: &mut Vec<EntityMapping>) {
+        if let Some(entity) = Self::find_annotation(&class.annotations, "Entity") {
+            let table = Self::find_annotation(&class.annotations, "Table")
+                .and_then(|table| Self::string_value(table, "name"))
+                .or_else(|| Self::string_value(entity, "name"))
+                .unwrap_or_else(|| class.name.clone());
+
+            mappings.push(EntityMapping {
+                class_name: class.name.clone(),
+                fqn: class.fqn.clone(),
+                table,
+                columns: class.fields.iter().filter_map(Self::column_mapping).collect(),
+            });
+        }
+
+        for nested in &class.nested_classes {
+            Self::extract_class(nested, mappings);
+        }
+    }
+
+    /// Static, transient and @Transient fields are not persisted
+    fn column_mapping(field: &FieldStructure) -> Option<ColumnMapping> {
+        let not_persisted = field.modifiers.iter().any(|m| m == "static" || m == "transient")
+            || Self::find_annotation(&field.annotations, "Transient").is_some();
+        if not_persisted {
+            return None;
+        }
+
+        let column = Self::find_annotation(&field.annotations, "Column")
+            .and_then(|column| Self::string_value(column, "name"))
+            .unwrap_or_else(|| field.name.clone());
+
+        Some(ColumnMapping {
+            field: field.name.clone(),
+            column,
+        })
+    }
+
+    /// Match both simple and qualified names (e.g., "jakarta.persistence.Entity")
+    fn find_annotation<'a>(annotations: &'a [Annotation], name: &str) -> Option<&'a Annotation> {
+        annotations
+            .iter()
+            .find(|a| a.name.rsplit('.').next() == Some(name))
+    }
+
+    fn string_value(annotation: &Annotation, key: &str) -> Option<String> {
+        annotation
+            .values
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.trim_matches('"').to_string())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::JavaStructureParser;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_entity_table_and_column_mappings() {
+        let java_content = r#"
+            package com.example.model;
+
+            @Entity
+            @Table(name = "users")
+            public class User {
+                @Id
+                private Long id;
+
+                @Column(name = "email")
+                private String emailAddress;
+
+                @Transient
+                private String displayName;
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("User.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = JavaStructureParser::new().unwrap().parse_structure(&java_path).unwrap();
+        let mappings = EntityMappingExtractor::extract(&structure);
+
+        assert_eq!(mappings.len(), 1);
+        let user = &mappings[0];
+        assert_eq!(user.fqn, "com.example.model.User");
+        assert_eq!(user.table, "users");
+
+        let columns: Vec<(&str, &str)> = user
+            .columns
+            .iter()
+            .map(|c| (c.field.as_str(), c.column.as_str()))
+            .collect();
+        assert_eq!(columns, vec![("id", "id"), ("emailAddress", "email")]);
+    }
+}
@@ -0,0 +1,6 @@
+//! Post-parse analyzers deriving framework-level facts from parsed Java structures
+mod entity;
+mod routes;
+
+pub use entity::*;
+pub use routes::*;
@@ -0,0 +1,186 @@
+use crate::parser::{Annotation, ClassStructure, JavaStructurePreview, MethodStructure, SourceRange};
+use serde::{Deserialize, Serialize};
+
+/// One HTTP endpoint exposed by a Spring handler method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    /// HTTP method ("GET", "POST", ...), or "ANY" when the mapping does not restrict it
+    pub http_method: String,
+    /// Full path, class-level prefix included (e.g., "/api/users/{id}")
+    pub path: String,
+    /// Handler as "fqn#method"
+    pub handler: String,
+    pub range: SourceRange,
+}
+
+/// Extracts Spring MVC routes from @RequestMapping and its shortcut annotations
+pub struct RouteExtractor;
+
+impl RouteExtractor {
+    /// Collect the routes of every class in a parsed file, nested classes included
+    pub fn extract(java_structure: &JavaStructurePreview) -> Vec<Route> {
+        let mut routes = Vec::new();
+        for class in &java_structure.top_level_classes {
+            Self::extract_class(class, &mut routes);
+        }
+        routes
+    }
+
+    fn extract_class(class: &ClassStructure, routes: &mut Vec<Route>) {
+        // A class-level @RequestMapping only contributes path prefixes
+        let prefixes = class
+            .annotations
+            .iter()
+            .find(|a| a.name == "RequestMapping")
+            .map(Self::mapping_paths)
+            .unwrap_or_else(|| vec![String::new()]);
+
+        for method in &class.methods {
+            for annotation in &method.annotations {
+                if let Some(http_methods) = Self::mapping_methods(annotation) {
+                    Self::push_routes(class, method, annotation, &prefixes, &http_methods, routes);
+                }
+            }
+        }
+
+        for nested in &class.nested_classes {
+            Self::extract_class(nested, routes);
+        }
+    }
+
+    fn push_routes(
+        class: &ClassStructure,
+        method: &MethodStructure,
+        annotation: &Annotation,
+        prefixes: &[String],
+        http_methods: &[String],
+        routes: &mut Vec<Route>,
+    ) {
+        for prefix in prefixes {
+            for path in Self::mapping_paths(annotation) {
+                for http_method in http_methods {
+                    routes.push(Route {
+                        http_method: http_method.clone(),
+                        path: Self::join_paths(prefix, &path),
+                        handler: format!("{}#{}", class.fqn, method.name),
+                        range: method.range.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// HTTP methods mapped by an annotation, or None if it is not a mapping annotation
+    fn mapping_methods(annotation: &Annotation) -> Option<Vec<String>> {
+        let shortcut = match annotation.name.as_str() {
+            "GetMapping" => "GET",
+            "PostMapping" => "POST",
+            "PutMapping" => "PUT",
+            "DeleteMapping" => "DELETE",
+            "PatchMapping" => "PATCH",
+            "RequestMapping" => {
+                // method = RequestMethod.GET or method = {RequestMethod.GET, RequestMethod.POST}
+                let methods: Vec<String> = annotation
+                    .values
+                    .iter()
+                    .filter(|(key, _)| key == "method")
+                    .flat_map(|(_, value)| Self::array_elements(value))
+                    .map(|value| value.rsplit('.').next().unwrap_or(&value).to_uppercase())
+                    .collect();
+                return Some(if methods.is_empty() { vec!["ANY".to_string()] } else { methods });
+            }
+            _ => return None,
+        };
+        Some(vec![shortcut.to_string()])
+    }
+
+    /// Paths declared through "value" or "path", defaulting to the empty path
+    fn mapping_paths(annotation: &Annotation) -> Vec<String> {
+        let paths: Vec<String> = annotation
+            .values
+            .iter()
+            .filter(|(key, _)| key == "value" || key == "path")
+            .flat_map(|(_, value)| Self::array_elements(value))
+            .map(|value| value.trim_matches('"').to_string())
+            .collect();
+
+        if paths.is_empty() { vec![String::new()] } else { paths }
+    }
+
+    /// Split an annotation value that may be an array initializer ("{a, b}") into its elements
+    fn array_elements(value: &str) -> Vec<String> {
+        let value = value.trim();
+        let inner = value
+            .strip_prefix('{')
+            .and_then(|v| v.strip_suffix('}'))
+            .unwrap_or(value);
+
+        inner
+            .split(',')
+            .map(|element| element.trim().to_string())
+            .filter(|element| !element.is_empty())
+            .collect()
+    }
+
+    fn join_paths(prefix: &str, path: &str) -> String {
+        let joined = format!(
+            "/{}/{}",
+            prefix.trim_matches('/'),
+            path.trim_matches('/')
+        );
+        let mut normalized = String::with_capacity(joined.len());
+        for c in joined.chars() {
+            if !(c == '/' && normalized.ends_with('/')) {
+                normalized.push(c);
+            }
+        }
+        if normalized.len() > 1 && normalized.ends_with('/') {
+            normalized.pop();
+        }
+        normalized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::JavaStructureParser;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_array_http_methods_expand_to_routes() {
+        let java_content = r#"
+            package com.example.web;
+
+            @RestController
+            @RequestMapping("/api")
+            public class OrderController {
+                @RequestMapping(value = "/orders", method = {RequestMethod.GET, RequestMethod.POST})
+                public List<Order> orders() {
+                    return List.of();
+                }
+
+                @GetMapping("/orders/{id}")
+                public Order order(@PathVariable Long id) {
+                    return null;
+                }
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("OrderController.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = JavaStructureParser::new().unwrap().parse_structure(&java_path).unwrap();
+        let routes = RouteExtractor::extract(&structure);
+
+        let orders: Vec<&Route> = routes.iter().filter(|r| r.path == "/api/orders").collect();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].http_method, "GET");
+        assert_eq!(orders[1].http_method, "POST");
+        assert!(orders.iter().all(|r| r.handler == "com.example.web.OrderController#orders"));
+
+        assert!(routes.iter().any(|r| r.path == "/api/orders/{id}" && r.http_method == "GET"));
+        assert_eq!(routes.len(), 3);
+    }
+}
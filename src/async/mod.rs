@@ -1,6 +1,7 @@
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::task::JoinSet;
 use tokio::sync::{Semaphore, mpsc};
 use rayon::prelude::*;
@@ -10,35 +11,187 @@ use crate::{
     indexer::IndexManager,
 };
 
+/// Number of parsed files the indexer buffers before committing, by default
+pub const DEFAULT_COMMIT_INTERVAL: usize = 100;
+/// Parsed files waiting for the indexer before parsing pauses, by default
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 1000;
+
+/// Settings of an AsyncProcessor, checked by `build`
+#[derive(Debug, Clone)]
+pub struct AsyncProcessorBuilder {
+    max_concurrent_files: usize,
+    max_concurrent_parsers: usize,
+    batch_size: usize,
+    commit_every: usize,
+    channel_capacity: usize,
+}
+
+impl Default for AsyncProcessorBuilder {
+    fn default() -> Self {
+        Self {
+            max_concurrent_files: 8,
+            max_concurrent_parsers: 4,
+            batch_size: 100,
+            commit_every: DEFAULT_COMMIT_INTERVAL,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+        }
+    }
+}
+
+impl AsyncProcessorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Files read and parsed at the same time by process_project_async
+    pub fn max_concurrent_files(mut self, max_concurrent_files: usize) -> Self {
+        self.max_concurrent_files = max_concurrent_files;
+        self
+    }
+
+    /// Parsers running at the same time in process_files_parallel
+    pub fn max_concurrent_parsers(mut self, max_concurrent_parsers: usize) -> Self {
+        self.max_concurrent_parsers = max_concurrent_parsers;
+        self
+    }
+
+    /// Files parsed and indexed together by process_in_batches
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Indexed files between two commits of process_project_async
+    pub fn commit_every(mut self, commit_every: usize) -> Self {
+        self.commit_every = commit_every;
+        self
+    }
+
+    /// Parsed files buffered between the parsers and the indexer
+    pub fn channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.channel_capacity = channel_capacity;
+        self
+    }
+
+    pub fn build(self) -> Result<AsyncProcessor> {
+        for (name, value) in [
+            ("max_concurrent_files", self.max_concurrent_files),
+            ("max_concurrent_parsers", self.max_concurrent_parsers),
+            ("batch_size", self.batch_size),
+            ("commit_every", self.commit_every),
+            ("channel_capacity", self.channel_capacity),
+        ] {
+            check_positive(name, value)?;
+        }
+        Ok(AsyncProcessor::from_builder(self))
+    }
+}
+
+/// The error build reports for a zero setting
+fn check_positive(name: &str, value: usize) -> Result<()> {
+    if value == 0 {
+        anyhow::bail!("AsyncProcessor {} must be greater than zero", name);
+    }
+    Ok(())
+}
+
 #[derive(Clone)]
 pub struct AsyncProcessor {
     max_concurrent_files: usize,
     max_concurrent_parsers: usize,
+    batch_size: usize,
+    commit_interval: usize,
+    channel_capacity: usize,
     semaphore: Arc<Semaphore>,
+    /// Set to stop processing before the next file
+    cancelled: Arc<AtomicBool>,
+    /// Updated as files are parsed, for callers reporting progress
+    progress_monitor: Option<Arc<ProgressMonitor>>,
 }
 
 impl AsyncProcessor {
+    /// A processor with the builder defaults for everything but the concurrency
     pub fn new(max_concurrent_files: usize, max_concurrent_parsers: usize) -> Self {
+        Self::from_builder(
+            AsyncProcessorBuilder::default()
+                .max_concurrent_files(max_concurrent_files)
+                .max_concurrent_parsers(max_concurrent_parsers),
+        )
+    }
+
+    pub fn builder() -> AsyncProcessorBuilder {
+        AsyncProcessorBuilder::default()
+    }
+
+    fn from_builder(builder: AsyncProcessorBuilder) -> Self {
         Self {
-            max_concurrent_files,
-            max_concurrent_parsers,
-            semaphore: Arc::new(Semaphore::new(max_concurrent_files)),
+            max_concurrent_files: builder.max_concurrent_files,
+            max_concurrent_parsers: builder.max_concurrent_parsers,
+            batch_size: builder.batch_size,
+            commit_interval: builder.commit_every,
+            channel_capacity: builder.channel_capacity,
+            semaphore: Arc::new(Semaphore::new(builder.max_concurrent_files)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            progress_monitor: None,
         }
     }
 
+    pub fn max_concurrent_files(&self) -> usize {
+        self.max_concurrent_files
+    }
+
+    pub fn max_concurrent_parsers(&self) -> usize {
+        self.max_concurrent_parsers
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    pub fn commit_interval(&self) -> usize {
+        self.commit_interval
+    }
+
+    pub fn channel_capacity(&self) -> usize {
+        self.channel_capacity
+    }
+
+    /// Commit every `commit_interval` indexed files instead of every DEFAULT_COMMIT_INTERVAL
+    /// Smaller intervals make progress durable and visible to readers sooner,
+    /// at the cost of more commits; zero is rejected, as by AsyncProcessorBuilder::commit_every
+    pub fn with_commit_interval(mut self, commit_interval: usize) -> Result<Self> {
+        check_positive("commit_every", commit_interval)?;
+        self.commit_interval = commit_interval;
+        Ok(self)
+    }
+
+    /// Stop process_project_async once `cancelled` is set: files not yet started are skipped,
+    /// the files parsed so far are still indexed, and the partial stats are returned
+    pub fn with_cancellation(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = cancelled;
+        self
+    }
+
+    /// Count each file handled by process_project_async in `monitor` as well, so that
+    /// another task can call print_progress while the project is processed
+    pub fn with_progress_monitor(mut self, monitor: Arc<ProgressMonitor>) -> Self {
+        self.progress_monitor = Some(monitor);
+        self
+    }
+
     pub async fn process_project_async(
         &self,
         project_root: &Path,
         index_manager: Arc<IndexManager>,
     ) -> Result<ProcessingStats> {
         let file_parser = FileParser::new()?;
-        let java_files = file_parser.find_source_files(project_root)?
+        let java_files = file_parser.find_source_files(project_root, &[])?
             .into_iter()
             .filter(|p| p.extension().map_or(false, |e| e == "java"))
             .collect::<Vec<_>>();
 
         let stats = Arc::new(std::sync::Mutex::new(ProcessingStats::new()));
-        let (tx, mut rx): (mpsc::Sender<Result<JavaStructurePreview>>, mpsc::Receiver<Result<JavaStructurePreview>>) = mpsc::channel(1000);
+        let (tx, mut rx): (mpsc::Sender<Result<JavaStructurePreview>>, mpsc::Receiver<Result<JavaStructurePreview>>) = mpsc::channel(self.channel_capacity);
 
         // Spawn async tasks for file processing
         let mut join_set = JoinSet::new();
@@ -49,19 +202,31 @@ impl AsyncProcessor {
             let tx = tx.clone();
             let stats = stats.clone();
             let semaphore = self.semaphore.clone();
+            let cancelled = self.cancelled.clone();
+            let progress_monitor = self.progress_monitor.clone();
 
             join_set.spawn(async move {
                 for file_path in chunk {
+                    if cancelled.load(Ordering::Relaxed) {
+                        // Only a run that actually skipped files counts as cancelled
+                        stats.lock().unwrap().cancelled = true;
+                        break;
+                    }
                     let _permit = semaphore.acquire().await.unwrap();
                     
                     match Self::process_single_file(&file_path).await {
                         Ok(java_file) => {
                             let _ = tx.send(Ok(java_file)).await;
                             stats.lock().unwrap().increment_processed();
+                            if let Some(monitor) = &progress_monitor {
+                                monitor.increment_processed();
+                            }
                         }
                         Err(e) => {
-                            eprintln!("Error processing {}: {}", file_path.display(), e);
-                            stats.lock().unwrap().increment_errors();
+                            stats.lock().unwrap().record_error(file_path, &e);
+                            if let Some(monitor) = &progress_monitor {
+                                monitor.increment_errors();
+                            }
                         }
                     }
                 }
@@ -71,23 +236,24 @@ impl AsyncProcessor {
         // Spawn indexer task
         let indexer_handle = tokio::spawn({
             let index_manager = index_manager.clone();
+            let stats = stats.clone();
+            let commit_interval = self.commit_interval;
             async move {
                 let mut processed = 0;
+                let mut batch = Vec::with_capacity(commit_interval);
                 while let Some(result) = rx.recv().await {
                     match result {
                         Ok(java_file) => {
-                            if let Err(e) = index_manager.index_java_file(&java_file).await {
-                                eprintln!("Error indexing file: {}", e);
-                            } else {
-                                processed += 1;
-                                if processed % 100 == 0 {
-                                    println!("📊 Indexed {} files...", processed);
-                                }
+                            batch.push(java_file);
+                            if batch.len() >= commit_interval {
+                                processed += Self::commit_batch(&index_manager, &mut batch, &stats).await;
+                                println!("📊 Indexed {} files...", processed);
                             }
                         }
                         Err(e) => eprintln!("Error: {}", e),
                     }
                 }
+                processed += Self::commit_batch(&index_manager, &mut batch, &stats).await;
                 processed
             }
         });
@@ -99,11 +265,57 @@ impl AsyncProcessor {
         let total_indexed = indexer_handle.await?;
         
         let final_stats = stats.lock().unwrap().clone();
-        println!("✅ Async processing completed. Indexed {} files", total_indexed);
+        if final_stats.cancelled {
+            println!("⏹️  Async processing cancelled. Indexed {} files", total_indexed);
+        } else {
+            println!("✅ Async processing completed. Indexed {} files", total_indexed);
+        }
+        for (path, message) in &final_stats.errors {
+            eprintln!("Error processing {}: {}", path.display(), message);
+        }
         
         Ok(final_stats)
     }
 
+    /// Index and drain the buffered files with a single commit
+    /// Returns how many files were indexed (0 if the batch failed)
+    async fn index_batch(index_manager: &IndexManager, batch: &mut Vec<JavaStructurePreview>) -> usize {
+        if batch.is_empty() {
+            return 0;
+        }
+
+        let count = batch.len();
+        let result = index_manager.index_java_files(batch).await;
+        batch.clear();
+
+        match result {
+            Ok(()) => count,
+            Err(e) => {
+                eprintln!("Error indexing batch of {} files: {}", count, e);
+                0
+            }
+        }
+    }
+
+    /// Index a batch and count the commit in the stats, returning how many files were indexed
+    async fn commit_batch(
+        index_manager: &IndexManager,
+        batch: &mut Vec<JavaStructurePreview>,
+        stats: &std::sync::Mutex<ProcessingStats>,
+    ) -> usize {
+        let indexed = Self::index_batch(index_manager, batch).await;
+        if indexed > 0 {
+            stats.lock().unwrap().commits += 1;
+        }
+        indexed
+    }
+
+    fn record_batch(stats: &std::sync::Mutex<ProcessingStats>, indexed: usize, pending: usize) {
+        let mut stats = stats.lock().unwrap();
+        stats.processed_files += indexed;
+        stats.error_files += pending - indexed;
+    }
+
     async fn process_single_file(file_path: &PathBuf) -> Result<JavaStructurePreview> {
         let mut java_parser = JavaStructureParser::new()?;
         java_parser.parse_structure(file_path)
@@ -148,6 +360,15 @@ impl AsyncProcessor {
         Ok(results)
     }
 
+    /// process_with_backpressure with the configured batch size
+    pub async fn process_in_batches(
+        &self,
+        project_root: &Path,
+        index_manager: Arc<IndexManager>,
+    ) -> Result<ProcessingStats> {
+        self.process_with_backpressure(project_root, index_manager, self.batch_size).await
+    }
+
     pub async fn process_with_backpressure(
         &self,
         project_root: &Path,
@@ -155,7 +376,7 @@ impl AsyncProcessor {
         batch_size: usize,
     ) -> Result<ProcessingStats> {
         let file_parser = FileParser::new()?;
-        let java_files = file_parser.find_source_files(project_root)?
+        let java_files = file_parser.find_source_files(project_root, &[])?
             .into_iter()
             .filter(|p| p.extension().map_or(false, |e| e == "java"))
             .collect::<Vec<_>>();
@@ -187,19 +408,18 @@ impl AsyncProcessor {
                 let mut processed = 0;
                 let mut rx = rx;
                 
+                let mut batch = Vec::with_capacity(batch_size);
+                
                 while let Some(result) = rx.recv().await {
                     match result {
                         Ok(java_structure) => {
-                            if let Err(e) = index_manager.index_java_file(&java_structure).await {
-                                eprintln!("Error indexing: {}", e);
-                                stats.lock().unwrap().increment_errors();
-                            } else {
-                                processed += 1;
-                                stats.lock().unwrap().increment_processed();
-                                
-                                if processed % 100 == 0 {
-                                    println!("🔄 Processed {} files with backpressure...", processed);
-                                }
+                            batch.push(java_structure);
+                            if batch.len() >= batch_size {
+                                let pending = batch.len();
+                                let indexed = Self::commit_batch(&index_manager, &mut batch, &stats).await;
+                                Self::record_batch(&stats, indexed, pending);
+                                processed += indexed;
+                                println!("🔄 Processed {} files with backpressure...", processed);
                             }
                         }
                         Err(e) => {
@@ -208,7 +428,11 @@ impl AsyncProcessor {
                         }
                     }
                 }
-                processed
+
+                let pending = batch.len();
+                let indexed = Self::commit_batch(&index_manager, &mut batch, &stats).await;
+                Self::record_batch(&stats, indexed, pending);
+                processed + indexed
             }
         });
 
@@ -226,7 +450,7 @@ impl AsyncProcessor {
         project_root: &Path,
     ) -> Result<ProgressMonitor> {
         let file_parser = FileParser::new()?;
-        let total_files = file_parser.find_source_files(project_root)?
+        let total_files = file_parser.find_source_files(project_root, &[])?
             .into_iter()
             .filter(|p| p.extension().map_or(false, |e| e == "java"))
             .count();
@@ -240,6 +464,12 @@ pub struct ProcessingStats {
     pub total_files: usize,
     pub processed_files: usize,
     pub error_files: usize,
+    /// Index commits made while processing
+    pub commits: usize,
+    /// Files that could not be parsed, with the reason
+    pub errors: Vec<(PathBuf, String)>,
+    /// Whether processing was cancelled before every file was handled
+    pub cancelled: bool,
     pub start_time: std::time::Instant,
 }
 
@@ -249,6 +479,9 @@ impl ProcessingStats {
             total_files: 0,
             processed_files: 0,
             error_files: 0,
+            commits: 0,
+            errors: Vec::new(),
+            cancelled: false,
             start_time: std::time::Instant::now(),
         }
     }
@@ -261,6 +494,12 @@ impl ProcessingStats {
         self.error_files += 1;
     }
 
+    /// Count a failed file and keep its error, causes included
+    pub fn record_error(&mut self, path: PathBuf, error: &anyhow::Error) {
+        self.increment_errors();
+        self.errors.push((path, format!("{:#}", error)));
+    }
+
     pub fn progress(&self) -> f64 {
         if self.total_files == 0 {
             0.0
@@ -322,6 +561,14 @@ impl ProgressMonitor {
         }
     }
 
+    pub fn processed_files(&self) -> usize {
+        self.processed_files.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    pub fn error_files(&self) -> usize {
+        self.error_files.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
     pub fn elapsed(&self) -> std::time::Duration {
         self.start_time.elapsed()
     }
@@ -376,6 +623,167 @@ mod tests {
         assert_eq!(stats.error_files, 0);
     }
 
+    #[tokio::test]
+    async fn test_errors_are_recorded_per_file() {
+        let dir = tempdir().unwrap();
+        let project_root = dir.path().join("src");
+        std::fs::create_dir(&project_root).unwrap();
+        std::fs::write(project_root.join("Valid.java"), "public class Valid {}").unwrap();
+        // Over the parser's size limit, so the file is not read
+        let unreadable = project_root.join("Generated.java");
+        let oversized = crate::parser::DEFAULT_MAX_FILE_SIZE as usize + 1;
+        std::fs::write(&unreadable, " ".repeat(oversized)).unwrap();
+
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let stats = AsyncProcessor::new(2, 2)
+            .process_project_async(&project_root, Arc::new(index_manager))
+            .await
+            .unwrap();
+
+        assert_eq!(stats.processed_files, 1);
+        assert_eq!(stats.error_files, 1);
+        assert_eq!(stats.errors.len(), 1);
+        let (path, message) = &stats.errors[0];
+        assert_eq!(path, &unreadable);
+        assert!(message.contains("exceeds the"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_keeps_partial_results() {
+        let dir = tempdir().unwrap();
+        let project_root = dir.path().join("src");
+        std::fs::create_dir(&project_root).unwrap();
+        for i in 0..5 {
+            std::fs::write(
+                project_root.join(format!("Service{}.java", i)),
+                format!("public class Service{} {{}}", i),
+            ).unwrap();
+        }
+
+        let index_manager = Arc::new(IndexManager::new(&dir.path().join("index")).unwrap());
+        let cancelled = Arc::new(AtomicBool::new(false));
+        // One worker, one permit: take the permit away so the first file waits for it
+        let processor = AsyncProcessor::new(1, 1).with_cancellation(cancelled.clone());
+        let semaphore = processor.semaphore.clone();
+        semaphore.acquire().await.unwrap().forget();
+
+        let run = tokio::spawn({
+            let index_manager = index_manager.clone();
+            async move { processor.process_project_async(&project_root, index_manager).await }
+        });
+
+        // A permit handed back is taken at once by a waiting worker, which is then past
+        // the check of the first file; otherwise take it back and let the worker run
+        loop {
+            semaphore.add_permits(1);
+            if semaphore.available_permits() == 0 {
+                break;
+            }
+            semaphore.try_acquire().unwrap().forget();
+            tokio::task::yield_now().await;
+        }
+
+        // The worker has not run since taking the permit: cancel before the second file
+        cancelled.store(true, Ordering::Relaxed);
+        let stats = run.await.unwrap().unwrap();
+
+        assert!(stats.cancelled);
+        assert_eq!(stats.processed_files, 1);
+        assert_eq!(index_manager.count_by_kind().unwrap().values().sum::<usize>(), 1);
+
+        // Cancelling a run with nothing left to skip does not mark it cancelled
+        let empty = dir.path().join("empty");
+        std::fs::create_dir(&empty).unwrap();
+        let processor = AsyncProcessor::new(1, 1).with_cancellation(cancelled.clone());
+        let stats = processor.process_project_async(&empty, index_manager.clone()).await.unwrap();
+        assert!(!stats.cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_progress_monitor_follows_processing() {
+        let dir = tempdir().unwrap();
+        let project_root = dir.path().join("src");
+        std::fs::create_dir(&project_root).unwrap();
+        for i in 0..6 {
+            std::fs::write(
+                project_root.join(format!("Service{}.java", i)),
+                format!("public class Service{} {{}}", i),
+            ).unwrap();
+        }
+
+        let processor = AsyncProcessor::new(2, 2);
+        let monitor = Arc::new(processor.monitor_progress(&project_root).await.unwrap());
+        let processor = processor.with_progress_monitor(monitor.clone());
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let stats = processor.process_project_async(&project_root, Arc::new(index_manager)).await.unwrap();
+
+        assert_eq!(monitor.processed_files(), stats.processed_files);
+        assert_eq!(monitor.processed_files(), 6);
+        assert_eq!(monitor.error_files(), 0);
+        assert_eq!(monitor.progress(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_builder_applies_settings() {
+        let processor = AsyncProcessor::builder()
+            .max_concurrent_files(3)
+            .max_concurrent_parsers(5)
+            .batch_size(7)
+            .commit_every(11)
+            .channel_capacity(13)
+            .build()
+            .unwrap();
+        assert_eq!(processor.max_concurrent_files(), 3);
+        assert_eq!(processor.max_concurrent_parsers(), 5);
+        assert_eq!(processor.batch_size(), 7);
+        assert_eq!(processor.commit_interval(), 11);
+        assert_eq!(processor.channel_capacity(), 13);
+        assert_eq!(processor.semaphore.available_permits(), 3);
+
+        let legacy = AsyncProcessor::new(2, 1);
+        assert_eq!(legacy.commit_interval(), DEFAULT_COMMIT_INTERVAL);
+        assert_eq!(legacy.channel_capacity(), DEFAULT_CHANNEL_CAPACITY);
+
+        let error = AsyncProcessor::builder().commit_every(0).build().err().unwrap().to_string();
+        assert!(error.contains("commit_every must be greater than zero"), "{}", error);
+
+        // The configured batch size drives process_in_batches
+        let dir = tempdir().unwrap();
+        let project_root = dir.path().join("src");
+        std::fs::create_dir(&project_root).unwrap();
+        for i in 0..3 {
+            std::fs::write(project_root.join(format!("Service{}.java", i)), format!("class Service{} {{}}", i)).unwrap();
+        }
+        let index_manager = Arc::new(IndexManager::new(&dir.path().join("index")).unwrap());
+        let processor = AsyncProcessor::builder().batch_size(2).build().unwrap();
+        let stats = processor.process_in_batches(&project_root, index_manager).await.unwrap();
+        assert_eq!(stats.processed_files, 3);
+        assert_eq!(stats.commits, 2);
+    }
+
+    #[tokio::test]
+    async fn test_commit_interval() {
+        let dir = tempdir().unwrap();
+        let project_root = dir.path().join("src");
+        std::fs::create_dir(&project_root).unwrap();
+        for i in 0..25 {
+            std::fs::write(
+                project_root.join(format!("Service{}.java", i)),
+                format!("public class Service{} {{}}", i),
+            ).unwrap();
+        }
+
+        let index_manager = Arc::new(IndexManager::new(&dir.path().join("index")).unwrap());
+        assert!(AsyncProcessor::new(4, 2).with_commit_interval(0).is_err());
+        let processor = AsyncProcessor::new(4, 2).with_commit_interval(10).unwrap();
+
+        let stats = processor.process_project_async(&project_root, index_manager.clone()).await.unwrap();
+
+        // Two full intervals committed while files were still arriving, plus the final remainder
+        assert_eq!(stats.commits, 3);
+        assert_eq!(index_manager.stats().unwrap().0, 25);
+    }
+
     #[tokio::test]
     async fn test_progress_monitor() {
         let monitor = ProgressMonitor::new(100);
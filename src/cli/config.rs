@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Name of the project configuration file, searched upward from the current directory
+pub const CONFIG_FILE_NAME: &str = ".code-insight.toml";
+
+/// Settings read from `.code-insight.toml`; command line flags take precedence
+/// Relative paths are resolved against the directory holding the file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub project_root: Option<PathBuf>,
+    pub index_path: Option<PathBuf>,
+    /// Added to the `--exclude` globs of the command line
+    pub exclude: Vec<String>,
+    pub writer_heap_mb: Option<usize>,
+    /// Export format used when `export` is run without `--format` (e.g. "markdown")
+    pub export_format: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Nearest config file in `start` or one of its ancestors
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Parse a config file, making its paths absolute
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let mut config: Self = toml::from_str(&content)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        config.project_root = Some(config.project_root.map_or_else(|| base.to_path_buf(), |root| base.join(root)));
+        config.index_path = config.index_path.map(|index_path| base.join(index_path));
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_and_load_config() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("service/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert!(ProjectConfig::find(&nested).is_none_or(|path| !path.starts_with(dir.path())));
+
+        let config_path = dir.path().join(CONFIG_FILE_NAME);
+        std::fs::write(
+            &config_path,
+            "index_path = \"build/index\"\nexclude = [\"**/generated/**\"]\nwriter_heap_mb = 64\n",
+        )
+        .unwrap();
+        assert_eq!(ProjectConfig::find(&nested), Some(config_path.clone()));
+
+        let config = ProjectConfig::load(&config_path).unwrap();
+        assert_eq!(config.project_root.as_deref(), Some(dir.path()));
+        assert_eq!(config.index_path, Some(dir.path().join("build/index")));
+        assert_eq!(config.exclude, vec!["**/generated/**"]);
+        assert_eq!(config.writer_heap_mb, Some(64));
+
+        std::fs::write(&config_path, "index = \"typo\"\n").unwrap();
+        assert!(ProjectConfig::load(&config_path).is_err());
+    }
+}
@@ -1,15 +1,22 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::{IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use tokio;
 
+mod config;
+
+pub use config::*;
+
 use crate::parser::JavaStructureParser;
 use crate::{
-    indexer::IndexManager,
-    llm::{ExportFormat, LlmExporter},
-    parser::FileParser,
+    graph::{GraphBuilder, GraphVisualizer},
+    indexer::{IndexConfig, IndexManager},
+    llm::{ExportFormat, LlmExporter, StereotypeFacet},
+    parser::{FileMeta, FileParser, FileSuffix, GroovyParser},
     query::QueryEngine,
-    types::{DeclarationKind, SearchKind, SearchQuery},
+    types::{DeclarationKind, ReferenceGraph, SearchKind, SearchQuery},
 };
 
 #[derive(Parser)]
@@ -19,11 +26,59 @@ pub struct Args {
     #[command(subcommand)]
     pub command: Commands,
 
-    #[arg(short, long, default_value = ".")]
-    pub project_root: PathBuf,
+    /// Project to analyze (default: the directory of .code-insight.toml, or ".")
+    #[arg(short, long)]
+    pub project_root: Option<PathBuf>,
+
+    /// Index directory (default: .code-insight/index, in the config file's project root if there is one)
+    #[arg(short, long)]
+    pub index_path: Option<PathBuf>,
+
+    /// Skip source files matching this glob, relative to the project root (repeatable)
+    #[arg(long, global = true)]
+    pub exclude: Vec<String>,
+
+    /// Config file to use instead of searching for .code-insight.toml upward from the current directory
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+}
+
+/// Index directory used when neither a flag nor the config file sets one
+pub const DEFAULT_INDEX_PATH: &str = ".code-insight/index";
+
+/// Global options after merging the config file under the command line flags
+struct ResolvedArgs {
+    command: Commands,
+    project_root: PathBuf,
+    index_path: PathBuf,
+    exclude: Vec<String>,
+    writer_heap_mb: Option<usize>,
+    export_format: ExportFormatArg,
+}
+
+impl Args {
+    fn merge(self, config: ProjectConfig) -> Result<ResolvedArgs> {
+        let export_format = match &config.export_format {
+            Some(format) => <ExportFormatArg as clap::ValueEnum>::from_str(format, true)
+                .map_err(|_| anyhow::anyhow!("Unknown export_format in config file: {}", format))?,
+            None => ExportFormatArg::Json,
+        };
+        // Without a config file the defaults stay relative to the current directory
+        let index_path = match (&config.project_root, self.index_path.or(config.index_path)) {
+            (_, Some(index_path)) => index_path,
+            (Some(config_dir), None) => config_dir.join(DEFAULT_INDEX_PATH),
+            (None, None) => PathBuf::from(DEFAULT_INDEX_PATH),
+        };
 
-    #[arg(short, long, default_value = ".code-insight/index")]
-    pub index_path: PathBuf,
+        Ok(ResolvedArgs {
+            command: self.command,
+            project_root: self.project_root.or(config.project_root).unwrap_or_else(|| PathBuf::from(".")),
+            index_path,
+            exclude: config.exclude.into_iter().chain(self.exclude).collect(),
+            writer_heap_mb: config.writer_heap_mb,
+            export_format,
+        })
+    }
 }
 
 #[derive(Subcommand)]
@@ -38,6 +93,18 @@ pub enum Commands {
     Index {
         #[arg(short, long)]
         force: bool,
+
+        /// Also index each method as its own searchable document
+        #[arg(long)]
+        index_methods: bool,
+
+        /// Wait up to this many seconds for another process to release the index
+        #[arg(long)]
+        lock_timeout: Option<u64>,
+
+        /// Memory of the index writer, in MB (default: 50)
+        #[arg(long)]
+        writer_heap_mb: Option<usize>,
     },
 
     /// Search declarations
@@ -51,11 +118,23 @@ pub enum Commands {
         #[arg(short, long)]
         limit: Option<usize>,
 
+        /// Skip this many results (use with --limit to page)
+        #[arg(long)]
+        offset: Option<usize>,
+
         #[arg(short, long)]
         filter_kind: Option<DeclarationKindArg>,
 
         #[arg(short, long)]
         filter_annotation: Option<String>,
+
+        /// Only declarations carrying this modifier (e.g., abstract, final)
+        #[arg(long)]
+        filter_modifier: Option<String>,
+
+        /// Only declarations with a method declaring this exception (e.g., IOException)
+        #[arg(long)]
+        filter_throws: Option<String>,
     },
 
     /// Export for LLM/RAG systems
@@ -63,8 +142,9 @@ pub enum Commands {
         #[arg(short, long)]
         output: PathBuf,
 
-        #[arg(short, long, default_value = "json")]
-        format: ExportFormatArg,
+        /// Export format (default: the config file's export_format, or json)
+        #[arg(short, long)]
+        format: Option<ExportFormatArg>,
 
         #[arg(short, long)]
         kind: Option<DeclarationKindArg>,
@@ -80,8 +160,47 @@ pub enum Commands {
 
         #[arg(long)]
         include_source: bool,
+
+        /// Add is_service/is_controller/... flags derived from annotations
+        #[arg(long)]
+        facets: bool,
+
+        /// Write the JSON Schema of the export format to the output file instead of exporting
+        #[arg(long)]
+        emit_schema: bool,
+
+        /// Add LOC, method count and cyclomatic complexity metrics to each exported declaration
+        #[arg(long)]
+        metrics: bool,
+    },
+
+
+    /// Render the type dependency graph of the index
+    Graph {
+        /// Only show this type and its direct neighbors
+        #[arg(long)]
+        focus: Option<String>,
+
+        #[arg(short, long, default_value = "mermaid")]
+        format: GraphFormatArg,
+
+        /// Print the dependency cycles instead of the graph
+        #[arg(long)]
+        cycles: bool,
     },
 
+    /// Keep the index up to date while Java files change
+    Watch {
+        /// Also index methods as separate documents, as `index --index-methods` does
+        #[arg(long)]
+        index_methods: bool,
+    },
+
+    /// Serve search, stats and export as a JSON REST API
+    Serve {
+        #[arg(long, default_value_t = 7878)]
+        port: u16,
+    },
 
     /// Run interactive TUI
     Tui,
@@ -95,6 +214,7 @@ pub enum SearchKindArg {
     Exact,
     Fuzzy,
     Regex,
+    Substring,
 }
 
 impl From<SearchKindArg> for SearchKind {
@@ -103,6 +223,7 @@ impl From<SearchKindArg> for SearchKind {
             SearchKindArg::Exact => SearchKind::Exact,
             SearchKindArg::Fuzzy => SearchKind::Fuzzy,
             SearchKindArg::Regex => SearchKind::Regex,
+            SearchKindArg::Substring => SearchKind::Substring,
         }
     }
 }
@@ -135,6 +256,10 @@ pub enum ExportFormatArg {
     Markdown,
     LlamaIndex,
     Rag,
+    StructuredJson,
+    ApiSnapshot,
+    Csv,
+    Yaml,
 }
 
 impl From<ExportFormatArg> for ExportFormat {
@@ -145,31 +270,75 @@ impl From<ExportFormatArg> for ExportFormat {
             ExportFormatArg::Markdown => ExportFormat::Markdown,
             ExportFormatArg::LlamaIndex => ExportFormat::LlamaIndex,
             ExportFormatArg::Rag => ExportFormat::RAG,
+            ExportFormatArg::StructuredJson => ExportFormat::StructuredJson,
+            ExportFormatArg::ApiSnapshot => ExportFormat::ApiSnapshot,
+            ExportFormatArg::Csv => ExportFormat::Csv,
+            ExportFormatArg::Yaml => ExportFormat::Yaml,
         }
     }
 }
 
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum GraphFormatArg {
+    Mermaid,
+    Dot,
+    Graphml,
+    Json,
+}
 
 pub async fn run(args: Args) -> Result<()> {
+    run_from(args, &std::env::current_dir()?).await
+}
+
+/// Run a command as if started in `cwd`, where the search for the config file begins
+pub async fn run_from(mut args: Args, cwd: &Path) -> Result<()> {
+    let config = match args.config.take().or_else(|| ProjectConfig::find(cwd)) {
+        Some(path) => ProjectConfig::load(&cwd.join(path))?,
+        None => ProjectConfig::default(),
+    };
+    let args = args.merge(config)?;
+
     match args.command {
-        Commands::Parse { verbose } => parse_java_project(&args.project_root, verbose).await,
-        Commands::Index { force } => build_index(&args.project_root, &args.index_path, force).await,
+        Commands::Parse { verbose } => parse_java_project(&args.project_root, &args.exclude, verbose).await,
+        Commands::Index {
+            force,
+            index_methods,
+            lock_timeout,
+            writer_heap_mb,
+        } => {
+            let config = IndexConfig {
+                index_methods,
+                lock_timeout: lock_timeout.map(std::time::Duration::from_secs),
+                writer_heap_bytes: writer_heap_mb.or(args.writer_heap_mb).map(|mb| mb * 1_000_000),
+                ..Default::default()
+            };
+            build_index(&args.project_root, &args.index_path, &args.exclude, force, config).await
+        }
         Commands::Search {
             query,
             kind,
             limit,
+            offset,
             filter_kind,
             filter_annotation,
+            filter_modifier,
+            filter_throws,
         } => {
-            search_declarations(
-                &args.index_path,
-                &query,
-                kind.into(),
-                limit,
-                filter_kind.map(Into::into),
-                filter_annotation,
-            )
-            .await
+            let mut filters = Vec::new();
+            if let Some(k) = filter_kind {
+                filters.push(crate::types::SearchFilter::Kind(k.into()));
+            }
+            if let Some(ann) = filter_annotation {
+                filters.push(crate::types::SearchFilter::Annotation(ann));
+            }
+            if let Some(modifier) = filter_modifier {
+                filters.push(crate::types::SearchFilter::Modifier(modifier));
+            }
+            if let Some(exception) = filter_throws {
+                filters.push(crate::types::SearchFilter::Throws(exception));
+            }
+
+            search_declarations(&args.index_path, &query, kind.into(), limit, offset, filters).await
         }
         Commands::Export {
             output,
@@ -179,33 +348,57 @@ pub async fn run(args: Args) -> Result<()> {
             package,
             limit,
             include_source,
+            facets,
+            emit_schema,
+            metrics,
         } => {
+            if emit_schema {
+                return emit_export_schema(&output).await;
+            }
             export_for_llm(
                 &args.project_root,
                 &args.index_path,
                 output,
-                format.into(),
+                format.unwrap_or(args.export_format).into(),
                 kind.map(Into::into),
                 annotation,
                 package,
                 limit,
                 include_source,
+                facets,
+                metrics,
             )
             .await
         }
+        Commands::Graph { focus, format, cycles } => {
+            show_graph(&args.index_path, focus.as_deref(), format, cycles).await
+        }
+        Commands::Watch { index_methods } => {
+            let config = IndexConfig {
+                index_methods,
+                writer_heap_bytes: args.writer_heap_mb.map(|mb| mb * 1_000_000),
+                ..Default::default()
+            };
+            let index_manager = IndexManager::with_config(&args.index_path, config)?;
+            crate::watch::WatchIndexer::new(index_manager)?
+                .with_exclude(&args.project_root, &args.exclude)?
+                .run(&args.project_root)
+                .await
+        }
+        Commands::Serve { port } => serve(&args.project_root, &args.index_path, port).await,
         Commands::Tui => run_tui(&args.project_root, &args.index_path).await,
         Commands::Stats => show_stats(&args.project_root, &args.index_path).await,
     }
 }
 
 // We should make it configurable, project language and build tools
-async fn parse_java_project(project_root: &Path, verbose: bool) -> Result<()> {
+async fn parse_java_project(project_root: &Path, exclude: &[String], verbose: bool) -> Result<()> {
     println!("🔍 Parsing Java project at: {}", project_root.display());
 
     let file_parser = FileParser;
 
     // Find source files
-    let source_files = file_parser.find_source_files(project_root)?;
+    let source_files = file_parser.find_source_files(project_root, exclude)?;
     println!("📄 Found {} source files", source_files.len());
 
     // Count by type
@@ -230,7 +423,13 @@ async fn parse_java_project(project_root: &Path, verbose: bool) -> Result<()> {
     Ok(())
 }
 
-async fn build_index(project_root: &Path, index_path: &Path, force: bool) -> Result<()> {
+async fn build_index(
+    project_root: &Path,
+    index_path: &Path,
+    exclude: &[String],
+    force: bool,
+    config: IndexConfig,
+) -> Result<()> {
     println!("📚 Building search index...");
     println!("Project root: {}", project_root.display());
     println!("Index path: {}", index_path.display());
@@ -240,39 +439,178 @@ async fn build_index(project_root: &Path, index_path: &Path, force: bool) -> Res
         std::fs::remove_dir_all(index_path).context("Failed to remove existing index")?;
     }
 
-    let index_manager = IndexManager::new(index_path)?;
+    // Waiting for the writer lock blocks, so keep it off the runtime's worker threads
+    let owned_index_path = index_path.to_path_buf();
+    let index_manager =
+        tokio::task::spawn_blocking(move || IndexManager::with_config(&owned_index_path, config)).await??;
     let file_parser = FileParser::new()?;
-    let mut java_structure_parser = JavaStructureParser::new()?;
-
-    let java_files = file_parser
-        .find_source_files(project_root)?
-        .into_iter()
-        .filter(|p| p.extension().map_or(false, |e| e == "java"))
-        .collect::<Vec<_>>();
+    let java_structure_parser = JavaStructureParser::new()?;
+
+    let mut java_files = Vec::new();
+    let mut groovy_files = Vec::new();
+    let mut config_files = Vec::new();
+    for path in file_parser.find_source_files(project_root, exclude)? {
+        match FileSuffix::from_path(&path) {
+            Some(FileSuffix::Java) => java_files.push(path),
+            Some(FileSuffix::Groovy) => groovy_files.push(path),
+            _ => config_files.push(path),
+        }
+    }
 
     println!("📄 Found {} Java files to index", java_files.len());
 
+    let mut progress = IndexProgress::for_stdout(java_files.len());
+    index_files(&index_manager, &java_structure_parser, java_files, &mut progress).await?;
+
+    if !groovy_files.is_empty() {
+        println!("📄 Found {} Groovy files to index", groovy_files.len());
+    }
+    let groovy_parser = GroovyParser::new()?;
+    for groovy_file in &groovy_files {
+        if let Ok(source) = std::fs::read_to_string(groovy_file)
+            && index_manager.is_indexed(groovy_file, &FileMeta::hash_source(&source))?
+        {
+            continue;
+        }
+        let indexed = match groovy_parser.parse_groovy_file(groovy_file) {
+            Ok(parsed) => index_manager.index_groovy_file(&parsed).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = indexed {
+            eprintln!("⚠️  Failed to index {}: {}", groovy_file.display(), e);
+        }
+    }
+
+    // Spring XML and properties files, searchable through find_config_references
+    for config_file in &config_files {
+        let indexed = match config_file.extension().and_then(|e| e.to_str()) {
+            Some("xml") => match file_parser.parse_xml_file(config_file) {
+                Ok(xml_file) => index_manager.index_xml_file(&xml_file).await,
+                Err(e) => Err(e),
+            },
+            _ => match file_parser.parse_properties_file(config_file) {
+                Ok(properties_file) => index_manager.index_properties_file(&properties_file).await,
+                Err(e) => Err(e),
+            },
+        };
+        if let Err(e) = indexed {
+            eprintln!("⚠️  Failed to index {}: {}", config_file.display(), e);
+        }
+    }
+
+    index_manager.optimize().await?;
+    Ok(())
+}
+
+/// Parse and index the files, committing once per batch rather than once per file
+/// Returns (indexed files, skipped unchanged files)
+async fn index_files<W: Write>(
+    index_manager: &IndexManager,
+    java_structure_parser: &JavaStructureParser,
+    java_files: Vec<PathBuf>,
+    progress: &mut IndexProgress<W>,
+) -> Result<(usize, usize)> {
     let mut processed = 0;
+    let mut unchanged = 0;
+    let mut batch = Vec::new();
     for file_path in java_files {
+        progress.inc()?;
+
+        // Skip files already indexed under their own path with this exact content
+        if let Ok(source) = std::fs::read_to_string(&file_path)
+            && index_manager.is_indexed(&file_path, &FileMeta::hash_source(&source))?
+        {
+            unchanged += 1;
+            continue;
+        }
+
         match java_structure_parser.parse_structure(&file_path) {
             Ok(java_structure) => {
-                index_manager.index_java_file(&java_structure).await?;
-                processed += 1;
+                batch.push(java_structure);
 
-                if processed % 100 == 0 {
-                    println!("  ✅ Indexed {} files...", processed);
+                if batch.len() == 100 {
+                    index_manager.index_java_files(&batch).await?;
+                    processed += batch.len();
+                    batch.clear();
                 }
             }
             Err(e) => {
-                eprintln!("⚠️  Failed to parse {}: {}", file_path.display(), e);
+                progress.warn(&format!("⚠️  Failed to parse {}: {}", file_path.display(), e));
             }
         }
     }
+    index_manager.index_java_files(&batch).await?;
+    processed += batch.len();
 
-    index_manager.optimize().await?;
+    progress.finish(processed, unchanged)?;
+    Ok((processed, unchanged))
+}
 
-    println!("✅ Successfully indexed {} files", processed);
-    Ok(())
+/// Progress of the index command: a progress bar on a terminal,
+/// plain lines every LINE_INTERVAL files otherwise (pipes, CI logs)
+enum IndexProgress<W: Write> {
+    Bar(ProgressBar),
+    Lines { out: W, total: usize, done: usize },
+}
+
+impl IndexProgress<std::io::Stdout> {
+    fn for_stdout(total: usize) -> Self {
+        let stdout = std::io::stdout();
+        if !stdout.is_terminal() {
+            return Self::lines(stdout, total);
+        }
+
+        let bar = ProgressBar::new(total as u64);
+        bar.set_style(
+            ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} ({percent}%) {per_sec} ETA {eta}")
+                .expect("valid progress template"),
+        );
+        Self::Bar(bar)
+    }
+}
+
+impl<W: Write> IndexProgress<W> {
+    const LINE_INTERVAL: usize = 100;
+
+    fn lines(out: W, total: usize) -> Self {
+        Self::Lines { out, total, done: 0 }
+    }
+
+    fn inc(&mut self) -> Result<()> {
+        match self {
+            Self::Bar(bar) => bar.inc(1),
+            Self::Lines { out, total, done } => {
+                *done += 1;
+                if *done % Self::LINE_INTERVAL == 0 {
+                    writeln!(out, "  ✅ Processed {}/{} files...", done, total)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn warn(&self, message: &str) {
+        match self {
+            Self::Bar(bar) => bar.suspend(|| eprintln!("{}", message)),
+            Self::Lines { .. } => eprintln!("{}", message),
+        }
+    }
+
+    fn finish(&mut self, processed: usize, unchanged: usize) -> Result<()> {
+        let mut summary = format!("✅ Successfully indexed {} files", processed);
+        if unchanged > 0 {
+            summary.push_str(&format!("\n⏭️  Skipped {} unchanged files", unchanged));
+        }
+
+        match self {
+            Self::Bar(bar) => {
+                bar.finish_and_clear();
+                println!("{}", summary);
+            }
+            Self::Lines { out, .. } => writeln!(out, "{}", summary)?,
+        }
+        Ok(())
+    }
 }
 
 async fn search_declarations(
@@ -280,24 +618,18 @@ async fn search_declarations(
     query: &str,
     kind: SearchKind,
     limit: Option<usize>,
-    filter_kind: Option<DeclarationKind>,
-    filter_annotation: Option<String>,
+    offset: Option<usize>,
+    filters: Vec<crate::types::SearchFilter>,
 ) -> Result<()> {
     let query_engine = QueryEngine::new(index_path)?;
 
-    let mut filters = Vec::new();
-    if let Some(k) = filter_kind {
-        filters.push(crate::types::SearchFilter::Kind(k));
-    }
-    if let Some(ann) = filter_annotation {
-        filters.push(crate::types::SearchFilter::Annotation(ann));
-    }
-
     let search_query = SearchQuery {
         query: query.to_string(),
         kind,
         filters,
         limit,
+        offset,
+        filter_combine: crate::types::FilterCombine::Any,
     };
 
     let results = query_engine.search(&search_query).await?;
@@ -307,7 +639,7 @@ async fn search_declarations(
     for (i, result) in results.iter().enumerate() {
         println!(
             "{}. {} ({}) - {}",
-            i + 1,
+            offset.unwrap_or(0) + i + 1,
             result.declaration.name,
             format!("{:?}", result.declaration.kind).to_lowercase(),
             result.file_path.display()
@@ -338,6 +670,8 @@ async fn export_for_llm(
     package: Option<String>,
     limit: Option<usize>,
     include_source: bool,
+    facets: bool,
+    metrics: bool,
 ) -> Result<()> {
     println!("🤖 Exporting for LLM/RAG...");
 
@@ -345,19 +679,21 @@ async fn export_for_llm(
     let exporter = LlmExporter::new(query_engine, project_root.to_path_buf())?;
 
     let request = crate::llm::LlmRequest {
-        query: None,
         kind,
         annotations: annotation.map(|a| vec![a]).unwrap_or_default(),
         package,
         limit,
         include_source,
         format: format.clone(),
+        stereotype_facets: if facets { StereotypeFacet::defaults() } else { vec![] },
+        include_metrics: metrics,
+        ..Default::default()
     };
 
     let response = exporter.export(request).await?;
     let formatted = exporter.format_export(&response, &format)?;
 
-    tokio::fs::write(&output, formatted)
+    LlmExporter::write_export(&output, &formatted)
         .await
         .context("Failed to write output file")?;
 
@@ -371,14 +707,75 @@ async fn export_for_llm(
     Ok(())
 }
 
+async fn emit_export_schema(output: &Path) -> Result<()> {
+    let schema = serde_json::to_string_pretty(&crate::llm::export_schema())?;
+    tokio::fs::write(output, schema)
+        .await
+        .context("Failed to write schema file")?;
+
+    println!("✅ Wrote export schema to {}", output.display());
+    Ok(())
+}
+
+
+async fn serve(project_root: &Path, index_path: &Path, port: u16) -> Result<()> {
+    let exporter = LlmExporter::new(QueryEngine::new(index_path)?, project_root.to_path_buf())?;
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("Failed to bind port {}", port))?;
+    println!("🌐 Serving on http://{}", listener.local_addr()?);
+    crate::server::serve(listener, exporter).await
+}
 
 async fn run_tui(project_root: &Path, index_path: &Path) -> Result<()> {
-    println!("🖥️  Starting interactive TUI...");
-    println!("TUI functionality not yet implemented.");
-    println!("Project root: {}", project_root.display());
-    println!("Index path: {}", index_path.display());
+    let query_engine = QueryEngine::new(index_path)?;
+    crate::tui::run(query_engine, project_root).await
+}
+
+async fn show_graph(index_path: &Path, focus: Option<&str>, format: GraphFormatArg, cycles: bool) -> Result<()> {
+    let index_manager = IndexManager::new(index_path)?;
+    let graph = build_reference_graph(&index_manager)?;
+    write_graph(&mut std::io::stdout().lock(), graph, focus, format, cycles)
+}
+
+/// Type-level graph of the indexed declarations, with Uses edges from member types
+fn build_reference_graph(index_manager: &IndexManager) -> Result<ReferenceGraph> {
+    let mut builder = GraphBuilder::new();
+    for (file_path, declaration) in index_manager.all_declarations_with_paths()? {
+        builder.add_declaration_with_references(&declaration, &file_path);
+    }
+    Ok(builder.build())
+}
+
+fn write_graph<W: Write>(
+    out: &mut W,
+    graph: ReferenceGraph,
+    focus: Option<&str>,
+    format: GraphFormatArg,
+    cycles: bool,
+) -> Result<()> {
+    let visualizer = GraphVisualizer::new(graph);
+
+    if cycles {
+        let found = visualizer.find_cycles();
+        writeln!(out, "🔁 Found {} dependency cycles", found.len())?;
+        for cycle in found {
+            writeln!(out, "  {} -> {}", cycle.join(" -> "), cycle[0])?;
+        }
+        return Ok(());
+    }
 
-    // TODO: Implement TUI using ratatui
+    let visualizer = match focus {
+        Some(focus) => GraphVisualizer::new(visualizer.generate_focused_graph(focus)),
+        None => visualizer,
+    };
+    let rendered = match format {
+        GraphFormatArg::Mermaid => visualizer.to_mermaid(),
+        GraphFormatArg::Dot => visualizer.to_dot(),
+        GraphFormatArg::Graphml => visualizer.to_graphml(),
+        GraphFormatArg::Json => visualizer.to_json()?,
+    };
+    write!(out, "{}", rendered)?;
     Ok(())
 }
 
@@ -397,8 +794,8 @@ async fn show_stats(project_root: &Path, index_path: &Path) -> Result<()> {
     println!("📦 Records: {}", stats.record_count);
     println!("📝 Annotations: {}", stats.annotation_count);
 
-    let (cache_entries, cache_items) = query_engine.get_cache_stats().await;
-    println!("💾 Cache entries: {}", cache_entries);
+    let (cache_entries, cache_items, cache_capacity) = query_engine.get_cache_stats().await;
+    println!("💾 Cache entries: {}/{}", cache_entries, cache_capacity);
     println!("💾 Cache items: {}", cache_items);
 
     Ok(())
@@ -418,11 +815,172 @@ mod tests {
         // Test parse command
         let args = Args {
             command: Commands::Parse { verbose: false },
-            project_root: project_root.to_path_buf(),
-            index_path: index_path.clone(),
+            project_root: Some(project_root.to_path_buf()),
+            index_path: Some(index_path.clone()),
+            exclude: vec![],
+            config: None,
         };
 
         let result = run(args).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_config_file_supplies_defaults() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            "index_path = \"build/insight-index\"\nexclude = [\"**/generated/**\"]\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("src/generated")).unwrap();
+        std::fs::write(dir.path().join("src/Greeter.java"), "public class Greeter {}\n").unwrap();
+        std::fs::write(dir.path().join("src/generated/Stub.java"), "public class Stub {}\n").unwrap();
+
+        // Started from a subdirectory, with no global flags
+        let args = Args::parse_from(["code-insight", "index"]);
+        run_from(args, &dir.path().join("src")).await.unwrap();
+
+        let index_path = dir.path().join("build/insight-index");
+        assert!(index_path.join("meta.json").exists());
+        assert!(!dir.path().join(DEFAULT_INDEX_PATH).exists());
+        let index_manager = IndexManager::new(&index_path).unwrap();
+        let names: Vec<String> = index_manager
+            .all_declarations()
+            .unwrap()
+            .into_iter()
+            .map(|(_, declaration)| declaration.name)
+            .collect();
+        assert_eq!(names, vec!["Greeter"]);
+
+        // Flags win over the config file
+        let args = Args::parse_from(["code-insight", "--index-path", "other", "export", "-o", "out.json"]);
+        let resolved = args.merge(ProjectConfig::load(&dir.path().join(CONFIG_FILE_NAME)).unwrap()).unwrap();
+        assert_eq!(resolved.index_path, PathBuf::from("other"));
+        assert_eq!(resolved.project_root, dir.path());
+    }
+
+    #[tokio::test]
+    async fn test_index_command_indexes_groovy_classes() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/Greeter.java"), "public class Greeter {}\n").unwrap();
+        std::fs::write(
+            dir.path().join("src/BuildHelper.groovy"),
+            "package com.example.build\n\nclass BuildHelper {\n    String version\n\n    def describe() {\n        \"v$version\"\n    }\n}\n",
+        )
+        .unwrap();
+
+        let index_path = dir.path().join("index");
+        let args = Args::parse_from([
+            "code-insight",
+            "--project-root",
+            dir.path().to_str().unwrap(),
+            "--index-path",
+            index_path.to_str().unwrap(),
+            "index",
+        ]);
+        run(args).await.unwrap();
+
+        let query_engine = QueryEngine::new(&index_path).unwrap();
+        let results = query_engine.exact_search("BuildHelper", None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.fqn(), "com.example.build.BuildHelper");
+        assert_eq!(results[0].file_path, dir.path().join("src/BuildHelper.groovy"));
+        assert_eq!(results[0].declaration.methods[0].name, "describe");
+    }
+
+    #[tokio::test]
+    async fn test_index_progress_plain_lines() {
+        let dir = tempdir().unwrap();
+        let mut java_files = Vec::new();
+        for i in 0..3 {
+            let java_path = dir.path().join(format!("Service{}.java", i));
+            std::fs::write(&java_path, format!("public class Service{} {{}}", i)).unwrap();
+            java_files.push(java_path);
+        }
+
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let parser = JavaStructureParser::new().unwrap();
+        let mut progress = IndexProgress::lines(Vec::new(), java_files.len());
+
+        let (processed, unchanged) = index_files(&index_manager, &parser, java_files, &mut progress).await.unwrap();
+        assert_eq!((processed, unchanged), (3, 0));
+
+        let IndexProgress::Lines { out, done, .. } = progress else {
+            panic!("expected plain line output");
+        };
+        assert_eq!(done, 3);
+        assert_eq!(String::from_utf8(out).unwrap(), "✅ Successfully indexed 3 files\n");
+    }
+
+    #[tokio::test]
+    async fn test_identical_files_are_indexed_under_each_path() {
+        let dir = tempdir().unwrap();
+        let original = dir.path().join("a/Stub.java");
+        let copy = dir.path().join("b/Stub.java");
+        for path in [&original, &copy] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "public class Stub {}\n").unwrap();
+        }
+
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let parser = JavaStructureParser::new().unwrap();
+
+        // The copy is found in a later run, after the original was committed
+        let mut progress = IndexProgress::lines(Vec::new(), 1);
+        let counts = index_files(&index_manager, &parser, vec![original.clone()], &mut progress).await.unwrap();
+        assert_eq!(counts, (1, 0));
+        let mut progress = IndexProgress::lines(Vec::new(), 2);
+        let counts = index_files(&index_manager, &parser, vec![original.clone(), copy.clone()], &mut progress)
+            .await
+            .unwrap();
+        assert_eq!(counts, (1, 1));
+
+        let mut paths: Vec<PathBuf> = index_manager
+            .search(&SearchQuery {
+                query: "Stub".to_string(),
+                kind: SearchKind::Exact,
+                filters: vec![],
+                limit: Some(10),
+                offset: None,
+                filter_combine: crate::types::FilterCombine::Any,
+            })
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.file_path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec![original, copy]);
+    }
+
+    #[tokio::test]
+    async fn test_graph_prints_cycles() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let parser = JavaStructureParser::new().unwrap();
+
+        let sources = [
+            ("OrderService", "public class OrderService { private CustomerService customers; }"),
+            ("CustomerService", "public class CustomerService { private OrderService orders; }"),
+            ("AuditLog", "public class AuditLog { private OrderService orders; }"),
+        ];
+        let mut structures = Vec::new();
+        for (name, content) in sources {
+            let java_path = dir.path().join(format!("{}.java", name));
+            std::fs::write(&java_path, content).unwrap();
+            structures.push(parser.parse_structure(&java_path).unwrap());
+        }
+        index_manager.index_java_files(&structures).await.unwrap();
+
+        let graph = build_reference_graph(&index_manager).unwrap();
+        let mut out = Vec::new();
+        write_graph(&mut out, graph, None, GraphFormatArg::Mermaid, true).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "🔁 Found 1 dependency cycles\n  CustomerService -> OrderService -> CustomerService\n"
+        );
+    }
 }
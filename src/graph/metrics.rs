@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::ReferenceGraph;
+
+/// Coupling of one package to the rest of the project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageCoupling {
+    pub package: String,
+    /// Afferent coupling: classes outside the package depending on classes inside it
+    pub afferent: usize,
+    /// Efferent coupling: classes inside the package depending on classes outside it
+    pub efferent: usize,
+    /// Ce / (Ca + Ce), from 0.0 (stable) to 1.0 (unstable); 0.0 without any coupling
+    pub instability: f64,
+}
+
+/// Compute Ca/Ce per package by collapsing class-level edges to packages
+/// Edges to types that are not declared in the project (e.g., library classes) are ignored
+pub fn package_coupling(graph: &ReferenceGraph) -> Vec<PackageCoupling> {
+    let packages = node_packages(graph);
+
+    let mut dependents: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut dependers: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for package in packages.values() {
+        dependents.entry(package).or_default();
+        dependers.entry(package).or_default();
+    }
+
+    for edge in &graph.edges {
+        let Some(from_package) = packages.get(edge.from.as_str()) else {
+            continue;
+        };
+        let Some(target) = resolve_target(&edge.to, from_package, graph, &packages) else {
+            continue;
+        };
+        let to_package = &packages[target];
+        if from_package != to_package {
+            dependents.entry(to_package).or_default().insert(edge.from.as_str());
+            dependers.entry(from_package).or_default().insert(edge.from.as_str());
+        }
+    }
+
+    dependents
+        .into_iter()
+        .map(|(package, incoming)| {
+            let afferent = incoming.len();
+            let efferent = dependers.get(package).map_or(0, |classes| classes.len());
+            let instability = if afferent + efferent == 0 {
+                0.0
+            } else {
+                efferent as f64 / (afferent + efferent) as f64
+            };
+            PackageCoupling {
+                package: package.to_string(),
+                afferent,
+                efferent,
+                instability,
+            }
+        })
+        .collect()
+}
+
+/// Package of every project node, keyed by node id
+pub(super) fn node_packages(graph: &ReferenceGraph) -> HashMap<&str, String> {
+    let ids: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    graph
+        .nodes
+        .iter()
+        .filter(|n| !n.external)
+        .map(|n| (n.id.as_str(), package_of(&n.id, &ids)))
+        .collect()
+}
+
+/// Package of a node id, skipping enclosing classes of nested types
+fn package_of(id: &str, ids: &HashSet<&str>) -> String {
+    let mut prefix = id;
+    while let Some((parent, _)) = prefix.rsplit_once('.') {
+        prefix = parent;
+        if !ids.contains(prefix) {
+            return prefix.to_string();
+        }
+    }
+    String::new()
+}
+
+/// Resolve a raw edge target ("Base", "com.x.Base", "Base<T>") to a project node id,
+/// preferring a type of the same package when the simple name is ambiguous
+pub(super) fn resolve_target<'a>(
+    target: &str,
+    from_package: &str,
+    graph: &'a ReferenceGraph,
+    packages: &HashMap<&str, String>,
+) -> Option<&'a str> {
+    let erased = erase_generics(target);
+    let project_nodes = || graph.nodes.iter().filter(|n| !n.external);
+    if let Some(node) = project_nodes().find(|n| n.id == erased) {
+        return Some(node.id.as_str());
+    }
+
+    let simple_name = erased.rsplit('.').next().unwrap_or(erased);
+    let candidates: Vec<&str> = project_nodes()
+        .filter(|n| n.label == simple_name)
+        .map(|n| n.id.as_str())
+        .collect();
+
+    match candidates.as_slice() {
+        [single] => Some(single),
+        _ => candidates
+            .into_iter()
+            .find(|id| packages.get(id).is_some_and(|p| p == from_package)),
+    }
+}
+
+/// "Base<T>" -> "Base"
+pub(super) fn erase_generics(type_name: &str) -> &str {
+    type_name.split('<').next().unwrap_or(type_name).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphBuilder;
+    use crate::test_support::parse_sources;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_package_coupling() {
+        let sources = [
+            ("BaseController.java", "package com.app.core;\npublic abstract class BaseController {}\n"),
+            ("Auditable.java", "package com.app.core;\npublic interface Auditable {}\n"),
+            (
+                "UserController.java",
+                "package com.app.web;\npublic class UserController extends BaseController implements Auditable {\n    public static class Form {}\n}\n",
+            ),
+            ("AdminController.java", "package com.app.web;\npublic class AdminController extends BaseController {}\n"),
+        ];
+
+        let dir = tempdir().unwrap();
+        let mut builder = GraphBuilder::new();
+        for structure in parse_sources(dir.path(), &sources) {
+            builder.add_structure(&structure);
+        }
+
+        let coupling = package_coupling(&builder.build());
+        assert_eq!(coupling.len(), 2);
+
+        let core = &coupling[0];
+        assert_eq!(core.package, "com.app.core");
+        assert_eq!((core.afferent, core.efferent), (2, 0));
+        assert_eq!(core.instability, 0.0);
+
+        // The nested Form class belongs to com.app.web, not to a "com.app.web.UserController" package
+        let web = &coupling[1];
+        assert_eq!(web.package, "com.app.web");
+        assert_eq!((web.afferent, web.efferent), (0, 2));
+        assert_eq!(web.instability, 1.0);
+    }
+}
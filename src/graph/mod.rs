@@ -0,0 +1,634 @@
+mod metrics;
+mod visualizer;
+
+pub use metrics::*;
+pub use visualizer::*;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use crate::parser::{ClassKind, ClassStructure, JavaStructurePreview};
+use crate::types::{Declaration, DeclarationKind, GraphEdge, GraphNode, ReferenceGraph, RelationshipType};
+
+/// Builds a ReferenceGraph from parsed Java files
+/// Every class (nested ones included) becomes a node, and its inheritance becomes edges
+#[derive(Clone)]
+pub struct GraphBuilder {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+    /// Types named by the fields and methods of each declaration, keyed by node id
+    type_references: HashMap<String, BTreeSet<String>>,
+    /// Type names never recorded as Uses references, by simple or qualified name
+    denylist: HashSet<String>,
+}
+
+/// Types that never become graph nodes
+const PRIMITIVE_TYPES: &[&str] = &[
+    "void", "boolean", "byte", "char", "short", "int", "long", "float", "double", "var",
+];
+
+/// JDK types used almost everywhere, left out of Uses references unless the denylist is replaced
+pub const DEFAULT_DENYLIST: &[&str] = &[
+    "Object", "String", "Integer", "Long", "Short", "Byte", "Double", "Float", "Boolean", "Character",
+    "Number", "Void", "CharSequence", "Class", "Optional", "Collection", "List", "ArrayList", "Set",
+    "HashSet", "Map", "HashMap", "Iterable", "Iterator", "Stream",
+];
+
+impl GraphBuilder {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            type_references: HashMap::new(),
+            denylist: DEFAULT_DENYLIST.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    /// Replace the default denylist; pass an empty list to keep every reference
+    pub fn with_denylist<I, S>(mut self, denylist: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.denylist = denylist.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn is_denylisted(&self, type_name: &str) -> bool {
+        let simple_name = type_name.rsplit('.').next().unwrap_or(type_name);
+        self.denylist.contains(type_name) || self.denylist.contains(simple_name)
+    }
+
+    /// Add all classes of one parsed file to the graph
+    pub fn add_structure(&mut self, java_structure: &JavaStructurePreview) {
+        for class in &java_structure.top_level_classes {
+            self.add_class(class, java_structure);
+        }
+
+        self.add_sealed_edges(java_structure);
+    }
+
+    /// Add a class and, recursively, its nested types linked by Contains edges
+    fn add_class(&mut self, class: &ClassStructure, java_structure: &JavaStructurePreview) {
+        self.nodes.push(GraphNode {
+            id: class.fqn.clone(),
+            label: class.name.clone(),
+            kind: Self::declaration_kind(class.kind),
+            file_path: java_structure.file_meta.path.clone(),
+            external: false,
+        });
+
+        if let Some(extends) = &class.extends {
+            self.add_edge(&class.fqn, extends, RelationshipType::Extends);
+        }
+
+        for implements in &class.implements {
+            self.add_edge(&class.fqn, implements, RelationshipType::Implements);
+        }
+
+        for nested in &class.nested_classes {
+            self.add_edge(&class.fqn, &nested.fqn, RelationshipType::Contains);
+            self.add_class(nested, java_structure);
+        }
+    }
+
+    /// Add a declaration with its inheritance plus one Uses edge per type named by its
+    /// field types, method return types and parameter types, generic arguments included
+    /// ("List<User>" uses both List and User)
+    pub fn add_declaration_with_references(&mut self, declaration: &Declaration, file_path: &Path) {
+        let id = declaration.fqn();
+        self.nodes.push(GraphNode {
+            id: id.clone(),
+            label: declaration.name.clone(),
+            kind: declaration.kind,
+            file_path: file_path.to_path_buf(),
+            external: false,
+        });
+
+        if let Some(extends) = &declaration.extends {
+            self.add_edge(&id, extends, RelationshipType::Extends);
+        }
+        for implements in &declaration.implements {
+            self.add_edge(&id, implements, RelationshipType::Implements);
+        }
+
+        let type_names = declaration
+            .fields
+            .iter()
+            .map(|f| f.type_name.as_str())
+            .chain(declaration.methods.iter().flat_map(|m| {
+                std::iter::once(m.return_type.as_str()).chain(m.parameters.iter().map(|p| p.type_name.as_str()))
+            }));
+
+        let mut references = BTreeSet::new();
+        for type_name in type_names {
+            references.extend(Self::referenced_types(type_name));
+        }
+        references.remove(&declaration.name);
+        references.retain(|reference| !self.is_denylisted(reference));
+
+        for reference in &references {
+            self.add_edge(&id, reference, RelationshipType::Uses);
+        }
+        self.type_references.entry(id).or_default().extend(references);
+    }
+
+    /// Types referenced by a declaration added through add_declaration_with_references
+    pub fn type_references(&self, id: &str) -> Option<&BTreeSet<String>> {
+        self.type_references.get(id)
+    }
+
+    /// Every class or interface named in a type ("Map<String, List<User>>" -> Map, String, List, User)
+    fn referenced_types(type_name: &str) -> Vec<String> {
+        type_name
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$' || c == '.'))
+            .map(|name| name.trim_matches('.'))
+            .filter(|name| !name.is_empty() && !matches!(*name, "extends" | "super"))
+            .filter(|name| !PRIMITIVE_TYPES.contains(name) && *name != crate::parser::UNKNOWN_TYPE)
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    /// Rewrite edge targets, which are type names as written in the source, to node ids
+    /// Types not declared in the project become nodes marked as external
+    pub fn build(self) -> ReferenceGraph {
+        let mut graph = ReferenceGraph {
+            nodes: self.nodes,
+            edges: Vec::new(),
+        };
+
+        let packages = metrics::node_packages(&graph);
+        let mut resolved = Vec::with_capacity(self.edges.len());
+        let mut external = Vec::new();
+        for mut edge in self.edges {
+            let from_package = packages.get(edge.from.as_str()).map_or("", String::as_str);
+            match metrics::resolve_target(&edge.to, from_package, &graph, &packages) {
+                Some(id) => edge.to = id.to_string(),
+                None => {
+                    edge.to = metrics::erase_generics(&edge.to).to_string();
+                    external.push((edge.to.clone(), edge.relationship));
+                }
+            }
+            resolved.push(edge);
+        }
+
+        let mut seen = HashSet::new();
+        for (id, relationship) in external {
+            if seen.insert(id.clone()) {
+                graph.nodes.push(GraphNode {
+                    label: Self::simple_type_name(&id).to_string(),
+                    kind: match relationship {
+                        RelationshipType::Implements => DeclarationKind::Interface,
+                        _ => DeclarationKind::Class,
+                    },
+                    file_path: Default::default(),
+                    external: true,
+                    id,
+                });
+            }
+        }
+
+        graph.edges = resolved;
+        graph
+    }
+
+    /// Collapse types into their packages, with one DependsOn edge per pair of packages
+    /// where a type of the first references a type of the second
+    /// External types are left out; package nodes reuse DeclarationKind::Class
+    pub fn build_package_graph(&self) -> ReferenceGraph {
+        let types = self.clone().build();
+        let packages = metrics::node_packages(&types);
+
+        let mut package_nodes: BTreeMap<&str, GraphNode> = BTreeMap::new();
+        for node in types.nodes.iter().filter(|n| !n.external) {
+            let package = packages[node.id.as_str()].as_str();
+            package_nodes.entry(package).or_insert_with(|| GraphNode {
+                id: package.to_string(),
+                label: if package.is_empty() { "(default)".to_string() } else { package.to_string() },
+                kind: DeclarationKind::Class,
+                file_path: node.file_path.parent().map(Into::into).unwrap_or_default(),
+                external: false,
+            });
+        }
+
+        let dependencies: BTreeSet<(&str, &str)> = types
+            .edges
+            .iter()
+            .filter_map(|e| {
+                let from = packages.get(e.from.as_str())?;
+                let to = packages.get(e.to.as_str())?;
+                Some((from.as_str(), to.as_str()))
+            })
+            .filter(|(from, to)| from != to)
+            .collect();
+
+        ReferenceGraph {
+            nodes: package_nodes.into_values().collect(),
+            edges: dependencies
+                .into_iter()
+                .map(|(from, to)| GraphEdge {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    relationship: RelationshipType::DependsOn,
+                })
+                .collect(),
+        }
+    }
+
+    /// Graph of the calls between the methods of `classes` (nested types included), with one
+    /// node per method named "Type#method" (overloads share a node) and Calls edges. A call
+    /// resolves to a method of the calling type first, then to the only method of that name
+    /// among the classes; other calls go to an external node named after the method.
+    pub fn build_call_graph(classes: &[ClassStructure]) -> ReferenceGraph {
+        let mut all_classes = Vec::new();
+        Self::collect_classes(classes, &mut all_classes);
+        let method_id = |class: &ClassStructure, method: &str| format!("{}#{}", class.fqn, method);
+
+        let mut owners: HashMap<&str, Vec<&ClassStructure>> = HashMap::new();
+        let mut graph = ReferenceGraph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        for class in &all_classes {
+            for method in &class.methods {
+                let id = method_id(class, &method.name);
+                if seen.insert(id.clone()) {
+                    owners.entry(method.name.as_str()).or_default().push(class);
+                    graph.nodes.push(GraphNode {
+                        id,
+                        label: format!("{}.{}", class.name, method.name),
+                        kind: DeclarationKind::Method,
+                        file_path: Default::default(),
+                        external: false,
+                    });
+                }
+            }
+        }
+
+        let mut edges = BTreeSet::new();
+        let mut external = BTreeSet::new();
+        for class in &all_classes {
+            for method in &class.methods {
+                for call in &method.calls {
+                    let candidates = owners.get(call.as_str()).map_or(&[][..], Vec::as_slice);
+                    let target = if candidates.iter().any(|c| c.fqn == class.fqn) {
+                        method_id(class, call)
+                    } else if let [owner] = candidates {
+                        method_id(owner, call)
+                    } else {
+                        external.insert(call.clone());
+                        call.clone()
+                    };
+                    edges.insert((method_id(class, &method.name), target));
+                }
+            }
+        }
+
+        graph.nodes.extend(external.into_iter().map(|name| GraphNode {
+            id: name.clone(),
+            label: name,
+            kind: DeclarationKind::Method,
+            file_path: Default::default(),
+            external: true,
+        }));
+        graph.edges = edges
+            .into_iter()
+            .map(|(from, to)| GraphEdge {
+                from,
+                to,
+                relationship: RelationshipType::Calls,
+            })
+            .collect();
+        graph
+    }
+
+    /// A sealed type without a permits clause implicitly permits the subtypes
+    /// declared in the same file, so link it to each of them
+    fn add_sealed_edges(&mut self, java_structure: &JavaStructurePreview) {
+        let mut classes = Vec::new();
+        Self::collect_classes(&java_structure.top_level_classes, &mut classes);
+
+        for sealed in classes.iter().filter(|c| c.modifiers.iter().any(|m| m == "sealed")) {
+            for subtype in &classes {
+                let is_subtype = subtype
+                    .extends
+                    .iter()
+                    .chain(subtype.implements.iter())
+                    .any(|parent| Self::simple_type_name(parent) == sealed.name);
+
+                if is_subtype {
+                    self.add_edge(&sealed.fqn, &subtype.fqn, RelationshipType::Permits);
+                }
+            }
+        }
+    }
+
+    fn collect_classes<'a>(classes: &'a [ClassStructure], out: &mut Vec<&'a ClassStructure>) {
+        for class in classes {
+            out.push(class);
+            Self::collect_classes(&class.nested_classes, out);
+        }
+    }
+
+    fn add_edge(&mut self, from: &str, to: &str, relationship: RelationshipType) {
+        self.edges.push(GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            relationship,
+        });
+    }
+
+    /// Strip generic arguments and qualifiers ("a.b.Shape<T>" -> "Shape")
+    fn simple_type_name(type_name: &str) -> &str {
+        let base = type_name.split('<').next().unwrap_or(type_name).trim();
+        base.rsplit('.').next().unwrap_or(base)
+    }
+
+    fn declaration_kind(kind: ClassKind) -> DeclarationKind {
+        match kind {
+            ClassKind::Class => DeclarationKind::Class,
+            ClassKind::Interface => DeclarationKind::Interface,
+            ClassKind::Enum => DeclarationKind::Enum,
+            ClassKind::Record => DeclarationKind::Record,
+            ClassKind::Annotation => DeclarationKind::Annotation,
+        }
+    }
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse_sources;
+    use crate::types::{Field, Method, Parameter, SourceRange};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_sealed_permits_edges() {
+        let java_content = r#"
+            package com.example.shapes;
+
+            public sealed class Shape {}
+
+            final class Circle extends Shape {}
+
+            non-sealed class Square extends Shape {}
+        "#;
+
+        let dir = tempdir().unwrap();
+        let structure = parse_sources(dir.path(), &[("Shape.java", java_content)]).remove(0);
+        let mut builder = GraphBuilder::new();
+        builder.add_structure(&structure);
+        let graph = builder.build();
+
+        assert_eq!(graph.nodes.len(), 3);
+
+        let permits: Vec<&GraphEdge> = graph
+            .edges
+            .iter()
+            .filter(|e| e.relationship == RelationshipType::Permits)
+            .collect();
+        assert_eq!(permits.len(), 2);
+        assert!(permits.iter().all(|e| e.from == "com.example.shapes.Shape"));
+        assert!(permits.iter().any(|e| e.to == "com.example.shapes.Circle"));
+        assert!(permits.iter().any(|e| e.to == "com.example.shapes.Square"));
+
+        let extends = graph
+            .edges
+            .iter()
+            .filter(|e| e.relationship == RelationshipType::Extends)
+            .count();
+        assert_eq!(extends, 2);
+    }
+
+    #[test]
+    fn test_nested_type_nodes() {
+        let java_content = r#"
+            package com.example.orders;
+
+            public class Order {
+                public record LineItem(String sku, int quantity) {}
+
+                public enum Status { OPEN, SHIPPED }
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let structure = parse_sources(dir.path(), &[("Order.java", java_content)]).remove(0);
+        let mut builder = GraphBuilder::new();
+        builder.add_structure(&structure);
+        let graph = builder.build();
+
+        assert_eq!(graph.nodes.len(), 3);
+        let record = graph
+            .nodes
+            .iter()
+            .find(|n| n.id == "com.example.orders.Order.LineItem")
+            .expect("nested record should be a node");
+        assert_eq!(record.kind, DeclarationKind::Record);
+        assert!(graph.nodes.iter().any(|n| n.id == "com.example.orders.Order.Status" && n.kind == DeclarationKind::Enum));
+
+        let contains: Vec<&GraphEdge> = graph
+            .edges
+            .iter()
+            .filter(|e| e.relationship == RelationshipType::Contains)
+            .collect();
+        assert_eq!(contains.len(), 2);
+        assert!(contains.iter().all(|e| e.from == "com.example.orders.Order"));
+    }
+
+    #[test]
+    fn test_edges_resolve_to_node_ids() {
+        let sources = [
+            ("BaseService.java", "package com.example.core;\npublic abstract class BaseService {}\n"),
+            (
+                "UserService.java",
+                "package com.example.user;\nimport com.example.core.BaseService;\npublic class UserService extends BaseService implements Serializable {}\n",
+            ),
+        ];
+
+        let dir = tempdir().unwrap();
+        let mut builder = GraphBuilder::new();
+        for structure in parse_sources(dir.path(), &sources) {
+            builder.add_structure(&structure);
+        }
+        let graph = builder.build();
+
+        let extends = graph
+            .edges
+            .iter()
+            .find(|e| e.relationship == RelationshipType::Extends)
+            .unwrap();
+        assert_eq!(extends.from, "com.example.user.UserService");
+        assert_eq!(extends.to, "com.example.core.BaseService");
+
+        // Library types are kept as external nodes so that no edge dangles
+        let serializable = graph.nodes.iter().find(|n| n.id == "Serializable").unwrap();
+        assert!(serializable.external);
+        assert_eq!(serializable.kind, DeclarationKind::Interface);
+        assert_eq!(graph.nodes.iter().filter(|n| !n.external).count(), 2);
+        assert!(graph.edges.iter().all(|e| graph.nodes.iter().any(|n| n.id == e.to)));
+    }
+
+    #[test]
+    fn test_package_graph() {
+        let sources = [
+            ("Repository.java", "package com.b;\npublic interface Repository {}\n"),
+            ("Entity.java", "package com.b;\npublic abstract class Entity {}\n"),
+            ("User.java", "package com.a;\npublic class User extends Entity {}\n"),
+            ("UserRepository.java", "package com.a;\npublic class UserRepository implements Repository {}\n"),
+        ];
+
+        let dir = tempdir().unwrap();
+        let mut builder = GraphBuilder::new();
+        for structure in parse_sources(dir.path(), &sources) {
+            builder.add_structure(&structure);
+        }
+
+        let graph = builder.build_package_graph();
+
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["com.a", "com.b"]);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].from, "com.a");
+        assert_eq!(graph.edges[0].to, "com.b");
+        assert_eq!(graph.edges[0].relationship, RelationshipType::DependsOn);
+    }
+
+    fn declaration(name: &str, fields: Vec<(&str, &str)>, methods: Vec<Method>) -> Declaration {
+        Declaration {
+            name: name.to_string(),
+            kind: DeclarationKind::Class,
+            modifiers: vec!["public".to_string()],
+            annotations: vec![],
+            signature: format!("public class {}", name),
+            extends: None,
+            implements: vec![],
+            fields: fields
+                .into_iter()
+                .map(|(name, type_name)| Field {
+                    name: name.to_string(),
+                    type_name: type_name.to_string(),
+                    modifiers: vec!["private".to_string()],
+                    annotations: vec![],
+                })
+                .collect(),
+            methods,
+            range: SourceRange { start_line: 1, start_column: 1, end_line: 20, end_column: 1 },
+            documentation: None,
+            package: "com.example".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_uses_edges_from_member_types() {
+        let find_all = Method {
+            name: "findAll".to_string(),
+            return_type: "List<User>".to_string(),
+            parameters: vec![Parameter {
+                name: "limit".to_string(),
+                type_name: "int".to_string(),
+                annotations: vec![],
+            }],
+            modifiers: vec!["public".to_string()],
+            annotations: vec![],
+            throws: vec![],
+            range: SourceRange { start_line: 5, start_column: 5, end_line: 7, end_column: 5 },
+            body_range: None,
+            complexity: None,
+        };
+
+        let mut builder = GraphBuilder::new().with_denylist(Vec::<String>::new());
+        let path = Path::new("UserService.java");
+        builder.add_declaration_with_references(
+            &declaration("UserService", vec![("userRepository", "UserRepository")], vec![find_all]),
+            path,
+        );
+        builder.add_declaration_with_references(&declaration("UserRepository", vec![], vec![]), path);
+        builder.add_declaration_with_references(&declaration("User", vec![], vec![]), path);
+
+        let references: Vec<&str> = builder
+            .type_references("com.example.UserService")
+            .unwrap()
+            .iter()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(references, vec!["List", "User", "UserRepository"]);
+
+        let graph = builder.build();
+        let uses: Vec<&str> = graph
+            .edges
+            .iter()
+            .filter(|e| e.from == "com.example.UserService" && e.relationship == RelationshipType::Uses)
+            .map(|e| e.to.as_str())
+            .collect();
+        assert_eq!(uses, vec!["List", "com.example.User", "com.example.UserRepository"]);
+
+        let service = graph.nodes.iter().find(|n| n.id == "com.example.UserService").unwrap();
+        assert_eq!(service.label, "UserService");
+        assert_eq!(service.file_path, path);
+        assert!(graph.nodes.iter().any(|n| n.id == "List" && n.external));
+    }
+
+    #[test]
+    fn test_denylisted_types_are_not_references() {
+        let service = declaration("OrderService", vec![("name", "String"), ("orders", "OrderRepository")], vec![]);
+        let path = Path::new("OrderService.java");
+
+        let mut builder = GraphBuilder::new();
+        builder.add_declaration_with_references(&service, path);
+        let uses: Vec<String> = builder.build().edges.into_iter().map(|e| e.to).collect();
+        assert_eq!(uses, vec!["OrderRepository"]);
+
+        // An overridden denylist keeps String, and drops the project type
+        let mut builder = GraphBuilder::new().with_denylist(["OrderRepository"]);
+        builder.add_declaration_with_references(&service, path);
+        let uses: Vec<String> = builder.build().edges.into_iter().map(|e| e.to).collect();
+        assert_eq!(uses, vec!["String"]);
+    }
+
+    #[test]
+    fn test_call_graph() {
+        let java_content = r#"
+            package com.example.billing;
+
+            public class Invoicer {
+                public void issue(Invoice invoice) {
+                    total(invoice);
+                    mailer.send(invoice);
+                    System.out.println(invoice);
+                }
+
+                private int total(Invoice invoice) { return 0; }
+            }
+
+            class Mailer {
+                void send(Invoice invoice) {}
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let structure = parse_sources(dir.path(), &[("Invoicer.java", java_content)]).remove(0);
+        let graph = GraphBuilder::build_call_graph(&structure.top_level_classes);
+
+        let calls: Vec<(&str, &str)> = graph.edges.iter().map(|e| (e.from.as_str(), e.to.as_str())).collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("com.example.billing.Invoicer#issue", "com.example.billing.Invoicer#total"),
+                ("com.example.billing.Invoicer#issue", "com.example.billing.Mailer#send"),
+                ("com.example.billing.Invoicer#issue", "println"),
+            ]
+        );
+        assert!(graph.edges.iter().all(|e| e.relationship == RelationshipType::Calls));
+        assert!(graph.nodes.iter().all(|n| n.kind == DeclarationKind::Method));
+        assert!(graph.nodes.iter().any(|n| n.id == "println" && n.external));
+        assert_eq!(graph.nodes.iter().filter(|n| !n.external).count(), 3);
+    }
+}
@@ -0,0 +1,721 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::types::{GraphEdge, GraphNode, ReferenceGraph, RelationshipType};
+
+/// Failures of the Graphviz rendering
+#[derive(Debug, Error)]
+pub enum GraphvizError {
+    /// The `dot` binary is not on PATH; callers can fall back to the DOT source
+    #[error("graphviz `dot` binary not found on PATH")]
+    NotFound,
+    #[error("graphviz `dot` failed ({status}): {stderr}")]
+    Failed { status: String, stderr: String },
+    #[error("graphviz I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Overview numbers of a reference graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphSummary {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// Groups of two or more types that all reach each other (dependency cycles)
+    pub strongly_connected_components: usize,
+    /// Nodes with the highest degree centrality, most central first (hubs, god classes)
+    #[serde(default)]
+    pub top_central_nodes: Vec<(String, f64)>,
+}
+
+/// Number of nodes listed in GraphSummary::top_central_nodes
+pub const TOP_CENTRAL_NODES: usize = 5;
+
+/// Changes between two versions of a reference graph; nodes are matched by id and edges by
+/// (from, to, relationship), each list keeping the order of the graph it comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<GraphNode>,
+    pub removed_nodes: Vec<GraphNode>,
+    pub added_edges: Vec<GraphEdge>,
+    pub removed_edges: Vec<GraphEdge>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+/// Renders and summarizes a ReferenceGraph
+pub struct GraphVisualizer {
+    graph: ReferenceGraph,
+}
+
+impl GraphVisualizer {
+    pub fn new(graph: ReferenceGraph) -> Self {
+        Self { graph }
+    }
+
+    pub fn graph(&self) -> &ReferenceGraph {
+        &self.graph
+    }
+
+    /// Graphviz DOT source of the graph
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph references {\n    rankdir=LR;\n    node [shape=box];\n");
+        for node in &self.graph.nodes {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\"];",
+                escape_dot(&node.id),
+                escape_dot(&node.label)
+            );
+        }
+        for edge in &self.graph.edges {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape_dot(&edge.from),
+                escape_dot(&edge.to),
+                relationship_label(&edge.relationship)
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render the graph to SVG by piping `to_dot` through `dot -Tsvg`
+    #[cfg(feature = "graphviz")]
+    pub fn to_svg(&self) -> Result<String, GraphvizError> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let mut child = Command::new("dot")
+            .arg("-Tsvg")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => GraphvizError::NotFound,
+                _ => GraphvizError::Io(e),
+            })?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(self.to_dot().as_bytes())?;
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(GraphvizError::Failed {
+                status: output.status.to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// What changed from `old` to `new`, e.g. the dependencies a branch introduced
+    pub fn diff(old: &ReferenceGraph, new: &ReferenceGraph) -> GraphDiff {
+        let node_ids = |graph: &ReferenceGraph| -> HashSet<String> { graph.nodes.iter().map(|n| n.id.clone()).collect() };
+        let edge_keys = |graph: &ReferenceGraph| -> HashSet<(String, String, RelationshipType)> {
+            graph.edges.iter().map(|e| (e.from.clone(), e.to.clone(), e.relationship)).collect()
+        };
+        let (old_nodes, new_nodes) = (node_ids(old), node_ids(new));
+        let (old_edges, new_edges) = (edge_keys(old), edge_keys(new));
+
+        let missing_edges = |graph: &ReferenceGraph, other: &HashSet<(String, String, RelationshipType)>| {
+            graph
+                .edges
+                .iter()
+                .filter(|e| !other.contains(&(e.from.clone(), e.to.clone(), e.relationship)))
+                .cloned()
+                .collect()
+        };
+        GraphDiff {
+            added_nodes: new.nodes.iter().filter(|n| !old_nodes.contains(&n.id)).cloned().collect(),
+            removed_nodes: old.nodes.iter().filter(|n| !new_nodes.contains(&n.id)).cloned().collect(),
+            added_edges: missing_edges(new, &old_edges),
+            removed_edges: missing_edges(old, &new_edges),
+        }
+    }
+
+    pub fn summary(&self) -> GraphSummary {
+        GraphSummary {
+            node_count: self.graph.nodes.len(),
+            edge_count: self.graph.edges.len(),
+            strongly_connected_components: self.count_strongly_connected_components(),
+            top_central_nodes: self.top_central_nodes(TOP_CENTRAL_NODES),
+        }
+    }
+
+    /// Degree centrality of each node: the number of distinct nodes it links to plus those
+    /// linking to it, divided by 2 * (n - 1) so it ranges from 0 to 1. Self-loops and edges
+    /// to types outside the graph are ignored.
+    pub fn compute_centrality(&self) -> HashMap<String, f64> {
+        let successors = self.successors();
+        let mut degree = vec![0usize; self.graph.nodes.len()];
+        for (from, targets) in successors.iter().enumerate() {
+            let distinct: HashSet<usize> = targets.iter().copied().filter(|&to| to != from).collect();
+            degree[from] += distinct.len();
+            for to in distinct {
+                degree[to] += 1;
+            }
+        }
+
+        let scale = 2.0 * self.graph.nodes.len().saturating_sub(1).max(1) as f64;
+        self.graph
+            .nodes
+            .iter()
+            .zip(degree)
+            .map(|(node, degree)| (node.id.clone(), degree as f64 / scale))
+            .collect()
+    }
+
+    /// The `count` most central nodes, ties broken by id
+    fn top_central_nodes(&self, count: usize) -> Vec<(String, f64)> {
+        let mut ranked: Vec<(String, f64)> = self.compute_centrality().into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked.truncate(count);
+        ranked
+    }
+
+    /// Number of strongly connected components with more than one node, found
+    /// with Tarjan's algorithm. Single nodes are not counted, even with a
+    /// self-loop, and edges to types outside the graph are ignored.
+    pub fn count_strongly_connected_components(&self) -> usize {
+        self.strongly_connected_components()
+            .iter()
+            .filter(|component| component.len() > 1)
+            .count()
+    }
+
+    /// All strongly connected components as lists of node ids (Tarjan's algorithm,
+    /// iterative so that deep inheritance chains cannot overflow the stack)
+    pub fn strongly_connected_components(&self) -> Vec<Vec<String>> {
+        let successors = self.successors();
+        let node_count = successors.len();
+        let mut index = vec![usize::MAX; node_count];
+        let mut low_link = vec![0; node_count];
+        let mut on_stack = vec![false; node_count];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut components = Vec::new();
+
+        for root in 0..node_count {
+            if index[root] != usize::MAX {
+                continue;
+            }
+
+            // (node, position of the next successor to visit)
+            let mut call_stack = vec![(root, 0)];
+            index[root] = next_index;
+            low_link[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&mut (node, ref mut next)) = call_stack.last_mut() {
+                if let Some(&successor) = successors[node].get(*next) {
+                    *next += 1;
+                    if index[successor] == usize::MAX {
+                        index[successor] = next_index;
+                        low_link[successor] = next_index;
+                        next_index += 1;
+                        stack.push(successor);
+                        on_stack[successor] = true;
+                        call_stack.push((successor, 0));
+                    } else if on_stack[successor] {
+                        low_link[node] = low_link[node].min(index[successor]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+
+                if low_link[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(self.graph.nodes[member].id.clone());
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// One dependency cycle per strongly connected component of two or more nodes,
+    /// as the node ids along the cycle starting from the smallest id
+    /// (["A", "B", "C"] stands for A -> B -> C -> A)
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        let index_of: HashMap<&str, usize> = self.node_indices();
+        let successors = self.successors();
+
+        let mut cycles: Vec<Vec<String>> = self
+            .strongly_connected_components()
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .filter_map(|component| {
+                let members: HashSet<usize> = component.iter().map(|id| index_of[id.as_str()]).collect();
+                let start = component.iter().min().map(|id| index_of[id.as_str()])?;
+
+                // Shortest way back to the start without leaving the component
+                let mut previous: HashMap<usize, usize> = HashMap::new();
+                let mut queue = VecDeque::from([start]);
+                while let Some(node) = queue.pop_front() {
+                    for &successor in &successors[node] {
+                        if successor == start {
+                            let mut path = vec![node];
+                            while let Some(&before) = previous.get(path.last().unwrap()) {
+                                path.push(before);
+                            }
+                            path.reverse();
+                            return Some(path.into_iter().map(|i| self.graph.nodes[i].id.clone()).collect());
+                        }
+                        if members.contains(&successor) && successor != start && !previous.contains_key(&successor) {
+                            previous.insert(successor, node);
+                            queue.push_back(successor);
+                        }
+                    }
+                }
+                None
+            })
+            .collect();
+
+        cycles.sort();
+        cycles
+    }
+
+    /// The node matching `focus` (by id or label) with its direct dependencies and dependents
+    pub fn generate_focused_graph(&self, focus: &str) -> ReferenceGraph {
+        let focus_ids: HashSet<&str> = self
+            .graph
+            .nodes
+            .iter()
+            .filter(|n| n.id == focus || n.label == focus)
+            .map(|n| n.id.as_str())
+            .collect();
+
+        let edges: Vec<GraphEdge> = self
+            .graph
+            .edges
+            .iter()
+            .filter(|e| focus_ids.contains(e.from.as_str()) || focus_ids.contains(e.to.as_str()))
+            .cloned()
+            .collect();
+
+        let mut kept: HashSet<&str> = focus_ids.clone();
+        for edge in &edges {
+            kept.insert(edge.from.as_str());
+            kept.insert(edge.to.as_str());
+        }
+
+        ReferenceGraph {
+            nodes: self
+                .graph
+                .nodes
+                .iter()
+                .filter(|n| kept.contains(n.id.as_str()))
+                .cloned()
+                .collect(),
+            edges,
+        }
+    }
+
+    /// Mermaid flowchart of the graph; node ids are replaced by n0, n1, ...
+    /// since Mermaid does not accept dots in them
+    pub fn to_mermaid(&self) -> String {
+        let index_of = self.node_indices();
+        let mut mermaid = String::from("graph LR\n");
+        for (i, node) in self.graph.nodes.iter().enumerate() {
+            let _ = writeln!(mermaid, "    n{}[\"{}\"]", i, node.label.replace('"', "#quot;"));
+        }
+        for edge in &self.graph.edges {
+            if let (Some(from), Some(to)) = (index_of.get(edge.from.as_str()), index_of.get(edge.to.as_str())) {
+                let _ = writeln!(
+                    mermaid,
+                    "    n{} -->|{}| n{}",
+                    from,
+                    relationship_label(&edge.relationship),
+                    to
+                );
+            }
+        }
+        mermaid
+    }
+
+    /// GraphML document of the graph (for Gephi, yEd, ...), with each node's `label` and
+    /// `kind` and each edge's `relationship` as data attributes. Edges to types outside the
+    /// graph are left out, since GraphML edges must connect declared nodes.
+    pub fn to_graphml(&self) -> String {
+        let mut graphml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"relationship\" for=\"edge\" attr.name=\"relationship\" attr.type=\"string\"/>\n",
+            "  <graph id=\"references\" edgedefault=\"directed\">\n",
+        ));
+        for node in &self.graph.nodes {
+            let _ = writeln!(
+                graphml,
+                "    <node id=\"{}\"><data key=\"label\">{}</data><data key=\"kind\">{}</data></node>",
+                escape_xml(&node.id),
+                escape_xml(&node.label),
+                format!("{:?}", node.kind).to_lowercase()
+            );
+        }
+        let index_of = self.node_indices();
+        let edges = self
+            .graph
+            .edges
+            .iter()
+            .filter(|e| index_of.contains_key(e.from.as_str()) && index_of.contains_key(e.to.as_str()));
+        for (i, edge) in edges.enumerate() {
+            let _ = writeln!(
+                graphml,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"><data key=\"relationship\">{}</data></edge>",
+                i,
+                escape_xml(&edge.from),
+                escape_xml(&edge.to),
+                relationship_label(&edge.relationship)
+            );
+        }
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+
+    /// Cytoscape.js elements JSON (`{"elements": {"nodes": [{"data": ...}], "edges": [...]}}`),
+    /// also easy to map onto D3. Edges use `source`/`target` and, like in GraphML, edges to
+    /// types outside the graph are left out.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let nodes = self
+            .graph
+            .nodes
+            .iter()
+            .map(|node| CytoscapeElement {
+                data: CytoscapeData::Node {
+                    id: &node.id,
+                    label: &node.label,
+                    kind: format!("{:?}", node.kind).to_lowercase(),
+                    external: node.external,
+                },
+            })
+            .collect();
+
+        let index_of = self.node_indices();
+        let edges = self
+            .graph
+            .edges
+            .iter()
+            .filter(|e| index_of.contains_key(e.from.as_str()) && index_of.contains_key(e.to.as_str()))
+            .enumerate()
+            .map(|(i, edge)| CytoscapeElement {
+                data: CytoscapeData::Edge {
+                    id: format!("e{}", i),
+                    source: &edge.from,
+                    target: &edge.to,
+                    relationship: relationship_label(&edge.relationship),
+                },
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&CytoscapeGraph {
+            elements: CytoscapeElements { nodes, edges },
+        })
+    }
+
+    fn node_indices(&self) -> HashMap<&str, usize> {
+        self.graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.as_str(), i))
+            .collect()
+    }
+
+    /// Adjacency lists by node index, ignoring edges to types outside the graph
+    fn successors(&self) -> Vec<Vec<usize>> {
+        let index_of = self.node_indices();
+        let mut successors = vec![Vec::new(); self.graph.nodes.len()];
+        for edge in &self.graph.edges {
+            let from = index_of.get(edge.from.as_str());
+            let to = index_of.get(edge.to.as_str());
+            if let (Some(&from), Some(&to)) = (from, to) {
+                successors[from].push(to);
+            }
+        }
+        successors
+    }
+}
+
+#[derive(Serialize)]
+struct CytoscapeGraph<'a> {
+    elements: CytoscapeElements<'a>,
+}
+
+#[derive(Serialize)]
+struct CytoscapeElements<'a> {
+    nodes: Vec<CytoscapeElement<'a>>,
+    edges: Vec<CytoscapeElement<'a>>,
+}
+
+#[derive(Serialize)]
+struct CytoscapeElement<'a> {
+    data: CytoscapeData<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum CytoscapeData<'a> {
+    Node {
+        id: &'a str,
+        label: &'a str,
+        kind: String,
+        external: bool,
+    },
+    Edge {
+        id: String,
+        source: &'a str,
+        target: &'a str,
+        relationship: &'static str,
+    },
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn relationship_label(relationship: &RelationshipType) -> &'static str {
+    match relationship {
+        RelationshipType::Extends => "extends",
+        RelationshipType::Implements => "implements",
+        RelationshipType::Uses => "uses",
+        RelationshipType::References => "references",
+        RelationshipType::DependsOn => "depends on",
+        RelationshipType::Permits => "permits",
+        RelationshipType::Contains => "contains",
+        RelationshipType::Calls => "calls",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DeclarationKind;
+    use std::path::PathBuf;
+
+    fn node(id: &str) -> GraphNode {
+        GraphNode {
+            id: id.to_string(),
+            label: id.to_string(),
+            kind: DeclarationKind::Class,
+            file_path: PathBuf::from(format!("{}.java", id)),
+            external: false,
+        }
+    }
+
+    fn edge(from: &str, to: &str) -> GraphEdge {
+        GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            relationship: RelationshipType::Uses,
+        }
+    }
+
+    #[test]
+    fn test_count_strongly_connected_components() {
+        let graph = ReferenceGraph {
+            nodes: ["A", "B", "C", "D", "E"].into_iter().map(node).collect(),
+            edges: vec![
+                edge("A", "B"),
+                edge("B", "C"),
+                edge("C", "A"),
+                // D depends on the cycle but is not part of it; E is isolated
+                edge("D", "A"),
+                edge("D", "String"),
+            ],
+        };
+
+        let visualizer = GraphVisualizer::new(graph);
+        assert_eq!(visualizer.count_strongly_connected_components(), 1);
+        assert_eq!(visualizer.strongly_connected_components().len(), 3);
+
+        let summary = visualizer.summary();
+        assert_eq!(summary.node_count, 5);
+        assert_eq!(summary.strongly_connected_components, 1);
+    }
+
+    #[test]
+    fn test_find_cycles_and_focus() {
+        let graph = ReferenceGraph {
+            nodes: ["A", "B", "C", "D"].into_iter().map(node).collect(),
+            edges: vec![edge("B", "C"), edge("C", "A"), edge("A", "B"), edge("A", "C"), edge("D", "A")],
+        };
+        let visualizer = GraphVisualizer::new(graph);
+
+        // A -> C -> A is shorter than A -> B -> C -> A
+        assert_eq!(visualizer.find_cycles(), vec![vec!["A".to_string(), "C".to_string()]]);
+
+        let focused = visualizer.generate_focused_graph("D");
+        assert_eq!(focused.nodes.len(), 2);
+        assert_eq!(focused.edges.len(), 1);
+
+        let mermaid = GraphVisualizer::new(focused).to_mermaid();
+        assert_eq!(mermaid, "graph LR\n    n0[\"A\"]\n    n1[\"D\"]\n    n1 -->|uses| n0\n");
+    }
+
+    #[test]
+    fn test_to_graphml() {
+        let mut generic = node("Box<T>");
+        generic.label = "Box<T> & \"friends\"".to_string();
+        let graph = ReferenceGraph {
+            nodes: vec![node("A"), node("B"), generic],
+            edges: vec![edge("A", "B"), edge("B", "Box<T>"), edge("A", "String")],
+        };
+        let graphml = GraphVisualizer::new(graph).to_graphml();
+
+        let mut reader = quick_xml::Reader::from_str(&graphml);
+        let (mut nodes, mut edges, mut data) = (0, 0, Vec::new());
+        let mut in_data = false;
+        loop {
+            match reader.read_event().unwrap() {
+                quick_xml::events::Event::Start(e) | quick_xml::events::Event::Empty(e) => {
+                    match e.name().as_ref() {
+                        b"node" => nodes += 1,
+                        b"edge" => edges += 1,
+                        b"data" => in_data = true,
+                        _ => {}
+                    }
+                }
+                quick_xml::events::Event::Text(text) if in_data => {
+                    data.push(text.unescape().unwrap().into_owned());
+                    in_data = false;
+                }
+                quick_xml::events::Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        assert_eq!(nodes, 3);
+        // The edge to String leaves the graph and is dropped
+        assert_eq!(edges, 2);
+        assert!(data.contains(&"Box<T> & \"friends\"".to_string()), "{:?}", data);
+        assert!(data.contains(&"class".to_string()));
+        assert!(data.contains(&"uses".to_string()));
+        assert!(graphml.contains("<node id=\"Box&lt;T&gt;\">"));
+    }
+
+    #[test]
+    fn test_star_hub_is_most_central() {
+        let leaves = ["B", "C", "D", "E"];
+        let graph = ReferenceGraph {
+            nodes: std::iter::once("Hub").chain(leaves).map(node).collect(),
+            edges: vec![edge("Hub", "B"), edge("Hub", "C"), edge("D", "Hub"), edge("E", "Hub"), edge("Hub", "String")],
+        };
+        let visualizer = GraphVisualizer::new(graph);
+
+        let centrality = visualizer.compute_centrality();
+        assert_eq!(centrality["Hub"], 0.5);
+        for leaf in leaves {
+            assert_eq!(centrality[leaf], 0.125);
+        }
+
+        let top = visualizer.summary().top_central_nodes;
+        assert_eq!(top.len(), TOP_CENTRAL_NODES);
+        assert_eq!(top[0], ("Hub".to_string(), 0.5));
+        assert_eq!(top[1].0, "B");
+    }
+
+    #[test]
+    fn test_graph_diff() {
+        let old = ReferenceGraph {
+            nodes: ["A", "B", "C"].into_iter().map(node).collect(),
+            edges: vec![edge("A", "B"), edge("B", "C")],
+        };
+        let new = ReferenceGraph {
+            nodes: ["A", "B", "D"].into_iter().map(node).collect(),
+            edges: vec![edge("A", "B"), edge("B", "D"), edge("B", "C")],
+        };
+
+        let diff = GraphVisualizer::diff(&old, &new);
+        let ids = |nodes: &[GraphNode]| nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        let pairs = |edges: &[GraphEdge]| edges.iter().map(|e| (e.from.clone(), e.to.clone())).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.added_nodes), vec!["D"]);
+        assert_eq!(ids(&diff.removed_nodes), vec!["C"]);
+        assert_eq!(pairs(&diff.added_edges), vec![("B".to_string(), "D".to_string())]);
+        assert!(diff.removed_edges.is_empty());
+
+        // Only the relationship changed: the edge counts as removed and added again
+        let mut extends = old.clone();
+        extends.edges[0].relationship = RelationshipType::Extends;
+        let diff = GraphVisualizer::diff(&old, &extends);
+        assert_eq!(pairs(&diff.added_edges), pairs(&diff.removed_edges));
+        assert!(GraphVisualizer::diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_to_cytoscape_json() {
+        let graph = ReferenceGraph {
+            nodes: ["A", "B"].into_iter().map(node).collect(),
+            edges: vec![edge("A", "B"), edge("A", "String")],
+        };
+        let json: serde_json::Value = serde_json::from_str(&GraphVisualizer::new(graph).to_json().unwrap()).unwrap();
+
+        let nodes = json["elements"]["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0]["data"]["id"], "A");
+        assert_eq!(nodes[0]["data"]["kind"], "class");
+
+        let edges = json["elements"]["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 1);
+        let data = edges[0]["data"].as_object().unwrap();
+        assert_eq!(data["source"], "A");
+        assert_eq!(data["target"], "B");
+        assert_eq!(data["relationship"], "uses");
+        assert!(!data.contains_key("from") && !data.contains_key("to"));
+    }
+
+    #[cfg(feature = "graphviz")]
+    #[test]
+    fn test_to_svg_with_graphviz() {
+        let graph = ReferenceGraph {
+            nodes: ["A", "B"].into_iter().map(node).collect(),
+            edges: vec![edge("A", "B")],
+        };
+
+        match GraphVisualizer::new(graph).to_svg() {
+            Ok(svg) => {
+                let svg = svg.trim_start();
+                assert!(svg.starts_with("<svg") || svg.starts_with("<?xml"));
+            }
+            // Graphviz is not installed here; nothing to render with
+            Err(GraphvizError::NotFound) => {}
+            Err(e) => panic!("dot failed: {}", e),
+        }
+    }
+}
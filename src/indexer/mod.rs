@@ -1,28 +1,103 @@
-use anyhow::{Result};
+mod normalizer;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tantivy::{
-    collector::TopDocs,
-    query::{Query, QueryParser, FuzzyTermQuery},
+    collector::{DocSetCollector, TopDocs},
+    directory::error::LockError,
+    query::{AllQuery, BooleanQuery, EnableScoring, FuzzyTermQuery, Occur, Query, QueryParser, RegexQuery, TermQuery},
     schema::*,
-    TantivyDocument,
-    Index, IndexReader, IndexWriter, Searcher, Term,
+    snippet::SnippetGenerator,
+    DocAddress, DocSet, Searcher, TantivyDocument, TERMINATED,
+    Index, IndexReader, IndexWriter, Term,
 };
 use tokio::sync::RwLock;
-use crate::parser::JavaStructurePreview;
+use crate::analyzer::{EntityMapping, EntityMappingExtractor};
+use crate::parser::{FileMeta, GroovyFile, JavaStructurePreview};
 use crate::types::{
-    Declaration, DeclarationKind, Field, Method, SearchQuery, SearchResult, SearchFilter,
+    Annotation, Declaration, PropertiesFile, XmlFile, DeclarationKind, Field, Method, MethodSearchResult, SearchQuery,
+    SearchResult, SearchFilter,
 };
 
+/// Options controlling how the index is built and queried
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexConfig {
+    /// Match names ignoring case and diacritics ("cafe" finds "Café")
+    pub normalize_names: bool,
+    /// Also add one document per method, searchable with search_methods
+    pub index_methods: bool,
+    /// How long to wait for another process to release the writer lock (None = fail at once)
+    /// The wait blocks the thread that constructs the IndexManager
+    #[serde(default)]
+    pub lock_timeout: Option<Duration>,
+    /// Memory the writer buffers documents in before flushing a segment
+    /// (None = DEFAULT_WRITER_HEAP_BYTES)
+    #[serde(default)]
+    pub writer_heap_bytes: Option<usize>,
+    /// Weight of each field in exact searches
+    #[serde(default)]
+    pub field_boosts: FieldBoosts,
+}
+
+/// Score multipliers of the fields matched by exact searches, so that a declaration
+/// named after the query ranks above one that only mentions it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FieldBoosts {
+    pub name: f32,
+    pub signature: f32,
+    pub documentation: f32,
+}
+
+impl Default for FieldBoosts {
+    fn default() -> Self {
+        Self {
+            name: 3.0,
+            signature: 2.0,
+            documentation: 1.0,
+        }
+    }
+}
+
+/// Writer heap used when IndexConfig::writer_heap_bytes is not set (50MB)
+pub const DEFAULT_WRITER_HEAP_BYTES: usize = 50_000_000;
+/// Smallest writer heap tantivy accepts (15MB)
+pub const MIN_WRITER_HEAP_BYTES: usize = 15_000_000;
+/// Writer heaps must stay below this; tantivy's per-thread limit (just under 4GB) is exclusive
+pub const MAX_WRITER_HEAP_BYTES: usize = u32::MAX as usize - 1_000_000;
+
+/// Interval between two attempts to take the writer lock
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct IndexManager {
     index: Index,
     reader: IndexReader,
     writer: Arc<RwLock<IndexWriter>>,
     schema: Schema,
+    config: IndexConfig,
 }
 
 impl IndexManager {
     pub fn new(index_path: &Path) -> Result<Self> {
+        Self::with_config(index_path, IndexConfig::default())
+    }
+
+    /// Open or create the index at `index_path`
+    /// Blocks the calling thread for up to `config.lock_timeout` while another writer holds the
+    /// lock; async callers with a timeout should construct it inside `spawn_blocking`.
+    pub fn with_config(index_path: &Path, config: IndexConfig) -> Result<Self> {
+        let writer_heap_bytes = config.writer_heap_bytes.unwrap_or(DEFAULT_WRITER_HEAP_BYTES);
+        if !(MIN_WRITER_HEAP_BYTES..MAX_WRITER_HEAP_BYTES).contains(&writer_heap_bytes) {
+            anyhow::bail!(
+                "Index writer heap must be at least {} and less than {} bytes, got {}",
+                MIN_WRITER_HEAP_BYTES,
+                MAX_WRITER_HEAP_BYTES,
+                writer_heap_bytes
+            );
+        }
         let schema = Self::create_schema()?;
         
         // Create directories if they don't exist
@@ -31,39 +106,81 @@ impl IndexManager {
         // Try to open existing index, create new one if it doesn't exist
         let index = match Index::open_in_dir(index_path) {
             Ok(existing_index) => {
-                println!("DEBUG: Opened existing index at {}", index_path.display());
+                tracing::debug!("Opened existing index at {}", index_path.display());
                 existing_index
             }
             Err(_) => {
-                println!("DEBUG: Creating new index at {}", index_path.display());
+                tracing::debug!("Creating new index at {}", index_path.display());
                 Index::create_in_dir(index_path, schema.clone())?
             }
         };
 
+        index
+            .tokenizers()
+            .register(normalizer::NORMALIZED_TOKENIZER, normalizer::normalized_analyzer());
+        index
+            .tokenizers()
+            .register(normalizer::RAW_LOWERCASE_TOKENIZER, normalizer::raw_lowercase_analyzer());
+
         let reader = index
             .reader_builder()
             .try_into()?;
 
-        let writer = Arc::new(RwLock::new(
-            index.writer(50_000_000)? // 50MB heap
-        ));
+        let writer = Arc::new(RwLock::new(Self::open_writer(&index, writer_heap_bytes, config.lock_timeout)?));
 
         Ok(Self {
             index,
             reader,
             writer,
             schema,
+            config,
         })
     }
 
+    /// Take the writer lock, retrying until `timeout` while another writer holds it
+    fn open_writer(index: &Index, heap_bytes: usize, timeout: Option<Duration>) -> Result<IndexWriter> {
+        let started = Instant::now();
+        loop {
+            match index.writer(heap_bytes) {
+                Ok(writer) => return Ok(writer),
+                Err(tantivy::TantivyError::LockFailure(LockError::LockBusy, _))
+                    if timeout.is_some_and(|timeout| started.elapsed() < timeout) =>
+                {
+                    std::thread::sleep(LOCK_RETRY_INTERVAL);
+                }
+                Err(e @ tantivy::TantivyError::LockFailure(LockError::LockBusy, _)) => {
+                    return Err(e).context("Index is locked by another writer");
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
     fn create_schema() -> Result<Schema> {
         let mut schema_builder = Schema::builder();
 
         // Basic fields
         schema_builder.add_text_field("name", TEXT | STORED);
+        // Untokenized name, so regex patterns see the whole identifier
+        schema_builder.add_text_field("name_raw", STRING);
+        let normalized_indexing = TextFieldIndexing::default()
+            .set_tokenizer(normalizer::NORMALIZED_TOKENIZER)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        schema_builder.add_text_field(
+            "name_normalized",
+            TextOptions::default().set_indexing_options(normalized_indexing),
+        );
         schema_builder.add_text_field("package", TEXT | STORED);
-        schema_builder.add_text_field("file_path", STORED);
+        schema_builder.add_text_field("file_path", STRING | STORED);
         schema_builder.add_text_field("signature", TEXT | STORED);
+        // Whole signature as one lowercased term, for substring matching
+        let signature_raw_indexing = TextFieldIndexing::default()
+            .set_tokenizer(normalizer::RAW_LOWERCASE_TOKENIZER)
+            .set_index_option(IndexRecordOption::Basic);
+        schema_builder.add_text_field(
+            "signature_raw",
+            TextOptions::default().set_indexing_options(signature_raw_indexing),
+        );
         schema_builder.add_text_field("documentation", TEXT | STORED);
 
         // Kind field (for exact matching)
@@ -71,7 +188,10 @@ impl IndexManager {
 
         // Modifiers and annotations
         schema_builder.add_text_field("modifiers", TEXT | STORED);
+        // Normalized access level (Visibility::as_str)
+        schema_builder.add_text_field("visibility", STRING | STORED);
         schema_builder.add_text_field("annotations", TEXT | STORED);
+        schema_builder.add_text_field("annotations_json", STORED);
 
         // Inheritance
         schema_builder.add_text_field("extends", TEXT | STORED);
@@ -90,28 +210,94 @@ impl IndexManager {
         // Hash for deduplication
         schema_builder.add_text_field("source_hash", STRING | STORED);
 
+        // Method documents ("class" or "method")
+        schema_builder.add_text_field("doc_type", STRING | STORED);
+        schema_builder.add_text_field("method_name", TEXT | STORED);
+        schema_builder.add_text_field("owner", STRING | STORED);
+
+        // Class referenced by a Spring XML bean or a properties value ("config" documents)
+        schema_builder.add_text_field("config_ref", STRING | STORED);
+
+        // JPA table of @Entity classes, lowercased since SQL names are case-insensitive
+        schema_builder.add_text_field("table_name", STRING | STORED);
+
         Ok(schema_builder.build())
     }
 
+    /// Index a single file and commit right away
+    /// Every commit flushes a new segment to disk, which is slow when repeated
+    /// for each file of a large project; use index_java_files for bulk indexing
     pub async fn index_java_file(&self, java_structure: &JavaStructurePreview) -> Result<()> {
+        self.index_java_files(std::slice::from_ref(java_structure)).await
+    }
+
+    /// Index a batch of files with a single commit
+    pub async fn index_java_files(&self, java_structures: &[JavaStructurePreview]) -> Result<()> {
         let mut writer = self.writer.write().await;
+
+        // Drop the documents buffered so far, or the next commit would publish half a batch
+        let added = java_structures.iter().try_for_each(|java_structure| self.add_java_file(&writer, java_structure));
+        if let Err(e) = added {
+            writer.rollback()?;
+            return Err(e);
+        }
+
+        writer.commit()?;
+        self.reader.reload()?;
+        
+        let (num_docs, _) = self.stats()?;
+        tracing::debug!("After indexing, index has {} documents", num_docs);
         
+        Ok(())
+    }
+
+    fn add_java_file(&self, writer: &IndexWriter, java_structure: &JavaStructurePreview) -> Result<()> {
+        // Drop documents from a previous version of this file first
+        writer.delete_term(self.file_path_term(&java_structure.file_meta.path));
+
         // Convert JavaStructurePreview to declarations and index them
         let declarations = self.convert_structure_to_declarations(java_structure);
-        
-        println!("DEBUG: Indexing {} declarations from {}", declarations.len(), java_structure.file_meta.path.display());
-        for declaration in &declarations {
-            let doc = self.create_document(declaration, java_structure)?;
+        let entity_mappings = EntityMappingExtractor::extract(java_structure);
+        self.add_declarations(writer, &java_structure.file_meta, &declarations, &entity_mappings)
+    }
+
+    /// Add the documents of a file's declarations (and of their methods, with index_methods)
+    fn add_declarations(
+        &self,
+        writer: &IndexWriter,
+        file_meta: &FileMeta,
+        declarations: &[Declaration],
+        entity_mappings: &[EntityMapping],
+    ) -> Result<()> {
+        tracing::debug!("Indexing {} declarations from {}", declarations.len(), file_meta.path.display());
+        for declaration in declarations {
+            let mut doc = self.create_document(declaration, file_meta)?;
+            if let Some(mapping) = entity_mappings.iter().find(|m| m.class_name == declaration.name) {
+                doc.add_text(self.schema.get_field("table_name").unwrap(), mapping.table.to_lowercase());
+            }
             writer.add_document(doc)?;
-            println!("DEBUG: Added document for {}: {:?}", declaration.name, declaration.kind);
+            tracing::debug!("Added document for {}: {:?}", declaration.name, declaration.kind);
+
+            if self.config.index_methods {
+                for method in &declaration.methods {
+                    writer.add_document(self.create_method_document(method, declaration, file_meta))?;
+                }
+            }
         }
 
+        Ok(())
+    }
+
+    /// Index the declarations of a Groovy file and commit right away
+    pub async fn index_groovy_file(&self, groovy_file: &GroovyFile) -> Result<()> {
+        let mut writer = self.writer.write().await;
+        writer.delete_term(self.file_path_term(&groovy_file.file_meta.path));
+        if let Err(e) = self.add_declarations(&writer, &groovy_file.file_meta, &groovy_file.declarations, &[]) {
+            writer.rollback()?;
+            return Err(e);
+        }
         writer.commit()?;
         self.reader.reload()?;
-        
-        let (num_docs, _) = self.stats()?;
-        println!("DEBUG: After indexing, index has {} documents", num_docs);
-        
         Ok(())
     }
 
@@ -184,13 +370,20 @@ impl IndexManager {
                     name: a.name.clone(),
                     values: a.values.clone(),
                 }).collect(),
+                throws: m.throws.clone(),
                 range: crate::types::SourceRange {
                     start_line: m.range.start_line,
                     start_column: m.range.start_column,
                     end_line: m.range.end_line,
                     end_column: m.range.end_column,
                 },
-                body_range: None,
+                body_range: m.body_range.as_ref().map(|r| crate::types::SourceRange {
+                    start_line: r.start_line,
+                    start_column: r.start_column,
+                    end_line: r.end_line,
+                    end_column: r.end_column,
+                }),
+                complexity: m.complexity,
             }).collect(),
             range: crate::types::SourceRange {
                 start_line: class.range.start_line,
@@ -199,20 +392,25 @@ impl IndexManager {
                 end_column: class.range.end_column,
             },
             documentation: class.documentation.clone(),
+            package: package.to_string(),
         }
     }
 
-    fn create_document(&self, declaration: &Declaration, java_structure: &JavaStructurePreview) -> Result<TantivyDocument> {
+    fn create_document(&self, declaration: &Declaration, file_meta: &FileMeta) -> Result<TantivyDocument> {
         let schema = &self.schema;
         
         let name_field = schema.get_field("name").unwrap();
+        let name_raw_field = schema.get_field("name_raw").unwrap();
+        let name_normalized_field = schema.get_field("name_normalized").unwrap();
         let package_field = schema.get_field("package").unwrap();
         let file_path_field = schema.get_field("file_path").unwrap();
         let signature_field = schema.get_field("signature").unwrap();
+        let signature_raw_field = schema.get_field("signature_raw").unwrap();
         let documentation_field = schema.get_field("documentation").unwrap();
         let kind_field = schema.get_field("kind").unwrap();
         let modifiers_field = schema.get_field("modifiers").unwrap();
         let annotations_field = schema.get_field("annotations").unwrap();
+        let annotations_json_field = schema.get_field("annotations_json").unwrap();
         let extends_field = schema.get_field("extends").unwrap();
         let implements_field = schema.get_field("implements").unwrap();
         let fields_field = schema.get_field("fields").unwrap();
@@ -226,9 +424,12 @@ impl IndexManager {
         let mut doc = TantivyDocument::new();
         
         doc.add_text(name_field, &declaration.name);
-        doc.add_text(package_field, &java_structure.package.as_deref().unwrap_or(""));
-        doc.add_text(file_path_field, java_structure.file_meta.path.to_string_lossy().as_ref());
+        doc.add_text(name_raw_field, &declaration.name);
+        doc.add_text(name_normalized_field, &declaration.name);
+        doc.add_text(package_field, &declaration.package);
+        doc.add_text(file_path_field, file_meta.path.to_string_lossy().as_ref());
         doc.add_text(signature_field, &declaration.signature);
+        doc.add_text(signature_raw_field, &declaration.signature);
         
         if let Some(documentation) = &declaration.documentation {
             doc.add_text(documentation_field, documentation);
@@ -236,6 +437,7 @@ impl IndexManager {
 
         doc.add_text(kind_field, format!("{:?}", declaration.kind));
         doc.add_text(modifiers_field, declaration.modifiers.join(" "));
+        doc.add_text(schema.get_field("visibility").unwrap(), declaration.visibility().as_str());
         
         let annotations: Vec<String> = declaration.annotations
             .iter()
@@ -243,6 +445,9 @@ impl IndexManager {
             .collect();
         doc.add_text(annotations_field, annotations.join(" "));
 
+        let annotations_json = serde_json::to_string(&declaration.annotations)?;
+        doc.add_text(annotations_json_field, annotations_json);
+
         if let Some(extends) = &declaration.extends {
             doc.add_text(extends_field, extends);
         }
@@ -260,61 +465,327 @@ impl IndexManager {
         doc.add_u64(start_column_field, declaration.range.start_column as u64);
         doc.add_u64(end_column_field, declaration.range.end_column as u64);
 
-        doc.add_text(source_hash_field, &java_structure.file_meta.hash_value);
+        doc.add_text(source_hash_field, &file_meta.hash_value);
+        doc.add_text(schema.get_field("doc_type").unwrap(), "class");
 
         Ok(doc)
     }
 
-    pub async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+    fn create_method_document(&self, method: &Method, owner: &Declaration, file_meta: &FileMeta) -> TantivyDocument {
+        let schema = &self.schema;
+        let owner_fqn = owner.fqn();
+
+        let parameters: Vec<String> = method.parameters
+            .iter()
+            .map(|p| format!("{} {}", p.type_name, p.name))
+            .collect();
+        let signature = format!(
+            "{} {} {}({})",
+            method.modifiers.join(" "),
+            method.return_type,
+            method.name,
+            parameters.join(", ")
+        ).trim().to_string();
+
+        let mut doc = TantivyDocument::new();
+        doc.add_text(schema.get_field("doc_type").unwrap(), "method");
+        doc.add_text(schema.get_field("method_name").unwrap(), &method.name);
+        doc.add_text(schema.get_field("owner").unwrap(), &owner_fqn);
+        doc.add_text(schema.get_field("signature").unwrap(), &signature);
+        doc.add_text(schema.get_field("signature_raw").unwrap(), &signature);
+        doc.add_text(schema.get_field("visibility").unwrap(), method.visibility(owner.kind).as_str());
+        doc.add_text(schema.get_field("file_path").unwrap(), file_meta.path.to_string_lossy().as_ref());
+        doc.add_u64(schema.get_field("start_line").unwrap(), method.range.start_line as u64);
+        doc.add_u64(schema.get_field("end_line").unwrap(), method.range.end_line as u64);
+        doc.add_u64(schema.get_field("start_column").unwrap(), method.range.start_column as u64);
+        doc.add_u64(schema.get_field("end_column").unwrap(), method.range.end_column as u64);
+        doc.add_text(schema.get_field("source_hash").unwrap(), &file_meta.hash_value);
+        doc
+    }
+
+    /// Find methods by name (requires IndexConfig::index_methods at indexing time)
+    pub async fn search_methods(&self, name: &str, limit: Option<usize>) -> Result<Vec<MethodSearchResult>> {
         let searcher = self.reader.searcher();
-        
-        // Handle kind filter specifically by searching the kind field
-        if let Some(SearchFilter::Kind(kind)) = query.filters.first() {
-            let kind_field = self.schema.get_field("kind").unwrap();
-            let kind_str = match kind {
-                DeclarationKind::Class => "Class",
-                DeclarationKind::Interface => "Interface",
-                DeclarationKind::Enum => "Enum",
-                DeclarationKind::Record => "Record",
-                DeclarationKind::Annotation => "Annotation",
+        let schema = &self.schema;
+
+        let query_parser = QueryParser::for_index(
+            &self.index,
+            vec![schema.get_field("method_name").unwrap()],
+        );
+        let top_docs = searcher.search(
+            &query_parser.parse_query(name)?,
+            &TopDocs::with_limit(limit.unwrap_or(100)),
+        )?;
+
+        let mut results = Vec::new();
+        for (score, doc_address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            let get_text = |field_name: &str| {
+                doc.get_first(schema.get_field(field_name).unwrap())
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string()
             };
-            let term = Term::from_field_text(kind_field, kind_str);
-            let query_obj: Box<dyn Query> = Box::new(tantivy::query::TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic));
-            
-            let top_docs = searcher.search(
-                &query_obj,
-                &TopDocs::with_limit(query.limit.unwrap_or(100)),
-            )?;
-
-            let mut results = Vec::new();
-            for (_score, doc_address) in top_docs {
-                let doc = searcher.doc(doc_address)?;
-                let result = self.document_to_result(&doc, searcher.clone())?;
-                results.push(result);
-            }
-            
-            return Ok(results);
+            let get_u64 = |field_name: &str| {
+                doc.get_first(schema.get_field(field_name).unwrap())
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0) as usize
+            };
+
+            results.push(MethodSearchResult {
+                name: get_text("method_name"),
+                owner: get_text("owner"),
+                signature: get_text("signature"),
+                file_path: PathBuf::from(get_text("file_path")),
+                range: crate::types::SourceRange {
+                    start_line: get_u64("start_line"),
+                    start_column: get_u64("start_column"),
+                    end_line: get_u64("end_line"),
+                    end_column: get_u64("end_column"),
+                },
+                score,
+            });
         }
-        
-        let query_obj = self.build_query(query)?;
+
+        Ok(results)
+    }
+
+    /// Find @Entity classes mapped to a table (case-insensitive)
+    pub async fn find_entities_by_table(&self, table: &str, limit: Option<usize>) -> Result<Vec<SearchResult>> {
+        let searcher = self.reader.searcher();
+        let term = Term::from_field_text(self.schema.get_field("table_name").unwrap(), &table.to_lowercase());
         let top_docs = searcher.search(
-            &query_obj,
-            &TopDocs::with_limit(query.limit.unwrap_or(100)),
+            &TermQuery::new(term, IndexRecordOption::Basic),
+            &TopDocs::with_limit(limit.unwrap_or(100)),
         )?;
 
+        let mut results = Vec::new();
+        for (score, doc_address) in top_docs {
+            let doc = searcher.doc(doc_address)?;
+            results.push(self.document_to_result(&doc, score, &[])?);
+        }
+
+        Ok(results)
+    }
+
+    /// Index the classes referenced by `class="..."` attributes of a Spring XML file
+    pub async fn index_xml_file(&self, xml_file: &XmlFile) -> Result<()> {
+        let class_attribute = regex::Regex::new(r#"\bclass\s*=\s*["']([\w.$]+)["']"#)?;
+        let references = xml_file
+            .content
+            .lines()
+            .enumerate()
+            .flat_map(|(i, line)| {
+                class_attribute
+                    .captures_iter(line)
+                    .map(move |c| (c[1].to_string(), i + 1, line.trim().to_string()))
+            })
+            .collect();
+        self.index_config_references(&xml_file.path, references).await
+    }
+
+    /// Index the properties whose value is a fully qualified class name
+    pub async fn index_properties_file(&self, properties_file: &PropertiesFile) -> Result<()> {
+        let class_name = regex::Regex::new(r"^(?:[a-z_][\w]*\.)+[A-Z][\w$]*$")?;
+        // Pairs carry no position, so look the key up in the file for the line number
+        let content = std::fs::read_to_string(&properties_file.path).unwrap_or_default();
+        let references = properties_file
+            .properties
+            .iter()
+            .filter(|(_, value)| class_name.is_match(value.trim()))
+            .map(|(key, value)| {
+                let line = content
+                    .lines()
+                    .position(|l| l.trim_start().starts_with(key.as_str()))
+                    .map_or(1, |i| i + 1);
+                (value.trim().to_string(), line, format!("{}={}", key, value))
+            })
+            .collect();
+        self.index_config_references(&properties_file.path, references).await
+    }
+
+    /// Replace the config documents of a file with one document per (class, line, text) reference
+    async fn index_config_references(&self, path: &Path, references: Vec<(String, usize, String)>) -> Result<()> {
+        let schema = &self.schema;
+        let mut writer = self.writer.write().await;
+        writer.delete_term(self.file_path_term(path));
+
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        for (class_name, line, text) in references {
+            let mut doc = TantivyDocument::new();
+            doc.add_text(schema.get_field("doc_type").unwrap(), "config");
+            doc.add_text(schema.get_field("config_ref").unwrap(), &class_name);
+            doc.add_text(schema.get_field("name").unwrap(), &file_name);
+            doc.add_text(schema.get_field("signature").unwrap(), &text);
+            doc.add_text(schema.get_field("file_path").unwrap(), path.to_string_lossy().as_ref());
+            doc.add_u64(schema.get_field("start_line").unwrap(), line as u64);
+            doc.add_u64(schema.get_field("end_line").unwrap(), line as u64);
+            writer.add_document(doc)?;
+        }
+
+        writer.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    /// Config files referencing a class, one result per reference
+    /// The declaration of each result describes the reference: the file name as `name`,
+    /// the referencing line as `signature` and its line number as `range`
+    pub async fn find_config_references(&self, class_fqn: &str) -> Result<Vec<SearchResult>> {
+        let searcher = self.reader.searcher();
+        let term = Term::from_field_text(self.schema.get_field("config_ref").unwrap(), class_fqn);
+        let doc_addresses = searcher.search(&TermQuery::new(term, IndexRecordOption::Basic), &DocSetCollector)?;
+
+        let mut results = Vec::with_capacity(doc_addresses.len());
+        for doc_address in doc_addresses {
+            let doc = searcher.doc(doc_address)?;
+            results.push(self.document_to_result(&doc, 1.0, &[])?);
+        }
+        results.sort_by(|a, b| {
+            (&a.file_path, a.declaration.range.start_line).cmp(&(&b.file_path, b.declaration.range.start_line))
+        });
+
+        Ok(results)
+    }
+
+    pub async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+        let searcher = self.reader.searcher();
+        let top_docs_collector = TopDocs::with_limit(query.limit.unwrap_or(100))
+            .and_offset(query.offset.unwrap_or(0));
+
+        let snippet_generators = self.query_snippet_generators(&searcher, query)?;
+        let top_docs = searcher.search(&self.declaration_query(query)?, &top_docs_collector)?;
+
         let mut results = Vec::new();
         
-        for (_score, doc_address) in top_docs {
+        for (score, doc_address) in top_docs {
             let doc = searcher.doc(doc_address)?;
-            let result = self.document_to_result(&doc, searcher.clone())?;
+            let result = self.document_to_result(&doc, score, &snippet_generators)?;
             results.push(result);
         }
 
         Ok(results)
     }
 
+    /// Declarations matching `query` in (name, file path, position) order, from `from_name` on,
+    /// keeping the first `count` that `accept` lets through
+    /// Names are visited in the order of the `name_raw` term dictionaries, so the only documents
+    /// loaded are the ones of the names walked through before `count` is reached
+    pub fn search_in_name_order(
+        &self,
+        query: &SearchQuery,
+        from_name: Option<&str>,
+        count: usize,
+        mut accept: impl FnMut(&SearchResult) -> bool,
+    ) -> Result<Vec<SearchResult>> {
+        let searcher = self.reader.searcher();
+        let snippet_generators = self.query_snippet_generators(&searcher, query)?;
+        let weight = self
+            .declaration_query(query)?
+            .weight(EnableScoring::disabled_from_searcher(&searcher))?;
+        let name_raw_field = self.schema.get_field("name_raw").unwrap();
+
+        let inverted_indexes = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.inverted_index(name_raw_field))
+            .collect::<tantivy::Result<Vec<_>>>()?;
+        let mut streams = Vec::new();
+        for (segment_ord, inverted_index) in inverted_indexes.iter().enumerate() {
+            let mut stream = inverted_index.terms().range().ge(from_name.unwrap_or("")).into_stream()?;
+            if stream.advance() {
+                streams.push((segment_ord, stream));
+            }
+        }
+
+        let mut results = Vec::new();
+        while results.len() < count {
+            // The smallest name left in any segment
+            let Some(name) = streams.iter().map(|(_, stream)| stream.key()).min().map(<[u8]>::to_vec) else {
+                break;
+            };
+
+            let mut same_name = Vec::new();
+            for (segment_ord, stream) in &streams {
+                if stream.key() != name {
+                    continue;
+                }
+                let segment_reader = searcher.segment_reader(*segment_ord as u32);
+                let mut postings = inverted_indexes[*segment_ord]
+                    .read_postings_from_terminfo(stream.value(), IndexRecordOption::Basic)?;
+                // Postings are in doc id order, so the query's scorer only ever seeks forward
+                let mut scorer = weight.scorer(segment_reader, 1.0)?;
+                while postings.doc() != TERMINATED {
+                    let doc_id = postings.doc();
+                    let alive = segment_reader.alive_bitset().is_none_or(|alive| alive.is_alive(doc_id));
+                    if alive && scorer.doc() <= doc_id && scorer.seek(doc_id) == doc_id {
+                        let doc = searcher.doc(DocAddress::new(*segment_ord as u32, doc_id))?;
+                        same_name.push(self.document_to_result(&doc, 1.0, &snippet_generators)?);
+                    }
+                    postings.advance();
+                }
+            }
+            streams.retain_mut(|(_, stream)| stream.key() != name || stream.advance());
+
+            same_name.sort_by(|a, b| {
+                (&a.file_path, a.declaration.range.start_line, a.declaration.range.start_column)
+                    .cmp(&(&b.file_path, b.declaration.range.start_line, b.declaration.range.start_column))
+            });
+            results.extend(same_name.into_iter().filter(|result| accept(result)));
+        }
+
+        results.truncate(count);
+        Ok(results)
+    }
+
+    /// Number of documents `search` would match without a limit, counted without loading them
+    /// (filters other than a leading kind filter are not applied)
+    pub fn count(&self, query: &SearchQuery) -> Result<usize> {
+        let searcher = self.reader.searcher();
+        Ok(searcher.search(&self.declaration_query(query)?, &tantivy::collector::Count)?)
+    }
+
+    /// Query matched by `search` and `count`: the text query, scoped to a kind when the first
+    /// filter is a kind filter. A blank text query matches every declaration.
+    fn declaration_query(&self, query: &SearchQuery) -> Result<Box<dyn Query>> {
+        let text_query: Box<dyn Query> = if query.query.trim().is_empty() {
+            Box::new(AllQuery)
+        } else {
+            self.build_query(query)?
+        };
+        let mut clauses = vec![(Occur::Must, text_query)];
+
+        if let Some(SearchFilter::Kind(kind)) = query.filters.first() {
+            let kind_field = self.schema.get_field("kind").unwrap();
+            // The kind field is tokenized, so its terms are lowercased
+            let kind_str = match kind {
+                DeclarationKind::Class => "class",
+                DeclarationKind::Interface => "interface",
+                DeclarationKind::Enum => "enum",
+                DeclarationKind::Record => "record",
+                DeclarationKind::Annotation => "annotation",
+                DeclarationKind::Method => "method",
+            };
+            let term = Term::from_field_text(kind_field, kind_str);
+            clauses.push((Occur::Must, Box::new(TermQuery::new(term, IndexRecordOption::Basic))));
+        }
+
+        // Method and config documents are only returned by search_methods and find_config_references
+        let doc_type_field = self.schema.get_field("doc_type").unwrap();
+        let method_term = Term::from_field_text(doc_type_field, "method");
+        let config_term = Term::from_field_text(doc_type_field, "config");
+        clauses.push((Occur::MustNot, Box::new(TermQuery::new(method_term, IndexRecordOption::Basic))));
+        clauses.push((Occur::MustNot, Box::new(TermQuery::new(config_term, IndexRecordOption::Basic))));
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
+
     fn build_query(&self, search: &SearchQuery) -> Result<Box<dyn Query>> {
         let schema = &self.schema;
+        let name_field = if self.config.normalize_names {
+            schema.get_field("name_normalized").unwrap()
+        } else {
+            schema.get_field("name").unwrap()
+        };
         
         match search.kind {
             crate::types::SearchKind::Exact => {
@@ -326,34 +797,69 @@ impl IndexManager {
                     );
                     Ok(query_parser.parse_query("*")?)
                 } else {
-                    let query_parser = QueryParser::for_index(
+                    let signature_field = schema.get_field("signature").unwrap();
+                    let documentation_field = schema.get_field("documentation").unwrap();
+                    let mut query_parser = QueryParser::for_index(
                         &self.index,
-                        vec![
-                            schema.get_field("name").unwrap(),
-                            schema.get_field("signature").unwrap(),
-                            schema.get_field("documentation").unwrap(),
-                        ],
+                        vec![name_field, signature_field, documentation_field],
                     );
+                    let boosts = &self.config.field_boosts;
+                    query_parser.set_field_boost(name_field, boosts.name);
+                    query_parser.set_field_boost(signature_field, boosts.signature);
+                    query_parser.set_field_boost(documentation_field, boosts.documentation);
                     Ok(query_parser.parse_query(&search.query)?)
                 }
             }
             crate::types::SearchKind::Fuzzy => {
-                let name_field = schema.get_field("name").unwrap();
-                let term = Term::from_field_text(name_field, &search.query);
+                let term = if self.config.normalize_names {
+                    Term::from_field_text(name_field, &normalizer::normalize_text(&search.query))
+                } else {
+                    Term::from_field_text(name_field, &search.query)
+                };
                 let fuzzy_query = FuzzyTermQuery::new(term, 2, true);
                 Ok(Box::new(fuzzy_query))
             }
             crate::types::SearchKind::Regex => {
-                let query_parser = QueryParser::for_index(
-                    &self.index,
-                    vec![schema.get_field("name").unwrap()],
-                );
-                Ok(query_parser.parse_query(&search.query)?)
+                let name_raw_field = schema.get_field("name_raw").unwrap();
+                let regex_query = RegexQuery::from_pattern(&search.query, name_raw_field)
+                    .with_context(|| format!("Invalid regex pattern: {}", search.query))?;
+                Ok(Box::new(regex_query))
+            }
+            crate::types::SearchKind::Substring => {
+                let signature_raw_field = schema.get_field("signature_raw").unwrap();
+                let pattern = format!(".*{}.*", regex::escape(&search.query.to_lowercase()));
+                Ok(Box::new(RegexQuery::from_pattern(&pattern, signature_raw_field)?))
             }
         }
     }
 
-    fn document_to_result(&self, doc: &TantivyDocument, _searcher: Searcher) -> Result<SearchResult> {
+    /// Snippet generators for the results of `query`
+    /// Snippets are only generated for text queries, not for kind lookups
+    fn query_snippet_generators(&self, searcher: &Searcher, query: &SearchQuery) -> Result<Vec<SnippetGenerator>> {
+        if matches!(query.filters.first(), Some(SearchFilter::Kind(_))) {
+            Ok(Vec::new())
+        } else {
+            self.snippet_generators(searcher, self.build_query(query)?.as_ref())
+        }
+    }
+
+    /// Snippet generators for the fields a preview can highlight, in order of preference
+    fn snippet_generators(&self, searcher: &Searcher, query: &dyn Query) -> Result<Vec<SnippetGenerator>> {
+        ["documentation", "signature"]
+            .iter()
+            .map(|field| {
+                let field = self.schema.get_field(field).unwrap();
+                Ok(SnippetGenerator::create(searcher, query, field)?)
+            })
+            .collect()
+    }
+
+    fn document_to_result(
+        &self,
+        doc: &TantivyDocument,
+        score: f32,
+        snippet_generators: &[SnippetGenerator],
+    ) -> Result<SearchResult> {
         let schema = &self.schema;
         
         let name_field = schema.get_field("name").unwrap();
@@ -376,13 +882,18 @@ impl IndexManager {
 
         let declaration = self.create_declaration_from_doc(doc)?;
         
-        // Create a simple preview
-        let preview = format!("{}: {}", name, signature);
+        // Highlight the query terms where they matched, else fall back to the signature
+        let preview = snippet_generators
+            .iter()
+            .map(|generator| generator.snippet_from_doc(doc))
+            .find(|snippet| !snippet.is_empty())
+            .map(|snippet| snippet.to_html())
+            .unwrap_or_else(|| format!("{}: {}", name, signature));
 
         Ok(SearchResult {
             declaration,
             file_path: PathBuf::from(file_path),
-            score: 1.0, // TODO: Calculate actual score
+            score,
             preview,
         })
     }
@@ -416,13 +927,14 @@ impl IndexManager {
         };
 
         let signature = get_text("signature");
-        let _package = get_text("package");
         let _file_path = PathBuf::from(get_text("file_path"));
 
-        // Read fields and methods from JSON
+        // Read annotations, fields and methods from JSON
+        let annotations_json = get_text("annotations_json");
         let fields_json = get_text("fields");
         let methods_json = get_text("methods");
         
+        let annotations: Vec<Annotation> = serde_json::from_str(&annotations_json).unwrap_or_default();
         let fields: Vec<Field> = serde_json::from_str(&fields_json).unwrap_or_default();
         let methods: Vec<Method> = serde_json::from_str(&methods_json).unwrap_or_default();
 
@@ -430,7 +942,7 @@ impl IndexManager {
             name,
             kind,
             modifiers: get_text("modifiers").split_whitespace().map(String::from).collect(),
-            annotations: vec![], // TODO: Parse annotations
+            annotations,
             signature,
             extends: Some(get_text("extends")).filter(|s| !s.is_empty()),
             implements: get_text("implements").split_whitespace().map(String::from).collect(),
@@ -443,9 +955,103 @@ impl IndexManager {
                 end_column: get_u64("end_column"),
             },
             documentation: Some(get_text("documentation")).filter(|s| !s.is_empty()),
+            package: get_text("package"),
         })
     }
 
+    /// Remove every document that came from the given file
+    pub async fn delete_by_file_path(&self, path: &Path) -> Result<()> {
+        let mut writer = self.writer.write().await;
+
+        writer.delete_term(self.file_path_term(path));
+        writer.commit()?;
+        self.reader.reload()?;
+
+        Ok(())
+    }
+
+    fn file_path_term(&self, path: &Path) -> Term {
+        let file_path_field = self.schema.get_field("file_path").unwrap();
+        Term::from_field_text(file_path_field, path.to_string_lossy().as_ref())
+    }
+
+    /// Number of indexed declarations of each kind, in one pass over the `kind` terms
+    /// Only postings are read, no stored document is loaded
+    pub fn count_by_kind(&self) -> Result<HashMap<DeclarationKind, usize>> {
+        let searcher = self.reader.searcher();
+        let kind_field = self.schema.get_field("kind").unwrap();
+        let mut counts = HashMap::new();
+
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(kind_field)?;
+            let mut terms = inverted_index.terms().stream()?;
+            while terms.advance() {
+                // The kind field is tokenized, so its terms are lowercased
+                let kind = match terms.key() {
+                    b"class" => DeclarationKind::Class,
+                    b"interface" => DeclarationKind::Interface,
+                    b"enum" => DeclarationKind::Enum,
+                    b"record" => DeclarationKind::Record,
+                    b"annotation" => DeclarationKind::Annotation,
+                    _ => continue,
+                };
+                let postings = inverted_index.read_postings_from_terminfo(terms.value(), IndexRecordOption::Basic)?;
+                let count = match segment_reader.alive_bitset() {
+                    Some(alive_bitset) => postings.doc_freq_given_deletes(alive_bitset) as usize,
+                    None => postings.doc_freq() as usize,
+                };
+                *counts.entry(kind).or_insert(0) += count;
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Every indexed class declaration, paired with its fully qualified name
+    pub fn all_declarations(&self) -> Result<Vec<(String, Declaration)>> {
+        Ok(self
+            .all_declarations_with_paths()?
+            .into_iter()
+            .map(|(_, declaration)| (declaration.fqn(), declaration))
+            .collect())
+    }
+
+    /// Every indexed class declaration, paired with the file it was indexed from
+    pub fn all_declarations_with_paths(&self) -> Result<Vec<(PathBuf, Declaration)>> {
+        let searcher = self.reader.searcher();
+        let class_term = Term::from_field_text(self.schema.get_field("doc_type").unwrap(), "class");
+        let file_path_field = self.schema.get_field("file_path").unwrap();
+        let doc_addresses = searcher.search(
+            &TermQuery::new(class_term, IndexRecordOption::Basic),
+            &DocSetCollector,
+        )?;
+
+        let mut declarations = Vec::with_capacity(doc_addresses.len());
+        for doc_address in doc_addresses {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            let file_path = doc.get_first(file_path_field).and_then(|v| v.as_str()).unwrap_or("");
+            declarations.push((PathBuf::from(file_path), self.create_declaration_from_doc(&doc)?));
+        }
+
+        Ok(declarations)
+    }
+
+    /// Whether the file at `file_path` has already been indexed with this content hash
+    /// (a copy of the same content under another path doesn't count)
+    pub fn is_indexed(&self, file_path: &Path, source_hash: &str) -> Result<bool> {
+        let searcher = self.reader.searcher();
+        let file_path_field = self.schema.get_field("file_path").unwrap();
+        let source_hash_field = self.schema.get_field("source_hash").unwrap();
+        let path_term = Term::from_field_text(file_path_field, file_path.to_string_lossy().as_ref());
+        let hash_term = Term::from_field_text(source_hash_field, source_hash);
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(TermQuery::new(path_term, IndexRecordOption::Basic)) as Box<dyn Query>),
+            (Occur::Must, Box::new(TermQuery::new(hash_term, IndexRecordOption::Basic))),
+        ]);
+
+        Ok(searcher.search(&query, &tantivy::collector::Count)? > 0)
+    }
+
     pub async fn delete_by_hash(&self, source_hash: &str) -> Result<()> {
         let mut writer = self.writer.write().await;
         
@@ -458,6 +1064,61 @@ impl IndexManager {
         Ok(())
     }
 
+    /// Merge documents from other indexes into this one
+    /// Documents are grouped by file_path, and a file coming from a source index
+    /// replaces whatever this index (or an earlier source) already held for it
+    pub async fn merge_from(&self, paths: &[&Path]) -> Result<()> {
+        let mut writer = self.writer.write().await;
+        let file_path_field = self.schema.get_field("file_path").unwrap();
+
+        for path in paths {
+            let source = Index::open_in_dir(path)?;
+            let searcher = source.reader()?.searcher();
+            let doc_addresses = searcher.search(&AllQuery, &DocSetCollector)?;
+
+            let mut docs_by_file: HashMap<String, Vec<TantivyDocument>> = HashMap::new();
+            for doc_address in doc_addresses {
+                let doc: TantivyDocument = searcher.doc(doc_address)?;
+                let file_path = doc.get_first(file_path_field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                docs_by_file.entry(file_path).or_default().push(doc);
+            }
+
+            tracing::debug!("Merging {} files from {}", docs_by_file.len(), path.display());
+            for (file_path, docs) in docs_by_file {
+                writer.delete_term(Term::from_field_text(file_path_field, &file_path));
+                for doc in docs {
+                    writer.add_document(self.restore_unstored_fields(doc))?;
+                }
+            }
+        }
+
+        writer.commit()?;
+        self.reader.reload()?;
+
+        Ok(())
+    }
+
+    /// Re-derive the indexed-only fields of a document read back from stored fields
+    /// (name_raw and name_normalized from name, signature_raw from signature)
+    fn restore_unstored_fields(&self, mut doc: TantivyDocument) -> TantivyDocument {
+        let text = |doc: &TantivyDocument, field: &str| {
+            doc.get_first(self.schema.get_field(field).unwrap())
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        if let Some(name) = text(&doc, "name") {
+            doc.add_text(self.schema.get_field("name_raw").unwrap(), &name);
+            doc.add_text(self.schema.get_field("name_normalized").unwrap(), &name);
+        }
+        if let Some(signature) = text(&doc, "signature") {
+            doc.add_text(self.schema.get_field("signature_raw").unwrap(), &signature);
+        }
+        doc
+    }
+
     pub async fn optimize(&self) -> Result<()> {
         let mut writer = self.writer.write().await;
         writer.commit()?;
@@ -472,7 +1133,7 @@ impl IndexManager {
         let segment_metas = self.index.searchable_segment_metas()?;
         let num_segments = segment_metas.len();
         
-        println!("DEBUG: Index has {} documents in {} segments", num_docs, num_segments);
+        tracing::debug!("Index has {} documents in {} segments", num_docs, num_segments);
         Ok((num_docs, num_segments))
     }
 }
@@ -510,6 +1171,7 @@ mod tests {
             },
             package: Some("com.example".to_string()),
             imports: vec![],
+            static_imports: vec![],
             top_level_classes: vec![
                 crate::parser::ClassStructure {
                     name: "UserService".to_string(),
@@ -529,7 +1191,10 @@ mod tests {
                         end_line: 10,
                         end_column: 1,
                     },
+                    loc: 0,
+                    sloc: 0,
                     documentation: Some("Service for user operations".to_string()),
+                    raw_documentation: None,
                 },
             ],
             file_annotations: vec![],
@@ -545,4 +1210,585 @@ mod tests {
         // Just verify index was created successfully
         assert!(true);
     }
-}
\ No newline at end of file
+
+    fn sample_structure(path: &str, class_name: &str) -> JavaStructurePreview {
+        crate::parser::JavaStructurePreview {
+            file_meta: crate::parser::FileMeta {
+                path: PathBuf::from(path),
+                name: format!("{}.java", class_name),
+                suffix: crate::parser::FileSuffix::Java,
+                hash_value: format!("hash-{}", class_name),
+            },
+            package: Some("com.example".to_string()),
+            imports: vec![],
+            static_imports: vec![],
+            top_level_classes: vec![
+                crate::parser::ClassStructure {
+                    name: class_name.to_string(),
+                    fqn: format!("com.example.{}", class_name),
+                    kind: crate::parser::ClassKind::Class,
+                    modifiers: vec!["public".to_string()],
+                    annotations: vec![],
+                    extends: None,
+                    implements: vec![],
+                    type_parameters: vec![],
+                    fields: vec![],
+                    methods: vec![],
+                    nested_classes: vec![],
+                    range: crate::parser::SourceRange {
+                        start_line: 1,
+                        start_column: 1,
+                        end_line: 10,
+                        end_column: 1,
+                    },
+                    loc: 0,
+                    sloc: 0,
+                    documentation: None,
+                    raw_documentation: None,
+                },
+            ],
+            file_annotations: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_from() {
+        let dir = tempdir().unwrap();
+        let module_a = dir.path().join("module_a");
+        let module_b = dir.path().join("module_b");
+        let combined = dir.path().join("combined");
+
+        {
+            let manager = IndexManager::new(&module_a).unwrap();
+            manager.index_java_file(&sample_structure("/a/UserService.java", "UserService")).await.unwrap();
+            manager.index_java_file(&sample_structure("/shared/Common.java", "Common")).await.unwrap();
+        }
+        {
+            let manager = IndexManager::new(&module_b).unwrap();
+            manager.index_java_file(&sample_structure("/b/OrderService.java", "OrderService")).await.unwrap();
+            manager.index_java_file(&sample_structure("/shared/Common.java", "Common")).await.unwrap();
+        }
+
+        let manager = IndexManager::new(&combined).unwrap();
+        manager.merge_from(&[module_a.as_path(), module_b.as_path()]).await.unwrap();
+
+        let (num_docs, _) = manager.stats().unwrap();
+        assert_eq!(num_docs, 3);
+
+        let query = SearchQuery {
+            query: "OrderService".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = manager.search(&query).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "OrderService");
+
+        // Fields that are indexed but not stored are rebuilt for the merged documents
+        let regex = SearchQuery {
+            query: "Order.*".to_string(),
+            kind: crate::types::SearchKind::Regex,
+            ..query.clone()
+        };
+        let results = manager.search(&regex).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "OrderService");
+
+        let signature = SearchQuery {
+            query: "public userser".to_string(),
+            kind: crate::types::SearchKind::Substring,
+            ..query.clone()
+        };
+        let results = manager.search(&signature).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "UserService");
+    }
+
+    #[tokio::test]
+    async fn test_annotations_round_trip() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let mut java_structure = sample_structure("/test/UserService.java", "UserService");
+        java_structure.top_level_classes[0].annotations.push(crate::parser::Annotation {
+            name: "Service".to_string(),
+            values: vec![("value".to_string(), "\"userService\"".to_string())],
+            range: crate::parser::SourceRange {
+                start_line: 1,
+                start_column: 1,
+                end_line: 1,
+                end_column: 24,
+            },
+        });
+        manager.index_java_file(&java_structure).await.unwrap();
+
+        let query = SearchQuery {
+            query: "UserService".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = manager.search(&query).await.unwrap();
+        assert_eq!(results.len(), 1);
+
+        let annotations = &results[0].declaration.annotations;
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].name, "Service");
+        assert_eq!(annotations[0].values, vec![("value".to_string(), "\"userService\"".to_string())]);
+        assert_eq!(results[0].declaration.package, "com.example");
+
+        let fqns: Vec<String> = manager.all_declarations().unwrap().into_iter().map(|(fqn, _)| fqn).collect();
+        assert_eq!(fqns, vec!["com.example.UserService"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_scores_are_ranked() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let docs = [
+            ("UserService", "user user user"),
+            ("UserRepository", "user user storage layer"),
+            ("UserController", "user endpoints exposed over http for the web layer"),
+        ];
+        for (name, documentation) in docs {
+            let mut java_structure = sample_structure(&format!("/test/{}.java", name), name);
+            java_structure.top_level_classes[0].documentation = Some(documentation.to_string());
+            manager.index_java_file(&java_structure).await.unwrap();
+        }
+
+        let query = SearchQuery {
+            query: "user".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = manager.search(&query).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].declaration.name, "UserService");
+
+        for pair in results.windows(2) {
+            assert!(pair[0].score > pair[1].score, "scores should be distinct and descending");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preview_highlights_documentation() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let mut java_structure = sample_structure("/test/Ledger.java", "Ledger");
+        java_structure.top_level_classes[0].documentation =
+            Some("Keeps balances and reconciles payments nightly".to_string());
+        manager.index_java_file(&java_structure).await.unwrap();
+
+        let query = SearchQuery {
+            query: "reconciles".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = manager.search(&query).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].preview.contains("balances and <b>reconciles</b> payments"), "{}", results[0].preview);
+
+        // No term hit in documentation or signature: preview falls back to the signature
+        let query = SearchQuery {
+            query: "Ledgr".to_string(),
+            kind: crate::types::SearchKind::Fuzzy,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = manager.search(&query).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].preview.starts_with("Ledger: "), "{}", results[0].preview);
+    }
+
+    #[tokio::test]
+    async fn test_lock_timeout_and_stale_lock_file() {
+        let dir = tempdir().unwrap();
+        let index_path = dir.path().join("test_index");
+        let manager = IndexManager::new(&index_path).unwrap();
+
+        // A waiting manager gives up after its timeout while a live writer holds the lock
+        let config = IndexConfig {
+            lock_timeout: Some(Duration::from_millis(300)),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        assert!(IndexManager::with_config(&index_path, config).is_err());
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        drop(manager);
+
+        // What a crashed process leaves behind: the lock file, with nobody holding the OS lock
+        std::fs::write(index_path.join(".tantivy-writer.lock"), "").unwrap();
+
+        let manager = IndexManager::new(&index_path).unwrap();
+        manager.index_java_file(&sample_structure("/test/Recovered.java", "Recovered")).await.unwrap();
+        assert_eq!(manager.stats().unwrap().0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_accent_insensitive_search() {
+        let dir = tempdir().unwrap();
+        let config = IndexConfig {
+            normalize_names: true,
+            ..Default::default()
+        };
+        let manager = IndexManager::with_config(&dir.path().join("test_index"), config).unwrap();
+
+        manager.index_java_file(&sample_structure("/test/Café.java", "Café")).await.unwrap();
+        manager.index_java_file(&sample_structure("/test/Пользователь.java", "Пользователь")).await.unwrap();
+
+        let search = |query: &str| SearchQuery {
+            query: query.to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+
+        let results = manager.search(&search("CAFE")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "Café");
+
+        let results = manager.search(&search("пользователь")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "Пользователь");
+    }
+
+    #[tokio::test]
+    async fn test_regex_search() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        manager.index_java_file(&sample_structure("/test/UserService.java", "UserService")).await.unwrap();
+        manager.index_java_file(&sample_structure("/test/UserController.java", "UserController")).await.unwrap();
+
+        let search = |pattern: &str| SearchQuery {
+            query: pattern.to_string(),
+            kind: crate::types::SearchKind::Regex,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+
+        let results = manager.search(&search("Us.*Service")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "UserService");
+
+        let results = manager.search(&search("User.*")).await.unwrap();
+        assert_eq!(results.len(), 2);
+
+        assert!(manager.search(&search("User(")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_indexing_single_commit() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let structures: Vec<JavaStructurePreview> = (0..200)
+            .map(|i| sample_structure(&format!("/test/Class{}.java", i), &format!("Class{}", i)))
+            .collect();
+        manager.index_java_files(&structures).await.unwrap();
+
+        let (num_docs, num_segments) = manager.stats().unwrap();
+        assert_eq!(num_docs, 200);
+        assert!(num_segments < 10, "expected few segments, got {}", num_segments);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_same_file() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let java_structure = sample_structure("/test/UserService.java", "UserService");
+        let path = Path::new("/test/UserService.java");
+        assert!(!manager.is_indexed(path, "hash-UserService").unwrap());
+
+        manager.index_java_file(&java_structure).await.unwrap();
+        assert!(manager.is_indexed(path, "hash-UserService").unwrap());
+        // The same content under another path is not indexed yet
+        assert!(!manager.is_indexed(Path::new("/test/copy/UserService.java"), "hash-UserService").unwrap());
+
+        manager.index_java_file(&java_structure).await.unwrap();
+        let (num_docs, _) = manager.stats().unwrap();
+        assert_eq!(num_docs, 1);
+
+        // A changed file replaces the old documents under the same path
+        let mut changed = sample_structure("/test/UserService.java", "UserService");
+        changed.file_meta.hash_value = "hash-changed".to_string();
+        manager.index_java_file(&changed).await.unwrap();
+
+        let (num_docs, _) = manager.stats().unwrap();
+        assert_eq!(num_docs, 1);
+        assert!(!manager.is_indexed(path, "hash-UserService").unwrap());
+        assert!(manager.is_indexed(path, "hash-changed").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_file_path() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        manager.index_java_file(&sample_structure("/test/UserService.java", "UserService")).await.unwrap();
+        let (num_docs, _) = manager.stats().unwrap();
+        assert_eq!(num_docs, 1);
+
+        manager.delete_by_file_path(Path::new("/test/UserService.java")).await.unwrap();
+        let (num_docs, _) = manager.stats().unwrap();
+        assert_eq!(num_docs, 0);
+    }
+
+    #[tokio::test]
+    async fn test_find_entities_by_table() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let annotation = |name: &str, values: Vec<(String, String)>| crate::parser::Annotation {
+            name: name.to_string(),
+            values,
+            range: crate::parser::SourceRange { start_line: 1, start_column: 1, end_line: 1, end_column: 1 },
+        };
+        let mut user = sample_structure("/test/User.java", "User");
+        user.top_level_classes[0].annotations = vec![
+            annotation("Entity", vec![]),
+            annotation("Table", vec![("name".to_string(), "\"USERS\"".to_string())]),
+        ];
+        manager.index_java_files(&[user, sample_structure("/test/UserService.java", "UserService")]).await.unwrap();
+
+        let results = manager.find_entities_by_table("users", Some(10)).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "User");
+    }
+
+    #[tokio::test]
+    async fn test_signature_substring_search() {
+        let dir = tempdir().unwrap();
+        let manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        manager.index_java_file(&sample_structure("/test/UserRepository.java", "UserRepository")).await.unwrap();
+        manager.index_java_file(&sample_structure("/test/OrderService.java", "OrderService")).await.unwrap();
+
+        let query = SearchQuery {
+            query: "userRepo".to_string(),
+            kind: crate::types::SearchKind::Substring,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = manager.search(&query).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "UserRepository");
+    }
+
+    #[tokio::test]
+    async fn test_index_methods() {
+        let dir = tempdir().unwrap();
+        let config = IndexConfig {
+            index_methods: true,
+            ..Default::default()
+        };
+        let manager = IndexManager::with_config(&dir.path().join("test_index"), config).unwrap();
+
+        let method = |name: &str, line: usize| crate::parser::MethodStructure {
+            name: name.to_string(),
+            return_type: "User".to_string(),
+            parameters: vec![crate::parser::ParameterStructure {
+                name: "value".to_string(),
+                type_name: "String".to_string(),
+                annotations: vec![],
+            }],
+            modifiers: vec!["public".to_string()],
+            annotations: vec![],
+            type_parameters: vec![],
+            throws: vec![],
+            range: crate::parser::SourceRange {
+                start_line: line,
+                start_column: 5,
+                end_line: line + 2,
+                end_column: 6,
+            },
+            loc: 0,
+            sloc: 0,
+            body_range: None,
+            complexity: None,
+            calls: vec![],
+            documentation: None,
+            raw_documentation: None,
+        };
+
+        let mut java_structure = sample_structure("/test/UserRepository.java", "UserRepository");
+        java_structure.top_level_classes[0].methods = vec![
+            method("findByEmail", 2),
+            method("findByName", 5),
+            method("save", 8),
+        ];
+        manager.index_java_file(&java_structure).await.unwrap();
+
+        let results = manager.search_methods("findByEmail", Some(10)).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "findByEmail");
+        assert_eq!(results[0].owner, "com.example.UserRepository");
+        assert_eq!(results[0].signature, "public User findByEmail(String value)");
+        assert_eq!(results[0].range.start_line, 2);
+
+        // Declaration search still only sees the class
+        let query = SearchQuery {
+            query: "User".to_string(),
+            kind: crate::types::SearchKind::Substring,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = manager.search(&query).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "UserRepository");
+    }
+
+    #[tokio::test]
+    async fn test_count_matches_search() {
+        let dir = tempdir().unwrap();
+        let config = IndexConfig {
+            index_methods: true,
+            ..Default::default()
+        };
+        let manager = IndexManager::with_config(&dir.path().join("test_index"), config).unwrap();
+
+        let mut repository = sample_structure("/test/UserRepository.java", "UserRepository");
+        repository.top_level_classes[0].kind = crate::parser::ClassKind::Interface;
+        repository.top_level_classes[0].methods = vec![crate::parser::MethodStructure {
+            name: "findUser".to_string(),
+            return_type: "User".to_string(),
+            parameters: vec![],
+            modifiers: vec![],
+            annotations: vec![],
+            type_parameters: vec![],
+            throws: vec![],
+            range: crate::parser::SourceRange {
+                start_line: 2,
+                start_column: 5,
+                end_line: 2,
+                end_column: 20,
+            },
+            loc: 1,
+            sloc: 1,
+            body_range: None,
+            complexity: None,
+            calls: vec![],
+            documentation: None,
+            raw_documentation: None,
+        }];
+        let structures = vec![
+            sample_structure("/test/UserService.java", "UserService"),
+            sample_structure("/test/OrderService.java", "OrderService"),
+            repository,
+        ];
+        manager.index_java_files(&structures).await.unwrap();
+
+        let query = |text: &str, filters: Vec<SearchFilter>| SearchQuery {
+            query: text.to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters,
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        // Method documents are left out of the count, as they are of the search
+        for (query, expected) in [
+            (query("*", vec![]), 3),
+            (query("UserService", vec![]), 1),
+            (query("*", vec![SearchFilter::Kind(DeclarationKind::Interface)]), 1),
+            // The kind filter scopes the text query instead of replacing it
+            (query("OrderService", vec![SearchFilter::Kind(DeclarationKind::Interface)]), 0),
+            (query("OrderService", vec![SearchFilter::Kind(DeclarationKind::Class)]), 1),
+            (query("NoSuchClass", vec![]), 0),
+        ] {
+            assert_eq!(manager.count(&query).unwrap(), expected, "{}", query.query);
+            assert_eq!(manager.search(&query).await.unwrap().len(), expected, "{}", query.query);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_writer_heap() {
+        let dir = tempdir().unwrap();
+        let config = IndexConfig {
+            writer_heap_bytes: Some(20_000_000),
+            ..Default::default()
+        };
+        let manager = IndexManager::with_config(&dir.path().join("test_index"), config).unwrap();
+        manager
+            .index_java_file(&sample_structure("/test/UserService.java", "UserService"))
+            .await
+            .unwrap();
+        assert_eq!(manager.count_by_kind().unwrap().get(&DeclarationKind::Class), Some(&1));
+
+        // The upper bound is exclusive, like tantivy's
+        for (name, heap) in [("small_index", 1_000_000), ("large_index", MAX_WRITER_HEAP_BYTES)] {
+            let config = IndexConfig {
+                writer_heap_bytes: Some(heap),
+                ..Default::default()
+            };
+            let error = IndexManager::with_config(&dir.path().join(name), config).err().unwrap().to_string();
+            assert!(error.contains("Index writer heap must be at least"), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_name_match_ranks_above_documentation_mention() {
+        let dir = tempdir().unwrap();
+        let names_for = |boosts: FieldBoosts, index: &str| {
+            let config = IndexConfig {
+                field_boosts: boosts,
+                ..Default::default()
+            };
+            let manager = IndexManager::with_config(&dir.path().join(index), config).unwrap();
+            async move {
+                // Named so that it sorts before Foo
+                let mut cache = sample_structure("/test/Cache.java", "Cache");
+                cache.top_level_classes[0].documentation = Some("Foo utilities: wraps Foo and caches Foo".to_string());
+                let foo = sample_structure("/test/Foo.java", "Foo");
+                manager.index_java_files(&[cache, foo]).await.unwrap();
+                // Through the query engine, which sorts the results the CLI, server and exports return
+                let query_engine = crate::query::QueryEngine::new_with_manager(manager).unwrap();
+
+                let query = SearchQuery {
+                    query: "Foo".to_string(),
+                    kind: crate::types::SearchKind::Exact,
+                    filters: vec![],
+                    limit: Some(10),
+                    offset: None,
+                    filter_combine: crate::types::FilterCombine::Any,
+                };
+                let results = query_engine.search(&query).await.unwrap();
+                results.into_iter().map(|r| r.declaration.name).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(names_for(FieldBoosts::default(), "default").await, vec!["Foo", "Cache"]);
+
+        // Boosts are configurable: favoring documentation turns the ranking around
+        let documentation_first = FieldBoosts {
+            name: 0.1,
+            signature: 0.1,
+            documentation: 5.0,
+        };
+        assert_eq!(names_for(documentation_first, "documentation_first").await, vec!["Cache", "Foo"]);
+    }
+}
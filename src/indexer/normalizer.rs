@@ -0,0 +1,88 @@
+use tantivy::tokenizer::{
+    LowerCaser, RawTokenizer, SimpleTokenizer, TextAnalyzer, Token, TokenFilter, TokenStream,
+    Tokenizer,
+};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Name of the tokenizer registered for case/accent-insensitive fields
+pub const NORMALIZED_TOKENIZER: &str = "normalized";
+
+/// Tokenizer that lowercases and strips diacritics ("Café" -> "cafe")
+pub fn normalized_analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(SimpleTokenizer::default())
+        .filter(LowerCaser)
+        .filter(DiacriticStripper)
+        .build()
+}
+
+/// Name of the tokenizer keeping a whole value as one lowercased term
+pub const RAW_LOWERCASE_TOKENIZER: &str = "raw_lowercase";
+
+/// Tokenizer producing a single lowercased term, for substring matching
+pub fn raw_lowercase_analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(RawTokenizer::default())
+        .filter(LowerCaser)
+        .build()
+}
+
+/// Apply the same normalization as the analyzer to a whole string
+pub fn normalize_text(text: &str) -> String {
+    strip_diacritics(&text.to_lowercase())
+}
+
+fn strip_diacritics(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).collect()
+}
+
+/// Token filter that decomposes tokens (Unicode NFD) and drops combining marks
+#[derive(Clone)]
+pub struct DiacriticStripper;
+
+impl TokenFilter for DiacriticStripper {
+    type Tokenizer<T: Tokenizer> = DiacriticStripperFilter<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+        DiacriticStripperFilter { tokenizer }
+    }
+}
+
+#[derive(Clone)]
+pub struct DiacriticStripperFilter<T> {
+    tokenizer: T,
+}
+
+impl<T: Tokenizer> Tokenizer for DiacriticStripperFilter<T> {
+    type TokenStream<'a> = DiacriticStripperTokenStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        DiacriticStripperTokenStream {
+            tail: self.tokenizer.token_stream(text),
+        }
+    }
+}
+
+pub struct DiacriticStripperTokenStream<T> {
+    tail: T,
+}
+
+impl<T: TokenStream> TokenStream for DiacriticStripperTokenStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        // ASCII never carries diacritics
+        if !self.tail.token().text.is_ascii() {
+            let stripped = strip_diacritics(&self.tail.token().text);
+            self.tail.token_mut().text = stripped;
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
@@ -23,10 +23,17 @@ pub mod types;
 pub mod parser;
 pub mod indexer;
 pub mod query;
+pub mod graph;
+pub mod analyzer;
 pub mod llm;
 pub mod cli;
+pub mod server;
+pub mod tui;
+pub mod watch;
 pub mod r#async;
 mod type_config;
+#[cfg(test)]
+mod test_support;
 
 pub use types::*;
 pub use cli::*;
\ No newline at end of file
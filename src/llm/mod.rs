@@ -1,12 +1,22 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use schemars::JsonSchema;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use serde::{Deserialize, Serialize};
 use chrono;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-use crate::types::{Declaration, LlmExport, DeclarationKind};
-use crate::query::QueryEngine;
+use crate::parser::{
+    ClassKind, ClassStructure, DependencyGraph, FileParser, FileSuffix, Import, JavaStructureParser, MavenModule,
+    MavenParser,
+};
+use crate::types::{CodeChunk, Declaration, ExportMetrics, LlmExport, DeclarationKind, Method, SearchResult};
+use crate::query::{QueryEngine, QueryStatistics};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LlmRequest {
     pub query: Option<String>,
     pub kind: Option<DeclarationKind>,
@@ -15,24 +25,137 @@ pub struct LlmRequest {
     pub limit: Option<usize>,
     pub include_source: bool,
     pub format: ExportFormat,
+    /// Boolean facets to derive from annotations (empty = no facets)
+    #[serde(default)]
+    pub stereotype_facets: Vec<StereotypeFacet>,
+    /// Whether a declaration needs any or all of `annotations`
+    #[serde(default)]
+    pub filter_combine: crate::types::FilterCombine,
+    /// Split source code larger than this many (estimated) tokens into several chunks
+    #[serde(default)]
+    pub max_chunk_tokens: Option<usize>,
+    /// Tokens repeated from the end of a chunk at the start of the next one
+    #[serde(default)]
+    pub chunk_overlap_tokens: usize,
+    /// Export whole declarations, or each of their methods separately
+    #[serde(default)]
+    pub granularity: ExportGranularity,
+    /// Fill LlmExport::ancestors with the supertype chain found in the index
+    #[serde(default)]
+    pub include_ancestors: bool,
+    /// Fill LlmExport::metrics with size metrics
+    #[serde(default)]
+    pub include_metrics: bool,
+    /// Drop exports repeating an earlier (file_path, name, line_range)
+    #[serde(default = "default_dedup")]
+    pub dedup: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_dedup() -> bool {
+    true
+}
+
+impl Default for LlmRequest {
+    /// Every declaration, as JSON, without source code or extras
+    fn default() -> Self {
+        Self {
+            query: None,
+            kind: None,
+            annotations: Vec::new(),
+            package: None,
+            limit: None,
+            include_source: false,
+            format: ExportFormat::Json,
+            stereotype_facets: Vec::new(),
+            filter_combine: crate::types::FilterCombine::default(),
+            max_chunk_tokens: None,
+            chunk_overlap_tokens: 0,
+            granularity: ExportGranularity::default(),
+            include_ancestors: false,
+            include_metrics: false,
+            dedup: default_dedup(),
+        }
+    }
+}
+
+/// Unit of an exported document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ExportGranularity {
+    /// One document per class, interface, enum, ...
+    #[default]
+    Declaration,
+    /// One document per method
+    Method,
+}
+
+/// A boolean flag set on an export when the declaration carries any of the annotations
+/// Example: "is_controller" for @Controller or @RestController
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StereotypeFacet {
+    pub key: String,
+    pub annotations: Vec<String>,
+}
+
+impl StereotypeFacet {
+    pub fn new(key: &str, annotations: &[&str]) -> Self {
+        Self {
+            key: key.to_string(),
+            annotations: annotations.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// Whether the declaration carries one of the facet's annotations
+    pub fn matches(&self, declaration: &Declaration) -> bool {
+        declaration.annotations.iter().any(|a| self.annotations.contains(&a.name))
+    }
+
+    /// The common Spring/JPA stereotypes
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("is_service", &["Service"]),
+            Self::new("is_controller", &["Controller", "RestController"]),
+            Self::new("is_repository", &["Repository"]),
+            Self::new("is_entity", &["Entity"]),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ExportFormat {
     Json,
     Jsonl,
     Markdown,
     LlamaIndex,
     RAG,
+    /// One JSON object per top-level class, keeping nested types, fields and methods
+    StructuredJson,
+    /// Sorted public API (FQN, kind, member signatures) without timestamps, meant to be diffed in git
+    ApiSnapshot,
+    /// One row per declaration, for spreadsheets
+    Csv,
+    /// The whole response as YAML, with multi-line documentation as block scalars
+    Yaml,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LlmResponse {
     pub declarations: Vec<LlmExport>,
     pub metadata: ExportMetadata,
+    /// Full class trees, only filled for ExportFormat::StructuredJson and ExportFormat::ApiSnapshot
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub structures: Vec<StructuredExport>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A top-level class with its complete nested structure
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StructuredExport {
+    pub file_path: String,
+    pub package: Option<String>,
+    #[serde(flatten)]
+    pub class: ClassStructure,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExportMetadata {
     pub total_count: usize,
     pub query: LlmRequest,
@@ -40,6 +163,60 @@ pub struct ExportMetadata {
     pub project_root: String,
 }
 
+/// Top-level description of a whole project, for an agent to orient itself before searching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectManifest {
+    pub project_root: String,
+    /// Sorted by package name; declarations of the default package are listed under ""
+    pub packages: Vec<PackageSummary>,
+    /// Maven modules found under the project root, in build order when it has one
+    pub modules: Vec<ModuleSummary>,
+    pub entry_points: Vec<EntryPoint>,
+    pub statistics: QueryStatistics,
+    /// Number of declarations matching each of StereotypeFacet::defaults, like "is_service"
+    pub stereotype_counts: BTreeMap<String, usize>,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageSummary {
+    pub name: String,
+    pub declaration_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleSummary {
+    /// The module's pom.xml, relative to the project root
+    pub path: String,
+    /// `groupId:artifactId:version`, with `?` for what the POM does not tell
+    pub coordinates: String,
+    /// `groupId:artifactId` of the modules of the same build it depends on
+    pub depends_on: Vec<String>,
+}
+
+/// A place where the application starts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryPoint {
+    pub fqn: String,
+    pub kind: EntryPointKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryPointKind {
+    /// A class annotated with @SpringBootApplication
+    SpringBootApplication,
+    /// A class declaring `public static void main`
+    MainMethod,
+}
+
+/// JSON Schema of LlmResponse (and, through it, LlmExport), the contract of JSON exports
+pub fn export_schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(LlmResponse)
+}
+
+/// Number of search results fetched at a time by streaming exports
+const EXPORT_PAGE_SIZE: usize = 500;
+
 pub struct LlmExporter {
     query_engine: QueryEngine,
     project_root: PathBuf,
@@ -53,8 +230,17 @@ impl LlmExporter {
         })
     }
 
+    pub fn query_engine(&self) -> &QueryEngine {
+        &self.query_engine
+    }
+
     pub async fn export(&self, request: LlmRequest) -> Result<LlmResponse> {
         let declarations = self.find_declarations(&request).await?;
+        let structures = if matches!(request.format, ExportFormat::StructuredJson | ExportFormat::ApiSnapshot) {
+            self.collect_structures(&declarations)?
+        } else {
+            Vec::new()
+        };
         let exports = self.convert_to_exports(declarations, &request).await?;
 
         let metadata = ExportMetadata {
@@ -67,21 +253,103 @@ impl LlmExporter {
         Ok(LlmResponse {
             declarations: exports,
             metadata,
+            structures,
         })
     }
 
+    /// Re-parse the source of each result to recover its full class tree
+    fn collect_structures(
+        &self,
+        search_results: &[crate::types::SearchResult],
+    ) -> Result<Vec<StructuredExport>> {
+        let parser = JavaStructureParser::new()?;
+        let mut structures = Vec::new();
+
+        for result in search_results {
+            let java_structure = parser.parse_structure(&result.file_path)?;
+            let class = java_structure
+                .top_level_classes
+                .into_iter()
+                .find(|c| c.name == result.declaration.name);
+
+            if let Some(class) = class {
+                structures.push(StructuredExport {
+                    file_path: Self::get_relative_path(&result.file_path, &self.project_root)?,
+                    package: java_structure.package.clone(),
+                    class,
+                });
+            }
+        }
+
+        Ok(structures)
+    }
+
+    /// Export matching declarations as JSON lines, searching page by page
+    /// so that no more than one page of results is held in memory
+    /// Returns the number of lines written
+    pub async fn export_jsonl_streaming<W>(&self, request: LlmRequest, mut writer: W) -> Result<usize>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        // Cursor pages only load the declarations of the page, and bypass the cache
+        // which would otherwise keep every page in memory
+        let query = self.declarations_query(&request, None, Some(EXPORT_PAGE_SIZE));
+        let mut cursor: Option<String> = None;
+        let mut written = 0;
+        let mut seen = HashSet::new();
+
+        loop {
+            let page = self.query_engine.search_page(&query, cursor.as_deref()).await?;
+            for result in page.results {
+                if request.limit.is_some_and(|limit| written >= limit) {
+                    break;
+                }
+                let export = self.create_export(&result.declaration, &result.file_path, &request).await?;
+                if request.dedup && !seen.insert(Self::dedup_key(&export)) {
+                    continue;
+                }
+                writer.write_all(serde_json::to_string(&export)?.as_bytes()).await?;
+                writer.write_all(b"\n").await?;
+                written += 1;
+            }
+
+            match page.next_cursor {
+                Some(next) if request.limit.is_none_or(|limit| written < limit) => cursor = Some(next),
+                _ => break,
+            }
+        }
+
+        writer.flush().await?;
+        Ok(written)
+    }
+
     async fn find_declarations(
         &self,
         request: &LlmRequest,
     ) -> Result<Vec<crate::types::SearchResult>> {
-        let query = crate::types::SearchQuery {
+        let query = self.declarations_query(request, None, request.limit);
+        // A snapshot stands for the whole public API, so without a limit it takes every match
+        // rather than the default page
+        if matches!(request.format, ExportFormat::ApiSnapshot) && request.limit.is_none() {
+            return self.query_engine.search_all(&query).await;
+        }
+        self.query_engine.search(&query).await
+    }
+
+    fn declarations_query(
+        &self,
+        request: &LlmRequest,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> crate::types::SearchQuery {
+        crate::types::SearchQuery {
             query: request.query.clone().unwrap_or_default(),
             kind: crate::types::SearchKind::Exact,
             filters: self.build_filters(request),
-            limit: request.limit,
-        };
-
-        self.query_engine.search(&query).await
+            limit,
+            offset,
+            filter_combine: request.filter_combine,
+        }
     }
 
     fn build_filters(&self, request: &LlmRequest) -> Vec<crate::types::SearchFilter> {
@@ -110,13 +378,29 @@ impl LlmExporter {
         let mut exports = Vec::new();
 
         for result in search_results {
-            let export = self.create_export(&result.declaration, &result.file_path, request).await?;
-            exports.push(export);
+            match request.granularity {
+                ExportGranularity::Declaration => {
+                    exports.push(self.create_export(&result.declaration, &result.file_path, request).await?);
+                }
+                ExportGranularity::Method => {
+                    for method in &result.declaration.methods {
+                        exports.push(self.create_method_export(method, &result.file_path, request).await?);
+                    }
+                }
+            }
         }
 
+        if request.dedup {
+            let mut seen = HashSet::new();
+            exports.retain(|export| seen.insert(Self::dedup_key(export)));
+        }
         Ok(exports)
     }
 
+    fn dedup_key(export: &LlmExport) -> (String, String, (usize, usize)) {
+        (export.file_path.clone(), export.name.clone(), export.line_range)
+    }
+
     async fn create_export(
         &self,
         declaration: &Declaration,
@@ -131,6 +415,29 @@ impl LlmExporter {
             declaration.signature.clone()
         };
 
+        let facets = request
+            .stereotype_facets
+            .iter()
+            .map(|facet| (facet.key.clone(), facet.matches(declaration)))
+            .collect();
+
+        let chunks = match request.max_chunk_tokens {
+            Some(max_tokens) if request.include_source && estimate_tokens(&code) > max_tokens => chunk_source(
+                &code,
+                declaration.range.start_line,
+                &declaration.methods,
+                max_tokens,
+                request.chunk_overlap_tokens,
+            ),
+            _ => Vec::new(),
+        };
+
+        let ancestors = if request.include_ancestors {
+            self.resolve_ancestors(declaration, file_path).await?
+        } else {
+            Vec::new()
+        };
+
         Ok(LlmExport {
             name: declaration.name.clone(),
             kind: format!("{:?}", declaration.kind).to_lowercase(),
@@ -142,6 +449,96 @@ impl LlmExporter {
                 declaration.range.start_line,
                 declaration.range.end_line,
             ),
+            facets,
+            chunks,
+            ancestors,
+            metrics: request.include_metrics.then(|| ExportMetrics {
+                loc: declaration.range.end_line.saturating_sub(declaration.range.start_line) + 1,
+                method_count: declaration.methods.len(),
+                cyclomatic_sum: declaration
+                    .methods
+                    .iter()
+                    .filter_map(|m| m.complexity)
+                    .reduce(|sum, complexity| sum + complexity),
+            }),
+        })
+    }
+
+    /// Follow `extends` through the index, nearest supertype first
+    /// The chain stops at the first supertype that is not indexed (e.g., a library class)
+    async fn resolve_ancestors(&self, declaration: &Declaration, file_path: &Path) -> Result<Vec<String>> {
+        let parser = JavaStructureParser::new()?;
+        let mut ancestors: Vec<String> = Vec::new();
+        let mut visited = vec![declaration.fqn()];
+        let mut next = declaration.extends.clone();
+        let mut package = declaration.package.clone();
+        let mut file_path = file_path.to_path_buf();
+
+        while let Some(supertype) = next.take() {
+            let erased = supertype.split('<').next().unwrap_or(&supertype).trim();
+            let simple_name = erased.rsplit('.').next().unwrap_or(erased);
+
+            // Supertypes are written as seen from the file declaring the subtype
+            let imports = parser.parse_header(&file_path).map(|(_, imports)| imports).unwrap_or_default();
+            let candidates: Vec<SearchResult> = self
+                .query_engine
+                .exact_search(simple_name, Some(20))
+                .await?
+                .into_iter()
+                .filter(|r| r.declaration.name == simple_name)
+                .collect();
+            let Some(result) = resolve_supertype(erased, &package, &imports, &candidates) else {
+                break;
+            };
+            if visited.contains(&result.declaration.fqn()) {
+                break;
+            }
+
+            visited.push(result.declaration.fqn());
+            ancestors.push(result.declaration.name.clone());
+            next = result.declaration.extends.clone();
+            package = result.declaration.package.clone();
+            file_path = result.file_path.clone();
+        }
+
+        Ok(ancestors)
+    }
+
+    async fn create_method_export(
+        &self,
+        method: &Method,
+        file_path: &PathBuf,
+        request: &LlmRequest,
+    ) -> Result<LlmExport> {
+        let parameters: Vec<String> = method
+            .parameters
+            .iter()
+            .map(|p| format!("{} {}", p.type_name, p.name))
+            .collect();
+        let signature = format!("{} {}({})", method.return_type, method.name, parameters.join(", "));
+
+        let code = match (&method.body_range, request.include_source) {
+            // The whole declaration, so the code carries the annotations and signature with the body
+            (Some(_), true) => self.extract_source_code(file_path, &method.range).await?,
+            _ => signature.clone(),
+        };
+
+        Ok(LlmExport {
+            name: method.name.clone(),
+            kind: "method".to_string(),
+            signature,
+            documentation: None,
+            code,
+            file_path: Self::get_relative_path(file_path, &self.project_root)?,
+            line_range: (method.range.start_line, method.range.end_line),
+            facets: BTreeMap::new(),
+            chunks: Vec::new(),
+            ancestors: Vec::new(),
+            metrics: request.include_metrics.then(|| ExportMetrics {
+                loc: method.range.end_line.saturating_sub(method.range.start_line) + 1,
+                method_count: 0,
+                cyclomatic_sum: method.complexity,
+            }),
         })
     }
 
@@ -151,13 +548,7 @@ impl LlmExporter {
         range: &crate::types::SourceRange,
     ) -> Result<String> {
         let content = tokio::fs::read_to_string(file_path).await?;
-        let lines: Vec<&str> = content.lines().collect();
-        
-        let start = range.start_line.saturating_sub(1);
-        let end = range.end_line.min(lines.len());
-        
-        let extracted: Vec<&str> = lines[start..end].to_vec();
-        Ok(extracted.join("\n"))
+        Ok(slice_source(&content, range))
     }
 
     fn get_relative_path(
@@ -176,6 +567,10 @@ impl LlmExporter {
             ExportFormat::Markdown => self.format_markdown(response),
             ExportFormat::LlamaIndex => self.format_llama_index(response),
             ExportFormat::RAG => self.format_rag(response),
+            ExportFormat::StructuredJson => self.format_structured_json(response),
+            ExportFormat::ApiSnapshot => self.format_api_snapshot(response),
+            ExportFormat::Csv => self.format_csv(response),
+            ExportFormat::Yaml => self.format_yaml(response),
         }
     }
 
@@ -183,6 +578,72 @@ impl LlmExporter {
         Ok(serde_json::to_string_pretty(response)?)
     }
 
+    /// serde_yaml writes strings containing line breaks as literal block scalars (`|-`)
+    fn format_yaml(&self, response: &LlmResponse) -> Result<String> {
+        Ok(serde_yaml::to_string(response)?)
+    }
+
+    fn format_structured_json(&self, response: &LlmResponse) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&response.structures)?)
+    }
+
+    fn format_api_snapshot(&self, response: &LlmResponse) -> Result<String> {
+        let mut entries = BTreeMap::new();
+        for structure in &response.structures {
+            Self::collect_api_entries(&structure.class, &mut entries);
+        }
+
+        let snapshot: Vec<ApiSnapshotEntry> = entries.into_values().collect();
+        Ok(serde_json::to_string_pretty(&snapshot)? + "\n")
+    }
+
+    /// Add the public API of a class and its public nested types, keyed by FQN
+    fn collect_api_entries(class: &ClassStructure, entries: &mut BTreeMap<String, ApiSnapshotEntry>) {
+        if !Self::is_public(&class.modifiers, false) {
+            return;
+        }
+
+        // Interface and annotation members are implicitly public
+        let implicitly_public = matches!(class.kind, ClassKind::Interface | ClassKind::Annotation);
+
+        let mut members: Vec<String> = class
+            .fields
+            .iter()
+            .filter(|f| Self::is_public(&f.modifiers, implicitly_public))
+            .map(|f| format!("{} {}", f.type_name, f.name))
+            .collect();
+        members.extend(
+            class
+                .methods
+                .iter()
+                .filter(|m| Self::is_public(&m.modifiers, implicitly_public))
+                .map(|m| {
+                    let parameters: Vec<&str> = m.parameters.iter().map(|p| p.type_name.as_str()).collect();
+                    format!("{} {}({})", m.return_type, m.name, parameters.join(", "))
+                }),
+        );
+        members.sort();
+        members.dedup();
+
+        entries.insert(
+            class.fqn.clone(),
+            ApiSnapshotEntry {
+                fqn: class.fqn.clone(),
+                kind: format!("{:?}", class.kind).to_lowercase(),
+                members,
+            },
+        );
+
+        for nested in &class.nested_classes {
+            Self::collect_api_entries(nested, entries);
+        }
+    }
+
+    fn is_public(modifiers: &[String], implicitly_public: bool) -> bool {
+        modifiers.iter().any(|m| m == "public")
+            || (implicitly_public && !modifiers.iter().any(|m| m == "private"))
+    }
+
     fn format_jsonl(&self, response: &LlmResponse) -> Result<String> {
         let mut lines = Vec::new();
         for declaration in &response.declarations {
@@ -191,6 +652,25 @@ impl LlmExporter {
         Ok(lines.join("\n"))
     }
 
+    fn format_csv(&self, response: &LlmResponse) -> Result<String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(["name", "kind", "signature", "file_path", "start_line", "end_line", "documentation"])?;
+
+        for declaration in &response.declarations {
+            writer.write_record([
+                declaration.name.as_str(),
+                declaration.kind.as_str(),
+                declaration.signature.as_str(),
+                declaration.file_path.as_str(),
+                &declaration.line_range.0.to_string(),
+                &declaration.line_range.1.to_string(),
+                declaration.documentation.as_deref().unwrap_or(""),
+            ])?;
+        }
+
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
     fn format_markdown(&self, response: &LlmResponse) -> Result<String> {
         let mut markdown = String::new();
         
@@ -199,6 +679,13 @@ impl LlmExporter {
         markdown.push_str(&format!("**Exported at:** {}\n\n", response.metadata.exported_at.format("%Y-%m-%d %H:%M:%S UTC")));
         markdown.push_str(&format!("**Project root:** {}\n\n", response.metadata.project_root));
 
+        // {@link} targets that are part of this export become links to their section
+        let anchors: HashMap<&str, String> = response
+            .declarations
+            .iter()
+            .map(|d| (d.name.as_str(), markdown_anchor(&d.name)))
+            .collect();
+
         for declaration in &response.declarations {
             markdown.push_str(&format!("## {}\n\n", declaration.name));
             markdown.push_str(&format!("**Type:** {}\n\n", declaration.kind));
@@ -208,7 +695,9 @@ impl LlmExporter {
                 declaration.line_range.1));
             
             if let Some(doc) = &declaration.documentation {
-                markdown.push_str(&format!("**Documentation:**\n```\n{}\n```\n\n", doc));
+                let linked = link_javadoc(doc, &anchors);
+                let quoted: Vec<String> = linked.lines().map(|line| format!("> {}", line).trim_end().to_string()).collect();
+                markdown.push_str(&format!("**Documentation:**\n\n{}\n\n", quoted.join("\n")));
             }
 
             markdown.push_str(&format!("**Signature:**\n```java\n{}\n```\n\n", declaration.signature));
@@ -232,6 +721,7 @@ impl LlmExporter {
                     file_path: declaration.file_path.clone(),
                     line_range: declaration.line_range,
                     documentation: declaration.documentation.clone(),
+                    facets: declaration.facets.clone(),
                 },
             };
             llama_docs.push(document);
@@ -244,18 +734,39 @@ impl LlmExporter {
         let mut chunks = Vec::new();
         
         for declaration in &response.declarations {
-            let chunk = RagChunk {
-                content: format!("{}\n\n{}", declaration.signature, declaration.code),
-                metadata: RagMetadata {
-                    source: declaration.file_path.clone(),
-                    name: declaration.name.clone(),
-                    kind: declaration.kind.clone(),
-                    line_range: declaration.line_range,
-                    documentation: declaration.documentation.clone(),
-                    chunk_type: "declaration".to_string(),
-                },
+            let metadata = RagMetadata {
+                source: declaration.file_path.clone(),
+                name: declaration.name.clone(),
+                kind: declaration.kind.clone(),
+                line_range: declaration.line_range,
+                documentation: declaration.documentation.clone(),
+                chunk_type: "declaration".to_string(),
+                facets: declaration.facets.clone(),
+                chunk_index: None,
+                total_chunks: None,
             };
-            chunks.push(chunk);
+
+            if declaration.chunks.is_empty() {
+                chunks.push(RagChunk {
+                    content: format!("{}\n\n{}", declaration.signature, declaration.code),
+                    metadata,
+                });
+                continue;
+            }
+
+            // Every sub-chunk repeats the signature so it stays identifiable on its own
+            let total_chunks = declaration.chunks.len();
+            for (chunk_index, code_chunk) in declaration.chunks.iter().enumerate() {
+                chunks.push(RagChunk {
+                    content: format!("{}\n\n{}", declaration.signature, code_chunk.content),
+                    metadata: RagMetadata {
+                        line_range: code_chunk.line_range,
+                        chunk_index: Some(chunk_index),
+                        total_chunks: Some(total_chunks),
+                        ..metadata.clone()
+                    },
+                });
+            }
         }
 
         Ok(serde_json::to_string_pretty(&chunks)?)
@@ -264,24 +775,131 @@ impl LlmExporter {
     pub async fn export_to_file(
         &self,
         request: LlmRequest,
-        output_path: &PathBuf,
+        output_path: &Path,
     ) -> Result<()> {
         let response = self.export(request.clone()).await?;
         let formatted = self.format_export(&response, &request.format)?;
-        
-        tokio::fs::write(output_path, formatted).await?;
+
+        Self::write_export(output_path, &formatted).await
+    }
+
+    /// Write formatted export output, gzip-compressed when the path ends in `.gz`
+    pub async fn write_export(output_path: &Path, formatted: &str) -> Result<()> {
+        if output_path.extension().is_some_and(|e| e == "gz") {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(formatted.as_bytes())?;
+            tokio::fs::write(output_path, encoder.finish()?).await?;
+        } else {
+            tokio::fs::write(output_path, formatted).await?;
+        }
         Ok(())
     }
 
+    /// Summarize the indexed project: packages, Maven modules, entry points and statistics
+    pub async fn export_manifest(&self) -> Result<ProjectManifest> {
+        let declarations = self.query_engine.all_declarations()?;
+
+        let mut package_counts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut entry_points = Vec::new();
+        for (fqn, declaration) in &declarations {
+            let package = fqn
+                .strip_suffix(declaration.name.as_str())
+                .and_then(|prefix| prefix.strip_suffix('.'))
+                .unwrap_or("");
+            *package_counts.entry(package.to_string()).or_default() += 1;
+
+            if declaration.annotations.iter().any(|a| a.name == "SpringBootApplication") {
+                entry_points.push(EntryPoint {
+                    fqn: fqn.clone(),
+                    kind: EntryPointKind::SpringBootApplication,
+                });
+            }
+            let has_main = declaration.methods.iter().any(|m| {
+                m.name == "main"
+                    && m.return_type == "void"
+                    && m.parameters.len() == 1
+                    && ["public", "static"].iter().all(|modifier| m.modifiers.iter().any(|m| m == modifier))
+            });
+            if has_main {
+                entry_points.push(EntryPoint {
+                    fqn: fqn.clone(),
+                    kind: EntryPointKind::MainMethod,
+                });
+            }
+        }
+        entry_points.sort_by(|a, b| a.fqn.cmp(&b.fqn));
+
+        let stereotype_counts = StereotypeFacet::defaults()
+            .into_iter()
+            .map(|facet| {
+                let count = declarations.iter().filter(|(_, d)| facet.matches(d)).count();
+                (facet.key, count)
+            })
+            .collect();
+
+        Ok(ProjectManifest {
+            project_root: self.project_root.to_string_lossy().to_string(),
+            packages: package_counts
+                .into_iter()
+                .map(|(name, declaration_count)| PackageSummary { name, declaration_count })
+                .collect(),
+            modules: self.maven_modules()?,
+            entry_points,
+            statistics: self.query_engine.get_statistics().await?,
+            stereotype_counts,
+            generated_at: chrono::Utc::now(),
+        })
+    }
+
+    /// The pom.xml files under the project root, dependencies first
+    fn maven_modules(&self) -> Result<Vec<ModuleSummary>> {
+        if !self.project_root.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let maven_parser = MavenParser::new()?;
+        let mut modules = Vec::new();
+        for path in FileParser::new()?.find_source_files(&self.project_root, &[])? {
+            if FileSuffix::from_path(&path) == Some(FileSuffix::Pom) {
+                modules.push(maven_parser.parse_pom_file(&path)?);
+            }
+        }
+
+        let graph = DependencyGraph::from_modules(&modules);
+        let key = |module: &MavenModule| {
+            format!(
+                "{}:{}",
+                module.group_id.as_deref().unwrap_or("?"),
+                module.artifact_id.as_deref().unwrap_or("?")
+            )
+        };
+        // A cyclic build has no order, so keep the modules sorted by key instead
+        let order = graph.topological_order().unwrap_or_default();
+        modules.sort_by_key(|module| {
+            let key = key(module);
+            (order.iter().position(|k| *k == key).unwrap_or(usize::MAX), key)
+        });
+
+        modules
+            .iter()
+            .map(|module| {
+                let key = key(module);
+                Ok(ModuleSummary {
+                    path: Self::get_relative_path(&module.path, &self.project_root)?,
+                    coordinates: format!("{}:{}", key, module.version.as_deref().unwrap_or("?")),
+                    depends_on: graph.get_dependencies(&key).into_iter().map(str::to_string).collect(),
+                })
+            })
+            .collect()
+    }
+
     pub async fn export_service_classes(&self, limit: Option<usize>) -> Result<LlmResponse> {
         let request = LlmRequest {
-            query: None,
             kind: Some(DeclarationKind::Class),
             annotations: vec!["Service".to_string(), "Component".to_string()],
-            package: None,
             limit,
             include_source: true,
-            format: ExportFormat::Json,
+            ..Default::default()
         };
         
         self.export(request).await
@@ -289,13 +907,10 @@ impl LlmExporter {
 
     pub async fn export_interfaces(&self, limit: Option<usize>) -> Result<LlmResponse> {
         let request = LlmRequest {
-            query: None,
             kind: Some(DeclarationKind::Interface),
-            annotations: vec![],
-            package: None,
             limit,
             include_source: true,
-            format: ExportFormat::Json,
+            ..Default::default()
         };
         
         self.export(request).await
@@ -303,17 +918,105 @@ impl LlmExporter {
 
     pub async fn export_controllers(&self, limit: Option<usize>) -> Result<LlmResponse> {
         let request = LlmRequest {
-            query: None,
             kind: Some(DeclarationKind::Class),
             annotations: vec!["Controller".to_string(), "RestController".to_string()],
-            package: None,
             limit,
             include_source: true,
-            format: ExportFormat::Json,
+            ..Default::default()
         };
         
         self.export(request).await
     }
+
+    /// Method-level exports of every method carrying one of `annotations` (e.g. "GetMapping"),
+    /// for documenting endpoints; `limit` caps the number of methods
+    pub async fn export_methods_by_annotation(&self, annotations: &[String], limit: Option<usize>) -> Result<LlmResponse> {
+        let request = LlmRequest {
+            annotations: annotations.to_vec(),
+            limit,
+            include_source: true,
+            granularity: ExportGranularity::Method,
+            ..Default::default()
+        };
+
+        // The annotations are on the methods, so go through every declaration rather than filtering on them;
+        // uncached, since the whole index would otherwise stay in the cache
+        let everything = crate::types::SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: None,
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let declarations = self.query_engine.search_all(&everything).await?;
+        let mut exports = Vec::new();
+        let mut seen = HashSet::new();
+        'declarations: for result in &declarations {
+            for method in &result.declaration.methods {
+                if limit.is_some_and(|limit| exports.len() >= limit) {
+                    break 'declarations;
+                }
+                let annotated = method
+                    .annotations
+                    .iter()
+                    .any(|a| annotations.iter().any(|wanted| a.name == wanted.trim_start_matches('@')));
+                if annotated {
+                    let export = self.create_method_export(method, &result.file_path, &request).await?;
+                    if !request.dedup || seen.insert(Self::dedup_key(&export)) {
+                        exports.push(export);
+                    }
+                }
+            }
+        }
+
+        Ok(LlmResponse {
+            metadata: ExportMetadata {
+                total_count: exports.len(),
+                query: request,
+                exported_at: chrono::Utc::now(),
+                project_root: self.project_root.to_string_lossy().to_string(),
+            },
+            declarations: exports,
+            structures: Vec::new(),
+        })
+    }
+}
+
+/// The declaration a supertype written as `written` refers to, among the indexed
+/// declarations of the same simple name, looked up the way javac does: a qualified name,
+/// a single-type import, the same package, then on-demand imports; a name declared only
+/// once in the project is taken as is
+fn resolve_supertype<'a>(
+    written: &str,
+    package: &str,
+    imports: &[Import],
+    candidates: &'a [SearchResult],
+) -> Option<&'a SearchResult> {
+    let with_fqn = |fqn: &str| candidates.iter().find(|c| c.declaration.fqn() == fqn);
+    let in_package = |package: &str| candidates.iter().find(|c| c.declaration.package == package);
+
+    if written.contains('.') {
+        return with_fqn(written);
+    }
+    // An imported type shadows the project's types of the same name, even if it is not indexed
+    if let Some(import) = imports
+        .iter()
+        .find(|i| !i.is_static && !i.is_wildcard && i.path.rsplit('.').next() == Some(written))
+    {
+        return with_fqn(&import.path);
+    }
+    in_package(package)
+        .or_else(|| {
+            imports
+                .iter()
+                .filter(|i| !i.is_static && i.is_wildcard)
+                .find_map(|i| in_package(&i.path))
+        })
+        .or(match candidates {
+            [single] => Some(single),
+            _ => None,
+        })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -330,6 +1033,15 @@ struct LlamIndexMetadata {
     file_path: String,
     line_range: (usize, usize),
     documentation: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    facets: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ApiSnapshotEntry {
+    fqn: String,
+    kind: String,
+    members: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -346,11 +1058,189 @@ struct RagMetadata {
     line_range: (usize, usize),
     documentation: Option<String>,
     chunk_type: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    facets: BTreeMap<String, bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunk_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_chunks: Option<usize>,
+}
+
+/// GitHub-style anchor of a Markdown heading ("UserService" -> "userservice")
+fn markdown_anchor(heading: &str) -> String {
+    heading
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c.to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rewrite `{@link Type#member label}`, `{@linkplain ...}` and `@see Type` references as
+/// Markdown links when `Type` (simple or qualified) has an anchor, and as plain text otherwise
+fn link_javadoc(documentation: &str, anchors: &HashMap<&str, String>) -> String {
+    let render = |reference: &str, label: Option<&str>| {
+        let target = reference.split('#').next().unwrap_or(reference);
+        let simple_name = target.rsplit('.').next().unwrap_or(target);
+        let text = label.unwrap_or(reference);
+        match anchors.get(target).or_else(|| anchors.get(simple_name)) {
+            Some(anchor) if !target.is_empty() => format!("[{}](#{})", text, anchor),
+            _ => text.to_string(),
+        }
+    };
+
+    let mut linked = String::with_capacity(documentation.len());
+    let mut rest = documentation;
+    while let Some(start) = rest.find("{@link") {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        linked.push_str(&rest[..start]);
+        let tag = &rest[start + 2..start + length];
+        let body = tag.trim_start_matches("linkplain").trim_start_matches("link").trim();
+        let (reference, label) = match body.split_once(char::is_whitespace) {
+            Some((reference, label)) => (reference, Some(label.trim())),
+            None => (body, None),
+        };
+        linked.push_str(&render(reference, label));
+        rest = &rest[start + length + 1..];
+    }
+    linked.push_str(rest);
+
+    linked
+        .lines()
+        .map(|line| match line.trim_start().strip_prefix("@see ") {
+            Some(see) => {
+                let (reference, label) = match see.trim().split_once(char::is_whitespace) {
+                    Some((reference, label)) => (reference, Some(label.trim())),
+                    None => (see.trim(), None),
+                };
+                // Quoted-string and HTML forms of @see are kept as written
+                if reference.starts_with('[') || reference.starts_with('"') || reference.starts_with('<') {
+                    line.to_string()
+                } else {
+                    format!("See {}", render(reference, label))
+                }
+            }
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Rough token count for embedding models (about four characters per token)
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Split source code starting at `first_line` into chunks of at most `max_tokens`,
+/// cutting between methods where possible and only inside a method that alone
+/// exceeds the limit. Each chunk after the first starts with the last lines of
+/// the previous one, up to `overlap_tokens`.
+fn chunk_source(
+    code: &str,
+    first_line: usize,
+    methods: &[Method],
+    max_tokens: usize,
+    overlap_tokens: usize,
+) -> Vec<CodeChunk> {
+    let lines: Vec<&str> = code.lines().collect();
+    let line_tokens = |index: usize| estimate_tokens(lines[index]) + 1;
+
+    // Cut points (indexes into `lines`) at the start of each method
+    let mut cuts: Vec<usize> = methods
+        .iter()
+        .map(|m| m.range.start_line.saturating_sub(first_line))
+        .filter(|&index| index > 0 && index < lines.len())
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+    cuts.push(lines.len());
+
+    // Method-aligned groups of lines
+    let mut units: Vec<std::ops::Range<usize>> = Vec::new();
+    let mut start = 0;
+    for cut in cuts {
+        if cut > start {
+            units.push(start..cut);
+            start = cut;
+        }
+    }
+
+    // Where the chunk following lines[start..end] begins, and the tokens it carries over
+    let overlap_from = |start: usize, end: usize| {
+        let mut overlap_start = end;
+        let mut overlap_size = 0;
+        while overlap_start > start + 1 {
+            let size = line_tokens(overlap_start - 1);
+            if overlap_size + size > overlap_tokens.min(max_tokens / 2) {
+                break;
+            }
+            overlap_size += size;
+            overlap_start -= 1;
+        }
+        (overlap_start, overlap_size)
+    };
+
+    let mut chunks: Vec<std::ops::Range<usize>> = Vec::new();
+    let mut chunk_start = 0;
+    let mut chunk_tokens = 0;
+    for unit in units {
+        let unit_tokens: usize = unit.clone().map(line_tokens).sum();
+        if chunk_tokens + unit_tokens > max_tokens && unit.start > chunk_start {
+            chunks.push(chunk_start..unit.start);
+            (chunk_start, chunk_tokens) = overlap_from(chunk_start, unit.start);
+        }
+        for index in unit {
+            // A unit larger than the limit is cut between lines
+            if chunk_tokens + line_tokens(index) > max_tokens && index > chunk_start {
+                chunks.push(chunk_start..index);
+                (chunk_start, chunk_tokens) = overlap_from(chunk_start, index);
+            }
+            chunk_tokens += line_tokens(index);
+        }
+    }
+    chunks.push(chunk_start..lines.len());
+
+    chunks
+        .into_iter()
+        .map(|range| CodeChunk {
+            content: lines[range.clone()].join("\n"),
+            line_range: (first_line + range.start, first_line + range.end - 1),
+        })
+        .collect()
+}
+
+/// Text covered by `range`: its lines, with the first and last trimmed to its columns.
+/// Columns are 1-based byte offsets with an exclusive end, as produced by the parsers;
+/// a column of 0, or one that doesn't fall on a character boundary, keeps the whole line.
+fn slice_source(content: &str, range: &crate::types::SourceRange) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = range.start_line.saturating_sub(1);
+    let end = range.end_line.min(lines.len());
+    if start >= end {
+        return String::new();
+    }
+
+    let mut extracted: Vec<&str> = lines[start..end].to_vec();
+    let last = extracted.len() - 1;
+    if range.end_line == end && range.end_column > 0 {
+        let line = extracted[last];
+        extracted[last] = line.get(..range.end_column - 1).unwrap_or(line);
+    }
+    if range.start_column > 0 {
+        let line = extracted[0];
+        extracted[0] = line.get(range.start_column - 1..).unwrap_or(line);
+    }
+    extracted.join("\n")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::index_sources;
     use tempfile::tempdir;
 
     #[tokio::test]
@@ -363,12 +1253,8 @@ mod tests {
         
         let request = LlmRequest {
             query: Some("test".to_string()),
-            kind: None,
-            annotations: vec![],
-            package: None,
             limit: Some(10),
-            include_source: false,
-            format: ExportFormat::Json,
+            ..Default::default()
         };
 
         let response = exporter.export(request).await.unwrap();
@@ -376,8 +1262,600 @@ mod tests {
         assert_eq!(response.metadata.total_count, 0);
     }
 
-    #[tokio::test]
-    async fn test_format_export() {
+    #[test]
+    fn test_export_schema() {
+        let schema = serde_json::to_value(export_schema()).unwrap();
+
+        assert_eq!(schema["properties"]["declarations"]["type"], "array");
+        assert_eq!(
+            schema["properties"]["declarations"]["items"]["$ref"],
+            "#/definitions/LlmExport"
+        );
+
+        let export_properties = &schema["definitions"]["LlmExport"]["properties"];
+        for property in ["name", "kind", "signature", "documentation", "code", "file_path", "line_range"] {
+            assert!(export_properties.get(property).is_some(), "missing {}", property);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stereotype_facets() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(
+            dir.path(),
+            &[("UserService.java", "@Service\npublic class UserService {}\n")],
+        )
+        .await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("UserService".to_string()),
+            limit: Some(10),
+            stereotype_facets: StereotypeFacet::defaults(),
+            ..Default::default()
+        };
+
+        let response = exporter.export(request).await.unwrap();
+        assert_eq!(response.declarations.len(), 1);
+
+        let facets = &response.declarations[0].facets;
+        assert_eq!(facets.get("is_service"), Some(&true));
+        assert_eq!(facets.get("is_controller"), Some(&false));
+        assert_eq!(facets.get("is_repository"), Some(&false));
+        assert_eq!(facets.get("is_entity"), Some(&false));
+    }
+
+    #[tokio::test]
+    async fn test_export_controllers_matches_any_annotation() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(
+            dir.path(),
+            &[
+                ("PageController.java", "package com.example;\n\n@Controller\npublic class PageController {}\n"),
+                ("ApiController.java", "package com.example;\n\n@RestController\npublic class ApiController {}\n"),
+            ],
+        )
+        .await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let response = exporter.export_controllers(Some(10)).await.unwrap();
+        let mut names: Vec<&str> = response.declarations.iter().map(|d| d.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["ApiController", "PageController"]);
+    }
+
+    #[tokio::test]
+    async fn test_rag_chunks_split_large_class() {
+        let dir = tempdir().unwrap();
+
+        let mut java_content = String::from("package com.example;\n\npublic class ReportService {\n");
+        for i in 0..8 {
+            java_content.push_str(&format!(
+                "    public String report{i}(String input) {{\n        String trimmed = input.trim();\n        return trimmed + \"{i}\";\n    }}\n\n"
+            ));
+        }
+        java_content.push_str("}\n");
+        let index_manager = index_sources(dir.path(), &[("ReportService.java", &java_content)]).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("ReportService".to_string()),
+            limit: Some(10),
+            include_source: true,
+            format: ExportFormat::RAG,
+            max_chunk_tokens: Some(80),
+            chunk_overlap_tokens: 10,
+            ..Default::default()
+        };
+        let response = exporter.export(request).await.unwrap();
+        let json = exporter.format_export(&response, &ExportFormat::RAG).unwrap();
+
+        let chunks: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert!(chunks.len() > 1, "expected several chunks, got {}", chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk["metadata"]["chunk_index"], i);
+            assert_eq!(chunk["metadata"]["total_chunks"], chunks.len());
+            let content = chunk["content"].as_str().unwrap();
+            assert!(content.starts_with("public ReportService"), "{}", content);
+            assert!(content.len() / 4 <= 80 + "public ReportService".len());
+        }
+
+        // The chunks cover the whole class, in order
+        let first = &chunks[0]["metadata"]["line_range"];
+        let last = &chunks[chunks.len() - 1]["metadata"]["line_range"];
+        assert_eq!(first[0], 3);
+        assert_eq!(last[1], java_content.lines().count());
+        assert!(chunks[1]["content"].as_str().unwrap().contains("public String report"));
+    }
+
+    #[tokio::test]
+    async fn test_api_snapshot_is_stable() {
+        let dir = tempdir().unwrap();
+        let sources = [
+            ("UserService.java", r#"
+                package com.example;
+
+                public class UserService {
+                    private final UserRepository repository;
+
+                    public User find(Long id) { return repository.find(id); }
+
+                    void evictCache() {}
+
+                    public static class Options {
+                        public int retries;
+                    }
+                }
+            "#),
+            ("UserRepository.java", r#"
+                package com.example;
+
+                public interface UserRepository {
+                    User find(Long id);
+                    void save(User user);
+                }
+            "#),
+        ];
+        let index_manager = index_sources(dir.path(), &sources).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let snapshot = || async {
+            let request = LlmRequest {
+                query: Some("*".to_string()),
+                format: ExportFormat::ApiSnapshot,
+                ..Default::default()
+            };
+            let response = exporter.export(request).await.unwrap();
+            exporter.format_export(&response, &ExportFormat::ApiSnapshot).unwrap()
+        };
+
+        let first = snapshot().await;
+        let second = snapshot().await;
+        assert_eq!(first, second);
+        assert!(!first.contains("exported_at"));
+
+        let entries: serde_json::Value = serde_json::from_str(&first).unwrap();
+        let fqns: Vec<&str> = entries.as_array().unwrap().iter().map(|e| e["fqn"].as_str().unwrap()).collect();
+        assert_eq!(fqns, vec!["com.example.UserRepository", "com.example.UserService", "com.example.UserService.Options"]);
+        assert_eq!(entries[0]["members"], serde_json::json!(["User find(Long)", "void save(User)"]));
+        assert_eq!(entries[1]["members"], serde_json::json!(["User find(Long)"]));
+    }
+
+    #[tokio::test]
+    async fn test_api_snapshot_without_limit_has_every_declaration() {
+        let dir = tempdir().unwrap();
+
+        // More declarations than the default search limit
+        let total = crate::query::DEFAULT_SEARCH_LIMIT + 20;
+        let types: String = (0..total).map(|i| format!("public class Type{} {{}}\n", i)).collect();
+        let source = format!("package com.example;\n\n{}", types);
+        let index_manager = index_sources(dir.path(), &[("Api.java", &source)]).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("*".to_string()),
+            package: Some("com.example".to_string()),
+            format: ExportFormat::ApiSnapshot,
+            ..Default::default()
+        };
+        let response = exporter.export(request).await.unwrap();
+        let snapshot = exporter.format_export(&response, &ExportFormat::ApiSnapshot).unwrap();
+
+        let entries: serde_json::Value = serde_json::from_str(&snapshot).unwrap();
+        assert_eq!(entries.as_array().unwrap().len(), total);
+        let (cache_entries, _, _) = exporter.query_engine().get_cache_stats().await;
+        assert_eq!(cache_entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_export_jsonl_streaming() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(
+            dir.path(),
+            &[
+                ("Alpha.java", "package com.example;\n\npublic class Alpha {}\n"),
+                ("Beta.java", "package com.example;\n\npublic class Beta {}\n"),
+                ("Gamma.java", "package com.example;\n\npublic class Gamma {}\n"),
+                ("Delta.java", "package com.example;\n\npublic class Delta {}\n"),
+            ],
+        )
+        .await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("*".to_string()),
+            format: ExportFormat::Jsonl,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        let written = exporter.export_jsonl_streaming(request, &mut buffer).await.unwrap();
+
+        let output = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(written, 4);
+        assert_eq!(lines.len(), 4);
+        for line in lines {
+            let export: LlmExport = serde_json::from_str(line).unwrap();
+            assert_eq!(export.kind, "class");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dedup_repeated_search_results() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(
+            dir.path(),
+            &[("Orders.java", "package com.example;\n\npublic class Orders {}\n\nclass OrderLine {}\n")],
+        )
+        .await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let mut request = LlmRequest {
+            query: Some("*".to_string()),
+            ..Default::default()
+        };
+        let mut results = exporter.find_declarations(&request).await.unwrap();
+        assert_eq!(results.len(), 2);
+        results.extend(results.clone());
+        results.push(results[0].clone());
+
+        let exports = exporter.convert_to_exports(results.clone(), &request).await.unwrap();
+        let mut names: Vec<&str> = exports.iter().map(|e| e.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["OrderLine", "Orders"]);
+
+        request.dedup = false;
+        assert_eq!(exporter.convert_to_exports(results, &request).await.unwrap().len(), 5);
+
+        // Requests written before the option existed keep deduplicating
+        let json = serde_json::to_value(&request).unwrap();
+        let mut object = json.as_object().unwrap().clone();
+        object.remove("dedup");
+        let request: LlmRequest = serde_json::from_value(serde_json::Value::Object(object)).unwrap();
+        assert!(request.dedup);
+    }
+
+    #[tokio::test]
+    async fn test_export_methods_by_annotation() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(dir.path(), &[("UserController.java", r#"package com.example;
+
+@RestController
+public class UserController {
+    @GetMapping("/users")
+    public List<User> list() {
+        return service.findAll();
+    }
+
+    public void audit(String action) {
+        log.info(action);
+    }
+}
+"#)]).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let mappings = vec!["GetMapping".to_string(), "PostMapping".to_string()];
+        let response = exporter.export_methods_by_annotation(&mappings, None).await.unwrap();
+        assert_eq!(response.metadata.total_count, 1);
+        assert_eq!(response.declarations[0].name, "list");
+        assert_eq!(response.declarations[0].kind, "method");
+        assert_eq!(response.declarations[0].file_path, "UserController.java");
+
+        let none = exporter.export_methods_by_annotation(&["DeleteMapping".to_string()], None).await.unwrap();
+        assert!(none.declarations.is_empty());
+
+        // Scanning every declaration leaves nothing behind in the query cache
+        let (cache_entries, cached_results, _) = exporter.query_engine().get_cache_stats().await;
+        assert_eq!((cache_entries, cached_results), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_source_code_bounded_by_columns() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(
+            dir.path(),
+            &[("Pair.java", "package com.example;\n\nclass Left { int a; } class Right {\n    int b;\n} // trailing\n")],
+        )
+        .await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("*".to_string()),
+            include_source: true,
+            ..Default::default()
+        };
+        let response = exporter.export(request).await.unwrap();
+        let code = |name: &str| {
+            response.declarations.iter().find(|e| e.name == name).map(|e| e.code.clone()).unwrap()
+        };
+        assert_eq!(code("Left"), "class Left { int a; }");
+        assert_eq!(code("Right"), "class Right {\n    int b;\n}");
+    }
+
+    #[test]
+    fn test_slice_source_without_columns() {
+        let range = crate::types::SourceRange {
+            start_line: 2,
+            start_column: 0,
+            end_line: 3,
+            end_column: 0,
+        };
+        assert_eq!(slice_source("a\nbb\ncc\nd", &range), "bb\ncc");
+    }
+
+    #[tokio::test]
+    async fn test_export_to_gzip_file() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(
+            dir.path(),
+            &[
+                ("Alpha.java", "package com.example;\n\npublic class Alpha {}\n"),
+                ("Beta.java", "package com.example;\n\npublic class Beta {}\n"),
+                ("Gamma.java", "package com.example;\n\npublic class Gamma {}\n"),
+            ],
+        )
+        .await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("*".to_string()),
+            include_source: true,
+            format: ExportFormat::Jsonl,
+            ..Default::default()
+        };
+        let plain_path = dir.path().join("export.jsonl");
+        let gzip_path = dir.path().join("export.jsonl.gz");
+        exporter.export_to_file(request.clone(), &plain_path).await.unwrap();
+        exporter.export_to_file(request, &gzip_path).await.unwrap();
+
+        let compressed = std::fs::read(&gzip_path).unwrap();
+        assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+        let mut decompressed = String::new();
+        std::io::Read::read_to_string(&mut flate2::read::GzDecoder::new(&compressed[..]), &mut decompressed).unwrap();
+
+        let plain = std::fs::read_to_string(&plain_path).unwrap();
+        assert_eq!(plain.lines().count(), 3);
+        assert_eq!(decompressed, plain);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_export_bypasses_cache() {
+        let dir = tempdir().unwrap();
+
+        // More declarations than fit in two pages
+        let total = EXPORT_PAGE_SIZE * 2 + 1;
+        let source: String = (0..total).map(|i| format!("class Type{} {{}}\n", i)).collect();
+        let index_manager = index_sources(dir.path(), &[("Many.java", &source)]).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("*".to_string()),
+            format: ExportFormat::Jsonl,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        let written = exporter.export_jsonl_streaming(request, &mut buffer).await.unwrap();
+        assert_eq!(written, total);
+
+        let (cache_entries, cached_results, _) = exporter.query_engine().get_cache_stats().await;
+        assert_eq!((cache_entries, cached_results), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_export_ancestors() {
+        let dir = tempdir().unwrap();
+        let sources = [
+            ("BaseEntity.java", "package com.example;\n\npublic abstract class BaseEntity implements Serializable {}\n"),
+            ("Person.java", "package com.example;\n\npublic abstract class Person extends BaseEntity {}\n"),
+            ("Employee.java", "package com.example;\n\npublic class Employee extends Person {}\n"),
+            ("Report.java", "package com.example;\n\npublic class Report extends java.util.AbstractList<String> {}\n"),
+        ];
+        let index_manager = index_sources(dir.path(), &sources).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = |query: &str| LlmRequest {
+            query: Some(query.to_string()),
+            include_ancestors: true,
+            ..Default::default()
+        };
+
+        let response = exporter.export(request("Employee")).await.unwrap();
+        let employee = response.declarations.iter().find(|d| d.name == "Employee").unwrap();
+        assert_eq!(employee.ancestors, vec!["Person", "BaseEntity"]);
+
+        // A supertype outside the index ends the chain right away
+        let response = exporter.export(request("Report")).await.unwrap();
+        let report = response.declarations.iter().find(|d| d.name == "Report").unwrap();
+        assert!(report.ancestors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_ancestors_resolves_ambiguous_names() {
+        let dir = tempdir().unwrap();
+
+        // Two BaseEntity classes: the one meant is the imported one, else the one of the same package
+        let sources = [
+            ("legacy/BaseEntity.java", "package com.legacy;\n\npublic class BaseEntity extends LegacyRecord {}\n"),
+            ("legacy/LegacyRecord.java", "package com.legacy;\n\npublic class LegacyRecord {}\n"),
+            ("legacy/Invoice.java", "package com.legacy;\n\npublic class Invoice extends BaseEntity {}\n"),
+            ("model/BaseEntity.java", "package com.model;\n\npublic class BaseEntity extends Auditable {}\n"),
+            ("model/Auditable.java", "package com.model;\n\npublic class Auditable {}\n"),
+            (
+                "app/Order.java",
+                "package com.app;\n\nimport com.model.BaseEntity;\n\npublic class Order extends BaseEntity {}\n",
+            ),
+            (
+                "app/Shipment.java",
+                "package com.app;\n\nimport com.legacy.*;\n\npublic class Shipment extends BaseEntity {}\n",
+            ),
+        ];
+        let index_manager = index_sources(dir.path(), &sources).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let ancestors = |name: &'static str| {
+            let exporter = &exporter;
+            async move {
+                let request = LlmRequest {
+                    query: Some(name.to_string()),
+                    include_ancestors: true,
+                    ..Default::default()
+                };
+                let response = exporter.export(request).await.unwrap();
+                response.declarations.into_iter().find(|d| d.name == name).unwrap().ancestors
+            }
+        };
+
+        assert_eq!(ancestors("Order").await, vec!["BaseEntity", "Auditable"]);
+        assert_eq!(ancestors("Invoice").await, vec!["BaseEntity", "LegacyRecord"]);
+        assert_eq!(ancestors("Shipment").await, vec!["BaseEntity", "LegacyRecord"]);
+    }
+
+    #[tokio::test]
+    async fn test_export_metrics() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(dir.path(), &[("Counter.java", r#"package com.example;
+
+public class Counter {
+    private int count;
+
+    public void increment() {
+        count++;
+    }
+
+    public int get() {
+        return count;
+    }
+}
+"#)]).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+        let mut request = LlmRequest {
+            query: Some("Counter".to_string()),
+            include_metrics: true,
+            ..Default::default()
+        };
+
+        let response = exporter.export(request.clone()).await.unwrap();
+        let metrics = response.declarations[0].metrics.as_ref().unwrap();
+        assert_eq!(metrics.loc, 11);
+        assert_eq!(metrics.method_count, 2);
+        assert_eq!(metrics.cyclomatic_sum, Some(2));
+
+        request.include_metrics = false;
+        let response = exporter.export(request).await.unwrap();
+        assert!(response.declarations[0].metrics.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_method_granularity_export() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(dir.path(), &[("Greeter.java", r#"package com.example;
+
+public class Greeter {
+    public String greet(String name) {
+        return "Hello " + name;
+    }
+
+    public void reset() {
+        count = 0;
+    }
+}
+"#)]).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("Greeter".to_string()),
+            limit: Some(10),
+            include_source: true,
+            granularity: ExportGranularity::Method,
+            ..Default::default()
+        };
+        let response = exporter.export(request).await.unwrap();
+
+        assert_eq!(response.declarations.len(), 2);
+        let greet = &response.declarations[0];
+        assert_eq!(greet.kind, "method");
+        assert_eq!(greet.name, "greet");
+        assert_eq!(greet.signature, "String greet(String name)");
+        assert_eq!(greet.code, "public String greet(String name) {\n        return \"Hello \" + name;\n    }");
+        assert_eq!(greet.line_range, (4, 6));
+        assert_eq!(response.declarations[1].signature, "void reset()");
+    }
+
+    #[tokio::test]
+    async fn test_structured_json_export() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(dir.path(), &[("Order.java", r#"
+            package com.example;
+
+            public class Order {
+                private String id;
+
+                public record LineItem(String sku, int quantity) {}
+
+                public static class Builder {
+                    public Order build() { return new Order(); }
+                }
+            }
+        "#)]).await;
+
+        let query_engine = crate::query::QueryEngine::new_with_manager(index_manager).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let request = LlmRequest {
+            query: Some("Order".to_string()),
+            limit: Some(10),
+            format: ExportFormat::StructuredJson,
+            ..Default::default()
+        };
+        let response = exporter.export(request).await.unwrap();
+        let json = exporter.format_export(&response, &ExportFormat::StructuredJson).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let classes = value.as_array().unwrap();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0]["name"], "Order");
+        assert_eq!(classes[0]["file_path"], "Order.java");
+        assert_eq!(classes[0]["fields"][0]["name"], "id");
+
+        let nested = classes[0]["nested_classes"].as_array().unwrap();
+        assert_eq!(nested.len(), 2);
+        assert_eq!(nested[0]["name"], "LineItem");
+        assert_eq!(nested[0]["kind"], "Record");
+        assert_eq!(nested[1]["methods"][0]["name"], "build");
+    }
+
+    #[tokio::test]
+    async fn test_format_export() {
         let dir = tempdir().unwrap();
         let index_path = dir.path().join("test_index");
         let query_engine = crate::query::QueryEngine::new(&index_path).unwrap();
@@ -389,17 +1867,12 @@ mod tests {
             metadata: ExportMetadata {
                 total_count: 0,
                 query: LlmRequest {
-                    query: None,
-                    kind: None,
-                    annotations: vec![],
-                    package: None,
-                    limit: None,
-                    include_source: false,
-                    format: ExportFormat::Json,
+                    ..Default::default()
                 },
                 exported_at: chrono::Utc::now(),
                 project_root: "/test".to_string(),
             },
+            structures: vec![],
         };
 
         let json = exporter.format_export(&response, &ExportFormat::Json).unwrap();
@@ -424,21 +1897,21 @@ mod tests {
                 code: "public class TestClass {}".to_string(),
                 file_path: "TestClass.java".to_string(),
                 line_range: (1, 3),
+                facets: BTreeMap::new(),
+                chunks: vec![],
+                ancestors: Vec::new(),
+                metrics: None,
             }],
             metadata: ExportMetadata {
                 total_count: 1,
                 query: LlmRequest {
-                    query: None,
-                    kind: None,
-                    annotations: vec![],
-                    package: None,
-                    limit: None,
-                    include_source: false,
                     format: ExportFormat::Markdown,
+                    ..Default::default()
                 },
                 exported_at: chrono::Utc::now(),
                 project_root: "/test".to_string(),
             },
+            structures: vec![],
         };
 
         let markdown = exporter.format_export(&response, &ExportFormat::Markdown).unwrap();
@@ -446,4 +1919,137 @@ mod tests {
         assert!(markdown.contains("TestClass"));
         assert!(markdown.contains("Test documentation"));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_markdown_resolves_javadoc_links() {
+        let dir = tempdir().unwrap();
+        let query_engine = crate::query::QueryEngine::new(&dir.path().join("test_index")).unwrap();
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let export = |name: &str, documentation: &str| LlmExport {
+            name: name.to_string(),
+            kind: "class".to_string(),
+            signature: format!("public class {}", name),
+            documentation: Some(documentation.to_string()),
+            code: String::new(),
+            file_path: format!("{}.java", name),
+            line_range: (1, 3),
+            facets: BTreeMap::new(),
+            chunks: vec![],
+            ancestors: Vec::new(),
+            metrics: None,
+        };
+        let mut response: LlmResponse = serde_json::from_value(serde_json::json!({
+            "declarations": [],
+            "metadata": {
+                "total_count": 2,
+                "query": {"query": null, "kind": null, "annotations": [], "package": null, "limit": null,
+                          "include_source": false, "format": "Markdown"},
+                "exported_at": "2024-01-01T00:00:00Z",
+                "project_root": "/test"
+            }
+        }))
+        .unwrap();
+        response.declarations = vec![
+            export(
+                "OrderService",
+                "Places orders through {@link com.example.PaymentGateway#charge the gateway}.\nUses {@link Inventory}.\n@see PaymentGateway",
+            ),
+            export("PaymentGateway", "Charges cards."),
+        ];
+
+        let markdown = exporter.format_export(&response, &ExportFormat::Markdown).unwrap();
+        assert!(markdown.contains("## PaymentGateway"));
+        assert!(markdown.contains("> Places orders through [the gateway](#paymentgateway)."), "{}", markdown);
+        assert!(markdown.contains("> Uses Inventory."));
+        assert!(markdown.contains("> See [PaymentGateway](#paymentgateway)"));
+    }
+
+    #[tokio::test]
+    async fn test_format_csv() {
+        let dir = tempdir().unwrap();
+        let index_path = dir.path().join("test_index");
+        let query_engine = crate::query::QueryEngine::new(&index_path).unwrap();
+
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let response = LlmResponse {
+            declarations: vec![LlmExport {
+                name: "UserService".to_string(),
+                kind: "class".to_string(),
+                signature: "public class UserService".to_string(),
+                documentation: Some("Creates, updates and deletes \"users\"".to_string()),
+                code: "public class UserService {}".to_string(),
+                file_path: "UserService.java".to_string(),
+                line_range: (3, 20),
+                facets: BTreeMap::new(),
+                chunks: vec![],
+                ancestors: Vec::new(),
+                metrics: None,
+            }],
+            metadata: ExportMetadata {
+                total_count: 1,
+                query: LlmRequest {
+                    format: ExportFormat::Csv,
+                    ..Default::default()
+                },
+                exported_at: chrono::Utc::now(),
+                project_root: "/test".to_string(),
+            },
+            structures: vec![],
+        };
+
+        let csv = exporter.format_export(&response, &ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "name,kind,signature,file_path,start_line,end_line,documentation");
+        assert_eq!(
+            lines[1],
+            r#"UserService,class,public class UserService,UserService.java,3,20,"Creates, updates and deletes ""users""""#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format_yaml_round_trip() {
+        let dir = tempdir().unwrap();
+        let index_path = dir.path().join("test_index");
+        let query_engine = crate::query::QueryEngine::new(&index_path).unwrap();
+
+        let exporter = LlmExporter::new(query_engine, dir.path().to_path_buf()).unwrap();
+
+        let response = LlmResponse {
+            declarations: vec![LlmExport {
+                name: "UserService".to_string(),
+                kind: "class".to_string(),
+                signature: "public class UserService".to_string(),
+                documentation: Some("Manages users.\n\n@author \"ops\": team\n  - indented".to_string()),
+                code: "public class UserService {\n}".to_string(),
+                file_path: "UserService.java".to_string(),
+                line_range: (3, 20),
+                facets: BTreeMap::from([("is_service".to_string(), true)]),
+                chunks: vec![],
+                ancestors: Vec::new(),
+                metrics: None,
+            }],
+            metadata: ExportMetadata {
+                total_count: 1,
+                query: LlmRequest {
+                    query: Some("User".to_string()),
+                    limit: Some(10),
+                    include_source: true,
+                    format: ExportFormat::Yaml,
+                    ..Default::default()
+                },
+                exported_at: chrono::Utc::now(),
+                project_root: "/test".to_string(),
+            },
+            structures: vec![],
+        };
+
+        let yaml = exporter.format_export(&response, &ExportFormat::Yaml).unwrap();
+        assert!(yaml.contains("documentation: |-\n"), "{}", yaml);
+        assert!(yaml.contains("    Manages users.\n"), "{}", yaml);
+
+        let parsed: LlmResponse = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&response).unwrap());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::parser::MavenModule;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Dependencies between the modules of a multi-module Maven build
+/// Nodes are `groupId:artifactId` keys; an edge `(a, b)` means module `a` depends on module `b`.
+/// Dependencies on artifacts outside the build are not part of the graph.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    pub nodes: BTreeSet<String>,
+    pub edges: BTreeSet<(String, String)>,
+}
+
+impl DependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Graph of the dependencies between the given modules
+    pub fn from_modules(modules: &[MavenModule]) -> Self {
+        let mut graph = Self::new();
+        let keys: Vec<Option<String>> = modules
+            .iter()
+            .map(|m| Some(format!("{}:{}", m.group_id.as_deref()?, m.artifact_id.as_deref()?)))
+            .collect();
+        for key in keys.iter().flatten() {
+            graph.add_node(key);
+        }
+
+        for (module, key) in modules.iter().zip(&keys) {
+            let Some(key) = key else { continue };
+            for dependency in &module.dependencies {
+                let target = format!("{}:{}", dependency.group_id, dependency.artifact_id);
+                if graph.nodes.contains(&target) {
+                    graph.add_edge(key, &target);
+                }
+            }
+        }
+        graph
+    }
+
+    pub fn add_node(&mut self, module: &str) {
+        self.nodes.insert(module.to_string());
+    }
+
+    /// Record that `from` depends on `to`, adding both nodes
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        self.add_node(from);
+        self.add_node(to);
+        self.edges.insert((from.to_string(), to.to_string()));
+    }
+
+    /// Modules `module` depends on directly
+    pub fn get_dependencies(&self, module: &str) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|(from, _)| from == module)
+            .map(|(_, to)| to.as_str())
+            .collect()
+    }
+
+    /// Modules depending directly on `module`
+    pub fn get_dependents(&self, module: &str) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|(_, to)| to == module)
+            .map(|(from, _)| from.as_str())
+            .collect()
+    }
+
+    /// Each cycle found by a depth-first search, as the modules along it starting from its
+    /// smallest key (`[a, b]` for a -> b -> a). Cycles are sorted, and one is reported per
+    /// back edge, so overlapping cycles may not all be listed.
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        let adjacency = self.adjacency();
+        let mut finished = BTreeSet::new();
+        let mut cycles = BTreeSet::new();
+
+        for start in &self.nodes {
+            if finished.contains(start.as_str()) {
+                continue;
+            }
+            // Iterative DFS: the current path, with the next successor index of each node
+            let mut path: Vec<(&str, usize)> = vec![(start.as_str(), 0)];
+            while let Some((node, next)) = path.last_mut() {
+                let successors = adjacency.get(*node).map_or(&[][..], Vec::as_slice);
+                let Some(successor) = successors.get(*next).copied() else {
+                    finished.insert(*node);
+                    path.pop();
+                    continue;
+                };
+                *next += 1;
+
+                if let Some(position) = path.iter().position(|(n, _)| *n == successor) {
+                    let mut cycle: Vec<String> = path[position..].iter().map(|(n, _)| n.to_string()).collect();
+                    let smallest = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap_or(0);
+                    cycle.rotate_left(smallest);
+                    cycles.insert(cycle);
+                } else if !finished.contains(successor) {
+                    path.push((successor, 0));
+                }
+            }
+        }
+
+        cycles.into_iter().collect()
+    }
+
+    /// Modules ordered so that every module comes after its dependencies (ties broken by key)
+    /// Fails, naming a cycle, when the graph is not a DAG
+    pub fn topological_order(&self) -> Result<Vec<String>> {
+        let mut remaining_dependencies: BTreeMap<&str, usize> =
+            self.nodes.iter().map(|n| (n.as_str(), 0)).collect();
+        for (from, _) in &self.edges {
+            *remaining_dependencies.entry(from.as_str()).or_default() += 1;
+        }
+
+        let mut ready: BTreeSet<&str> = remaining_dependencies
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(node, _)| *node)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(node) = ready.pop_first() {
+            order.push(node.to_string());
+            for dependent in self.get_dependents(node) {
+                let count = remaining_dependencies.entry(dependent).or_default();
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(dependent);
+                }
+            }
+        }
+
+        if order.len() < self.nodes.len() {
+            let cycle = self.find_cycles().into_iter().next().unwrap_or_default();
+            let mut description = cycle.join(" -> ");
+            if let Some(first) = cycle.first() {
+                description = format!("{} -> {}", description, first);
+            }
+            anyhow::bail!("Module dependencies contain a cycle: {}", description);
+        }
+        Ok(order)
+    }
+
+    fn adjacency(&self) -> BTreeMap<&str, Vec<&str>> {
+        let mut adjacency: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (from, to) in &self.edges {
+            adjacency.entry(from.as_str()).or_default().push(to.as_str());
+        }
+        adjacency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dag_has_topological_order() {
+        let mut graph = DependencyGraph::new();
+        graph.add_edge("com.example:web", "com.example:service");
+        graph.add_edge("com.example:service", "com.example:core");
+        graph.add_edge("com.example:web", "com.example:core");
+        graph.add_node("com.example:tools");
+
+        assert!(graph.find_cycles().is_empty());
+        assert_eq!(
+            graph.topological_order().unwrap(),
+            vec!["com.example:core", "com.example:service", "com.example:tools", "com.example:web"]
+        );
+        assert_eq!(graph.get_dependents("com.example:core"), vec!["com.example:service", "com.example:web"]);
+    }
+
+    #[test]
+    fn test_two_module_cycle() {
+        let mut graph = DependencyGraph::new();
+        graph.add_edge("com.example:orders", "com.example:billing");
+        graph.add_edge("com.example:billing", "com.example:orders");
+        graph.add_edge("com.example:orders", "com.example:core");
+
+        assert_eq!(graph.find_cycles(), vec![vec!["com.example:billing", "com.example:orders"]]);
+
+        let error = graph.topological_order().unwrap_err().to_string();
+        assert!(
+            error.contains("com.example:billing -> com.example:orders -> com.example:billing"),
+            "{}",
+            error
+        );
+    }
+}
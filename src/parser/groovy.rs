@@ -0,0 +1,446 @@
+use crate::parser::{read_source_lossy, FileMeta, FileParseable, FileSuffix};
+use crate::types::{Declaration, DeclarationKind, Field, Method, Parameter, SourceRange};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::Path;
+
+/// Type recorded for `def` and untyped declarations
+pub const UNKNOWN_TYPE: &str = "unknown";
+
+/// Words that can precede a name and a parenthesis in statements without being a type
+const NON_TYPE_KEYWORDS: &[&str] = &[
+    "return", "new", "throw", "else", "if", "for", "while", "switch", "catch", "assert", "in",
+];
+
+/// The declarations of a Groovy source file
+#[derive(Debug, Clone)]
+pub struct GroovyFile {
+    pub file_meta: FileMeta,
+    pub declarations: Vec<Declaration>,
+}
+
+/// Pragmatic regex-based parser for Groovy sources
+/// Extracts top-level classes, interfaces, traits and enums with their fields and methods
+/// into the shared Declaration model. Comments and string literals are blanked out first,
+/// so braces inside them do not confuse the block matching. Annotations are not extracted.
+pub struct GroovyParser {
+    package_pattern: Regex,
+    class_pattern: Regex,
+    method_pattern: Regex,
+    field_pattern: Regex,
+}
+
+impl GroovyParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            package_pattern: Regex::new(r"(?m)^[ \t]*package\s+(?P<package>[\w.]+)")?,
+            class_pattern: Regex::new(
+                r"(?m)^[ \t]*(?P<mods>(?:(?:public|protected|private|abstract|final|static|sealed|non-sealed)\s+)*)(?P<keyword>class|interface|trait|enum)\s+(?P<name>[A-Za-z_]\w*)(?:\s+extends\s+(?P<extends>[\w.<>, ]+?))?(?:\s+implements\s+(?P<implements>[\w.<>, ]+?))?(?:\s+permits\s+[\w.<>, ]+?)?\s*\{",
+            )?,
+            method_pattern: Regex::new(
+                r"^\s*(?P<mods>(?:(?:public|protected|private|static|final|abstract|synchronized|def)\s+)*)(?:(?P<type>[A-Za-z_][\w.]*(?:<[^()]*>)?(?:\[\])*)\s+)?(?P<name>[A-Za-z_]\w*)\s*\((?P<params>[^)]*)\)\s*(?:throws\s+(?P<throws>[\w.,\s]+?))?\s*(?P<end>\{|;|$)",
+            )?,
+            field_pattern: Regex::new(
+                r"^\s*(?P<mods>(?:(?:public|protected|private|static|final|def)\s+)*)(?:(?P<type>[A-Za-z_][\w.]*(?:<[^()=]*>)?(?:\[\])*)\s+)?(?P<name>[A-Za-z_]\w*)\s*(?:=.*)?;?\s*$",
+            )?,
+        })
+    }
+
+    /// Parse a Groovy file along with the metadata the indexer stores for it
+    pub fn parse_groovy_file(&self, path: &Path) -> Result<GroovyFile> {
+        let source = read_source_lossy(path)
+            .with_context(|| format!("Failed to read Groovy file: {}", path.display()))?;
+        Ok(GroovyFile {
+            file_meta: FileMeta::new(path, FileSuffix::Groovy, &source),
+            declarations: self.parse_source(&source),
+        })
+    }
+
+    pub fn parse_source(&self, source: &str) -> Vec<Declaration> {
+        let masked = mask_comments_and_strings(source);
+        let package = self
+            .package_pattern
+            .captures(&masked)
+            .map_or(String::new(), |captures| captures["package"].to_string());
+        let mut declarations = Vec::new();
+        let mut depth = 0;
+        let mut scanned = 0;
+
+        for captures in self.class_pattern.captures_iter(&masked) {
+            let header = captures.get(0).unwrap();
+            depth += brace_delta(&masked[scanned..header.start()]);
+            scanned = header.start();
+            if depth != 0 {
+                continue;
+            }
+
+            let open = header.end() - 1;
+            let close = matching_brace(&masked, open).unwrap_or(masked.len());
+            let name = captures["name"].to_string();
+            let modifiers = split_modifiers(captures.name("mods").map_or("", |m| m.as_str()));
+
+            let kind = match &captures["keyword"] {
+                "interface" | "trait" => DeclarationKind::Interface,
+                "enum" => DeclarationKind::Enum,
+                _ => DeclarationKind::Class,
+            };
+
+            let (fields, methods) = self.parse_members(&masked, open + 1, close, &name);
+
+            declarations.push(Declaration {
+                signature: collapse_whitespace(source[header.start()..open].trim()),
+                extends: captures.name("extends").map(|m| m.as_str().trim().to_string()),
+                implements: captures
+                    .name("implements")
+                    .map(|m| split_type_list(m.as_str()))
+                    .unwrap_or_default(),
+                annotations: Vec::new(),
+                range: source_range(&masked, header.start(), (close + 1).min(masked.len())),
+                documentation: None,
+                package: package.clone(),
+                name,
+                kind,
+                modifiers,
+                fields,
+                methods,
+            });
+        }
+
+        declarations
+    }
+
+    /// Fields and methods declared directly in the body between `start` and `end`
+    fn parse_members(&self, masked: &str, start: usize, end: usize, class_name: &str) -> (Vec<Field>, Vec<Method>) {
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
+        let mut depth = 0;
+        let mut line_start = start;
+
+        for line in masked[start..end].split_inclusive('\n') {
+            let offset = line_start;
+            line_start += line.len();
+
+            let line_depth = depth;
+            depth += brace_delta(line);
+            if line_depth != 0 {
+                continue;
+            }
+
+            if let Some(captures) = self.method_pattern.captures(line.trim_end_matches('\n')) {
+                let mods = captures.name("mods").map_or("", |m| m.as_str());
+                let return_type = captures.name("type").map(|m| m.as_str());
+                let name = &captures["name"];
+
+                // Without a modifier or a type this is a call, and a typeless name equal to the class is a constructor
+                let is_declaration = !(mods.is_empty() && return_type.is_none())
+                    && return_type.is_none_or(|t| !NON_TYPE_KEYWORDS.contains(&t))
+                    && !(return_type.is_none() && name == class_name);
+                if is_declaration {
+                    let header_start = offset + captures.get(0).unwrap().start();
+                    let body_range = (&captures["end"] == "{").then(|| {
+                        let open = offset + captures.name("end").unwrap().start();
+                        let close = matching_brace(masked, open).unwrap_or(end);
+                        (open, close + 1)
+                    });
+                    let header_end = offset + captures.get(0).unwrap().end();
+
+                    methods.push(Method {
+                        name: name.to_string(),
+                        return_type: declared_type(return_type),
+                        parameters: parse_parameters(&captures["params"]),
+                        modifiers: split_modifiers(mods),
+                        annotations: Vec::new(),
+                        throws: captures.name("throws").map_or_else(Vec::new, |t| {
+                            t.as_str().split(',').map(|e| e.trim().to_string()).filter(|e| !e.is_empty()).collect()
+                        }),
+                        range: source_range(masked, header_start, body_range.map_or(header_end, |(_, close)| close)),
+                        body_range: body_range.map(|(open, close)| source_range(masked, open, close)),
+                        complexity: None,
+                    });
+                    continue;
+                }
+            }
+
+            if let Some(captures) = self.field_pattern.captures(line.trim_end_matches('\n')) {
+                let mods = captures.name("mods").map_or("", |m| m.as_str());
+                let type_name = captures.name("type").map(|m| m.as_str());
+                if (mods.is_empty() && type_name.is_none()) || type_name.is_some_and(|t| NON_TYPE_KEYWORDS.contains(&t)) {
+                    continue;
+                }
+
+                fields.push(Field {
+                    name: captures["name"].to_string(),
+                    type_name: declared_type(type_name),
+                    modifiers: split_modifiers(mods),
+                    annotations: Vec::new(),
+                });
+            }
+        }
+
+        (fields, methods)
+    }
+}
+
+impl FileParseable<Vec<Declaration>> for GroovyParser {
+    fn parse_file(&mut self, path: &Path) -> Result<Vec<Declaration>> {
+        Ok(self.parse_groovy_file(path)?.declarations)
+    }
+}
+
+/// `def` and missing types become UNKNOWN_TYPE
+fn declared_type(type_name: Option<&str>) -> String {
+    match type_name {
+        None | Some("def") => UNKNOWN_TYPE.to_string(),
+        Some(type_name) => type_name.to_string(),
+    }
+}
+
+/// `def` is a placeholder for the type, not a modifier
+fn split_modifiers(mods: &str) -> Vec<String> {
+    mods.split_whitespace()
+        .filter(|m| *m != "def")
+        .map(|m| m.to_string())
+        .collect()
+}
+
+/// "String name, def count = 0, flag" -> typed parameters, untyped ones as UNKNOWN_TYPE
+fn parse_parameters(params: &str) -> Vec<Parameter> {
+    split_type_list(params)
+        .into_iter()
+        .filter_map(|param| {
+            let declaration = param.split('=').next().unwrap_or(&param);
+            let tokens: Vec<&str> = declaration
+                .split_whitespace()
+                .filter(|t| *t != "final" && !t.starts_with('@'))
+                .collect();
+            let (name, type_tokens) = tokens.split_last()?;
+            let type_name = (!type_tokens.is_empty()).then(|| type_tokens.join(" "));
+            Some(Parameter {
+                name: name.to_string(),
+                type_name: declared_type(type_name.as_deref()),
+                annotations: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Split on commas outside of generic arguments ("Map<K, V>, Runnable" -> two items)
+fn split_type_list(list: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in list.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    items.push(current.trim().to_string());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn brace_delta(text: &str) -> i32 {
+    text.chars().fold(0, |delta, c| match c {
+        '{' => delta + 1,
+        '}' => delta - 1,
+        _ => delta,
+    })
+}
+
+/// Byte offset of the `}` closing the `{` at `open`
+fn matching_brace(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 1-based range of the bytes `start..end`
+fn source_range(text: &str, start: usize, end: usize) -> SourceRange {
+    let position = |offset: usize| {
+        let before = &text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = offset - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        (line, column)
+    };
+    let (start_line, start_column) = position(start);
+    let (end_line, end_column) = position(end);
+    SourceRange {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+    }
+}
+
+/// Replace comments and string literals (GStrings and triple-quoted ones included) with
+/// spaces, keeping newlines and byte offsets so positions still match the source
+fn mask_comments_and_strings(source: &str) -> String {
+    let mut masked = String::with_capacity(source.len());
+    let mut rest = source;
+
+    let blank = |text: &str, out: &mut String| {
+        for c in text.chars() {
+            if c == '\n' {
+                out.push('\n');
+            } else {
+                out.extend(std::iter::repeat_n(' ', c.len_utf8()));
+            }
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        let literal_end = if rest.starts_with("//") {
+            Some(rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            Some(comment.find("*/").map_or(rest.len(), |i| i + 4))
+        } else if let Some(quote) = ["\"\"\"", "'''"].into_iter().find(|q| rest.starts_with(q)) {
+            Some(rest[3..].find(quote).map_or(rest.len(), |i| i + 6))
+        } else if c == '"' || c == '\'' {
+            let mut escaped = false;
+            let end = rest[1..].char_indices().find_map(|(i, ch)| {
+                let closes = ch == c && !escaped;
+                escaped = ch == '\\' && !escaped;
+                (closes || ch == '\n').then_some(i + 1 + ch.len_utf8())
+            });
+            Some(end.unwrap_or(rest.len()))
+        } else {
+            None
+        };
+
+        match literal_end {
+            Some(end) => {
+                blank(&rest[..end], &mut masked);
+                rest = &rest[end..];
+            }
+            None => {
+                masked.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_groovy_class() {
+        let groovy_content = r#"
+package com.example.build
+
+/**
+ * Greets people { not a block }
+ */
+class Greeter extends BaseTask implements Serializable, Comparable<Greeter> {
+    String greeting = "Hello {"
+    def count = 0
+    private static final int MAX = 3
+
+    def greet(name, String suffix = '!') {
+        count++
+        if (count > MAX) {
+            return null
+        }
+        println "${greeting} ${name}${suffix}"
+    }
+
+    String describe() {
+        return 'Greeter'
+    }
+
+    private static List<String> names(Map<String, Object> config) { [] }
+}
+
+trait Named {
+    abstract String getName()
+}
+"#;
+
+        let parser = GroovyParser::new().unwrap();
+        let declarations = parser.parse_source(groovy_content);
+        assert_eq!(declarations.len(), 2);
+
+        let greeter = &declarations[0];
+        assert_eq!(greeter.name, "Greeter");
+        assert_eq!(greeter.package, "com.example.build");
+        assert_eq!(greeter.kind, DeclarationKind::Class);
+        assert_eq!(greeter.extends.as_deref(), Some("BaseTask"));
+        assert_eq!(greeter.implements, vec!["Serializable", "Comparable<Greeter>"]);
+        assert_eq!(greeter.range.start_line, 7);
+        assert_eq!(greeter.range.end_line, 25);
+
+        let methods: Vec<(&str, &str)> = greeter
+            .methods
+            .iter()
+            .map(|m| (m.name.as_str(), m.return_type.as_str()))
+            .collect();
+        assert_eq!(
+            methods,
+            vec![("greet", UNKNOWN_TYPE), ("describe", "String"), ("names", "List<String>")]
+        );
+
+        let greet = &greeter.methods[0];
+        assert_eq!(greet.parameters[0].name, "name");
+        assert_eq!(greet.parameters[0].type_name, UNKNOWN_TYPE);
+        assert_eq!(greet.parameters[1].type_name, "String");
+        assert_eq!(greet.body_range.as_ref().unwrap().end_line, 18);
+        assert_eq!(greeter.methods[2].modifiers, vec!["private", "static"]);
+        assert_eq!(greeter.methods[2].parameters[0].type_name, "Map<String, Object>");
+
+        let fields: Vec<(&str, &str)> = greeter
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.type_name.as_str()))
+            .collect();
+        assert_eq!(fields, vec![("greeting", "String"), ("count", UNKNOWN_TYPE), ("MAX", "int")]);
+
+        let named = &declarations[1];
+        assert_eq!(named.kind, DeclarationKind::Interface);
+        assert_eq!(named.methods[0].name, "getName");
+        assert!(named.methods[0].body_range.is_none());
+    }
+
+    #[test]
+    fn test_sealed_groovy_types() {
+        let groovy_content = r#"
+sealed interface Shape permits Circle, Square {}
+
+non-sealed class Square implements Shape {}
+"#;
+
+        let declarations = GroovyParser::new().unwrap().parse_source(groovy_content);
+        let modifiers: Vec<(&str, Vec<String>)> =
+            declarations.iter().map(|d| (d.name.as_str(), d.modifiers.clone())).collect();
+        assert_eq!(
+            modifiers,
+            vec![("Shape", vec!["sealed".to_string()]), ("Square", vec!["non-sealed".to_string()])]
+        );
+        assert_eq!(declarations[1].implements, vec!["Shape"]);
+    }
+}
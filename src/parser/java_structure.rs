@@ -1,8 +1,11 @@
-use crate::parser::{FileMeta, FileParseable, FileSuffix};
+use crate::parser::{clean_javadoc, read_source_lossy, FileMeta, FileParseable, FileSuffix};
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
 use std::path::Path;
-use tree_sitter::{Node, Parser, Tree};
+use tree_sitter::{InputEdit, Node, Parser, Tree};
 
 /// Complete structure preview of a Java source file
 /// Provides IntelliJ-like structure view data for code navigation
@@ -10,13 +13,28 @@ use tree_sitter::{Node, Parser, Tree};
 pub struct JavaStructurePreview {
     pub file_meta: FileMeta,
     pub package: Option<String>,
+    /// Type and package imports ("java.util.List", "java.util.*")
     pub imports: Vec<String>,
+    /// Static member imports ("org.junit.Assert.assertEquals", "java.lang.Math.*")
+    #[serde(default)]
+    pub static_imports: Vec<String>,
     pub top_level_classes: Vec<ClassStructure>,
     pub file_annotations: Vec<Annotation>,
 }
 
+/// An import declaration like "import java.util.List;"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Import {
+    /// Imported name without the trailing ".*" (e.g., "java.util.List")
+    pub path: String,
+    /// "import static ..."
+    pub is_static: bool,
+    /// "import java.util.*;"
+    pub is_wildcard: bool,
+}
+
 /// Structure representation of a Java class, interface, enum, or record
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ClassStructure {
     pub name: String,
     pub fqn: String,
@@ -25,16 +43,41 @@ pub struct ClassStructure {
     pub annotations: Vec<Annotation>,
     pub extends: Option<String>,
     pub implements: Vec<String>,
-    pub type_parameters: Vec<String>,
+    pub type_parameters: Vec<TypeParameter>,
     pub fields: Vec<FieldStructure>,
     pub methods: Vec<MethodStructure>,
     pub nested_classes: Vec<ClassStructure>,
     pub range: SourceRange,
+    /// Physical lines spanned by the declaration, nested types included
+    #[serde(default)]
+    pub loc: usize,
+    /// Lines holding code other than comments; lines of nested types are left out, since
+    /// those are counted by the nested ClassStructure itself
+    #[serde(default)]
+    pub sloc: usize,
+    /// Javadoc text, cleaned by clean_javadoc
     pub documentation: Option<String>,
+    /// The Javadoc comment as written, markers included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_documentation: Option<String>,
+}
+
+impl ClassStructure {
+    /// Declaration line like "public class com.example.UserService", with the fully
+    /// qualified name or, without `use_fqn`, the simple name ("public class UserService")
+    pub fn signature(&self, use_fqn: bool) -> String {
+        let name = if use_fqn { &self.fqn } else { &self.name };
+        self.modifiers
+            .iter()
+            .map(String::as_str)
+            .chain([self.kind.keyword(), name.as_str()])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
 }
 
 /// Different types of Java type declarations
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 pub enum ClassKind {
     Class,
     Interface,
@@ -43,32 +86,80 @@ pub enum ClassKind {
     Annotation,
 }
 
+impl ClassKind {
+    /// Keyword introducing the declaration in source
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            ClassKind::Class => "class",
+            ClassKind::Interface => "interface",
+            ClassKind::Enum => "enum",
+            ClassKind::Record => "record",
+            ClassKind::Annotation => "@interface",
+        }
+    }
+}
+
+/// A generic type parameter like "T extends Comparable<T> & Serializable"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TypeParameter {
+    pub name: String,
+    /// Upper bounds, one per `&`-joined type (empty when unbounded)
+    pub bounds: Vec<String>,
+}
+
 /// Structure representation of a field
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FieldStructure {
     pub name: String,
     pub type_name: String,
     pub modifiers: Vec<String>,
     pub annotations: Vec<Annotation>,
+    /// Source text of the initializer, like `"user_"` for `String PREFIX = "user_"`
+    #[serde(default)]
+    pub initializer: Option<String>,
+    /// Javadoc text, cleaned by clean_javadoc
     pub documentation: Option<String>,
+    /// The Javadoc comment as written, markers included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_documentation: Option<String>,
 }
 
 /// Structure representation of a method
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MethodStructure {
     pub name: String,
     pub return_type: String,
     pub parameters: Vec<ParameterStructure>,
     pub modifiers: Vec<String>,
     pub annotations: Vec<Annotation>,
-    pub type_parameters: Vec<String>,
+    pub type_parameters: Vec<TypeParameter>,
     pub throws: Vec<String>,
     pub range: SourceRange,
+    /// Physical lines spanned by the declaration
+    #[serde(default)]
+    pub loc: usize,
+    /// Lines holding code other than comments
+    #[serde(default)]
+    pub sloc: usize,
+    /// The `{ ... }` block; None for abstract and interface methods
+    #[serde(default)]
+    pub body_range: Option<SourceRange>,
+    /// McCabe cyclomatic complexity of the body; None for abstract and interface methods
+    #[serde(default)]
+    pub complexity: Option<usize>,
+    /// Distinct names of the methods invoked in the body ("save" for `repository.save(user)`);
+    /// receivers are not resolved
+    #[serde(default)]
+    pub calls: Vec<String>,
+    /// Javadoc text, cleaned by clean_javadoc
     pub documentation: Option<String>,
+    /// The Javadoc comment as written, markers included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_documentation: Option<String>,
 }
 
 /// Structure representation of a method parameter
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ParameterStructure {
     pub name: String,
     pub type_name: String,
@@ -76,7 +167,7 @@ pub struct ParameterStructure {
 }
 
 /// Annotation representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Annotation {
     pub name: String,
     pub values: Vec<(String, String)>,
@@ -84,7 +175,7 @@ pub struct Annotation {
 }
 
 /// Source location range
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SourceRange {
     pub start_line: usize,
     pub start_column: usize,
@@ -92,28 +183,185 @@ pub struct SourceRange {
     pub end_column: usize,
 }
 
+/// A tree-sitter parse tree together with the source it was parsed from
+/// Node byte ranges index into `source()`, so node text is always available
+pub struct SourceTree {
+    tree: Tree,
+    source: String,
+}
+
+impl SourceTree {
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    pub fn root_node(&self) -> Node<'_> {
+        self.tree.root_node()
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Source text covered by a node of this tree
+    pub fn node_text(&self, node: &Node) -> &str {
+        &self.source[node.byte_range()]
+    }
+}
+
+/// Files larger than this are skipped by default (5MB)
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
 /// Parser for extracting Java structure using tree-sitter
-pub struct JavaStructureParser;
+pub struct JavaStructureParser {
+    max_file_size: u64,
+}
 
 impl JavaStructureParser {
     pub fn new() -> Result<Self> {
-        Ok(JavaStructureParser)
+        Ok(JavaStructureParser {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+        })
+    }
+
+    /// Skip files above `max_file_size` bytes instead of parsing them
+    /// Huge generated sources can otherwise use a lot of memory
+    pub fn with_max_file_size(max_file_size: u64) -> Result<Self> {
+        Ok(JavaStructureParser { max_file_size })
     }
 
     pub fn parse_structure(&self, path: &Path) -> Result<JavaStructurePreview> {
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read Java file: {:?}", path))?;
+        let content = self.read_source(path)?;
+        let tree = self.parse_syntax(&content, None)?;
+
+        self.extract_structure(path, &content, &tree)
+    }
+
+    /// Read only the package and import declarations of a file
+    /// The file is read and parsed up to its first type declaration only, so the body of
+    /// the types is neither loaded nor parsed.
+    pub fn parse_header(&self, path: &Path) -> Result<(Option<String>, Vec<Import>)> {
+        let content = self.read_header(path)?;
+        let tree = self.parse_syntax(&content, None)?;
+        let root_node = tree.root_node();
+
+        let mut package = None;
+        let mut imports = Vec::new();
+        let mut cursor = root_node.walk();
+        for child in root_node.children(&mut cursor) {
+            match child.kind() {
+                "package_declaration" => package = self.parse_package(&child, &content),
+                "import_declaration" => {
+                    if let Some(import) = self.parse_import(&child, &content) {
+                        imports.push(import);
+                    }
+                }
+                "class_declaration"
+                | "interface_declaration"
+                | "enum_declaration"
+                | "record_declaration"
+                | "annotation_type_declaration" => break,
+                _ => continue,
+            }
+        }
+
+        Ok((package, imports))
+    }
+
+    fn read_source(&self, path: &Path) -> Result<String> {
+        self.check_file_size(path)?;
+        read_source_lossy(path).with_context(|| format!("Failed to read Java file: {:?}", path))
+    }
+
+    /// The lines of a file before its first type declaration: package, imports,
+    /// comments and the annotations that precede the type
+    fn read_header(&self, path: &Path) -> Result<String> {
+        self.check_file_size(path)?;
+        let file = std::fs::File::open(path).with_context(|| format!("Failed to read Java file: {:?}", path))?;
+        let mut reader = BufReader::new(file);
+
+        let mut header = String::new();
+        let mut line = Vec::new();
+        let mut in_block_comment = false;
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            let text = String::from_utf8_lossy(&line);
+            let trimmed = text.trim();
+            if in_block_comment {
+                in_block_comment = !trimmed.contains("*/");
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                in_block_comment = !comment.contains("*/");
+            } else if !(trimmed.is_empty()
+                || trimmed.starts_with("//")
+                || trimmed.starts_with('@')
+                || trimmed.starts_with("package ")
+                || trimmed.starts_with("import "))
+            {
+                break;
+            }
+            header.push_str(&text);
+            line.clear();
+        }
+        Ok(header)
+    }
+
+    fn check_file_size(&self, path: &Path) -> Result<()> {
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("Failed to read Java file: {:?}", path))?
+            .len();
+        if size > self.max_file_size {
+            anyhow::bail!(
+                "Skipping {:?}: file size {} bytes exceeds the {} byte limit",
+                path,
+                size,
+                self.max_file_size
+            );
+        }
+        Ok(())
+    }
+
+    /// Parse Java source into its tree-sitter tree, for callers running their own queries
+    pub fn parse_tree(&self, source: &str) -> Result<SourceTree> {
+        Ok(SourceTree {
+            tree: self.parse_syntax(source, None)?,
+            source: source.to_string(),
+        })
+    }
+
+    /// Structure of a file from a tree made by parse_tree or reparse
+    pub fn structure_from_tree(&self, path: &Path, source_tree: &SourceTree) -> Result<JavaStructurePreview> {
+        self.extract_structure(path, &source_tree.source, &source_tree.tree)
+    }
+
+    /// Parse `new_source` incrementally, reusing the unchanged parts of `old_tree`
+    /// `edit` describes how the source of `old_tree` became `new_source`; keep the returned
+    /// tree to pass it back for the next edit
+    pub fn reparse(
+        &self,
+        path: &Path,
+        old_tree: &SourceTree,
+        edit: InputEdit,
+        new_source: &str,
+    ) -> Result<(JavaStructurePreview, SourceTree)> {
+        let mut edited = old_tree.tree.clone();
+        edited.edit(&edit);
+
+        let source_tree = SourceTree {
+            tree: self.parse_syntax(new_source, Some(&edited))?,
+            source: new_source.to_string(),
+        };
+        let structure = self.structure_from_tree(path, &source_tree)?;
+        Ok((structure, source_tree))
+    }
 
+    fn parse_syntax(&self, content: &str, old_tree: Option<&Tree>) -> Result<Tree> {
         let mut parser = Parser::new();
         parser
             .set_language(tree_sitter_java::language())
             .context("Failed to load Java grammar")?;
 
-        let tree = parser
-            .parse(&content, None)
-            .context("Failed to parse Java file")?;
-
-        self.extract_structure(path, &content, &tree)
+        parser
+            .parse(content, old_tree)
+            .context("Failed to parse Java file")
     }
 
     fn extract_structure(
@@ -125,7 +373,7 @@ impl JavaStructureParser {
         let root_node = tree.root_node();
 
         let package = self.extract_package(&root_node, content);
-        let imports = self.extract_imports(&root_node, content);
+        let (imports, static_imports) = self.extract_imports(&root_node, content);
         let top_level_classes = self.extract_classes(&root_node, content, &package)?;
         let file_annotations = self.extract_file_annotations(&root_node, content);
 
@@ -133,6 +381,7 @@ impl JavaStructureParser {
             file_meta: FileMeta::new(path, FileSuffix::Java, content),
             package,
             imports,
+            static_imports,
             top_level_classes,
             file_annotations,
         })
@@ -142,30 +391,71 @@ impl JavaStructureParser {
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             if child.kind() == "package_declaration" {
-                // Look for scoped_identifier directly under package_declaration
-                let mut package_cursor = child.walk();
-                for package_child in child.children(&mut package_cursor) {
-                    if package_child.kind() == "scoped_identifier" {
-                        let package_name = self.node_text(&package_child, content).to_string();
-                        return Some(package_name.trim().to_string());
-                    }
-                }
+                return self.parse_package(&child, content);
             }
         }
         None
     }
 
-    fn extract_imports(&self, node: &Node, content: &str) -> Vec<String> {
+    fn parse_package(&self, node: &Node, content: &str) -> Option<String> {
+        // Look for scoped_identifier directly under package_declaration
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.kind() == "scoped_identifier" || child.kind() == "identifier" {
+                let package_name = self.node_text(&child, content).to_string();
+                return Some(package_name.trim().to_string());
+            }
+        }
+        None
+    }
+
+    /// Imports split into (type imports, static imports), wildcards keeping their ".*"
+    fn extract_imports(&self, node: &Node, content: &str) -> (Vec<String>, Vec<String>) {
         let mut imports = Vec::new();
+        let mut static_imports = Vec::new();
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             if child.kind() == "import_declaration" {
-                if let Some(name_node) = child.child_by_field_name("name") {
-                    imports.push(self.node_text(&name_node, content).to_string());
+                if let Some(import) = self.parse_import(&child, content) {
+                    let name = if import.is_wildcard {
+                        format!("{}.*", import.path)
+                    } else {
+                        import.path
+                    };
+                    if import.is_static {
+                        static_imports.push(name);
+                    } else {
+                        imports.push(name);
+                    }
                 }
             }
         }
-        imports
+        (imports, static_imports)
+    }
+
+    fn parse_import(&self, node: &Node, content: &str) -> Option<Import> {
+        let mut path = None;
+        let mut is_static = false;
+        let mut is_wildcard = false;
+
+        // The grammar has no field names here: "import" ["static"] name ["." "*"] ";"
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "static" => is_static = true,
+                "asterisk" => is_wildcard = true,
+                "scoped_identifier" | "identifier" => {
+                    path = Some(self.node_text(&child, content).to_string());
+                }
+                _ => {}
+            }
+        }
+
+        path.map(|path| Import {
+            path,
+            is_static,
+            is_wildcard,
+        })
     }
 
     fn extract_file_annotations(&self, node: &Node, content: &str) -> Vec<Annotation> {
@@ -235,9 +525,13 @@ impl JavaStructureParser {
         let type_parameters = self.extract_type_parameters(&node, content);
         let fields = self.extract_fields(&node, content)?;
         let methods = self.extract_methods(&node, content)?;
-        let nested_classes = self.extract_nested_classes(&node, content, package)?;
+        // Nested types are qualified by their enclosing type (pkg.Outer.Inner)
+        let nested_classes = self.extract_nested_classes(&node, content, &Some(fqn.clone()))?;
         let range = self.node_range(node);
-        let documentation = self.extract_documentation(&node, content);
+        let loc = range.end_line - range.start_line + 1;
+        let sloc = self.source_lines_of_code(node, true);
+        let raw_documentation = self.extract_documentation(&node, content);
+        let documentation = raw_documentation.as_deref().map(clean_javadoc);
 
         Ok(Some(ClassStructure {
             name,
@@ -252,7 +546,10 @@ impl JavaStructureParser {
             methods,
             nested_classes,
             range,
+            loc,
+            sloc,
             documentation,
+            raw_documentation,
         }))
     }
 
@@ -297,7 +594,9 @@ impl JavaStructureParser {
                     // Only include actual modifier keywords, exclude annotations which have their own node type
                     match kind {
                         "public" | "private" | "protected" | "static" | "final" | "abstract"
-                        | "synchronized" | "volatile" | "transient" | "native" | "strictfp" => {
+                        | "synchronized" | "volatile" | "transient" | "native" | "strictfp" | "sealed"
+                        // `non-sealed` is a single token of the grammar, hyphen included
+                        | "non-sealed" => {
                             let text = self.node_text(&modifier, content);
                             if !text.is_empty() {
                                 modifiers.push(text.to_string());
@@ -444,10 +743,12 @@ impl JavaStructureParser {
                     }
                     "element_value_array_initializer" => {
                         // Handle array values like @RequestMapping(method = {GET, POST})
+                        // and containers of repeated annotations like @Schedules({@Schedule(..), @Schedule(..)})
                         let mut array_cursor = child.walk();
                         for array_child in child.children(&mut array_cursor) {
                             match array_child.kind() {
-                                "string_literal" | "identifier" => {
+                                "string_literal" | "identifier" | "field_access" | "annotation"
+                                | "marker_annotation" => {
                                     let value = self.node_text(&array_child, content).to_string();
                                     values.push(("value".to_string(), value));
                                 }
@@ -511,17 +812,14 @@ impl JavaStructureParser {
         implements
     }
 
-    fn extract_type_parameters(&self, node: &Node, content: &str) -> Vec<String> {
+    fn extract_type_parameters(&self, node: &Node, content: &str) -> Vec<TypeParameter> {
         let mut type_params = Vec::new();
 
         if let Some(type_params_node) = node.child_by_field_name("type_parameters") {
             let mut cursor = type_params_node.walk();
             for child in type_params_node.children(&mut cursor) {
                 if child.kind() == "type_parameter" {
-                    let text = self.node_text(&child, content);
-                    if !text.is_empty() {
-                        type_params.push(text.to_string());
-                    }
+                    type_params.extend(self.parse_type_parameter(&child, content));
                 }
             }
         }
@@ -529,6 +827,30 @@ impl JavaStructureParser {
         type_params
     }
 
+    /// Split "T extends A & B" into the name and one bound per type of the type_bound node
+    fn parse_type_parameter(&self, node: &Node, content: &str) -> Option<TypeParameter> {
+        let mut name = None;
+        let mut bounds = Vec::new();
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "identifier" | "type_identifier" if name.is_none() => {
+                    name = Some(self.node_text(&child, content).to_string());
+                }
+                "type_bound" => {
+                    let mut bound_cursor = child.walk();
+                    for bound in child.named_children(&mut bound_cursor) {
+                        bounds.push(self.node_text(&bound, content).to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        name.map(|name| TypeParameter { name, bounds })
+    }
+
     fn extract_fields(&self, node: &Node, content: &str) -> Result<Vec<FieldStructure>> {
         let mut fields = Vec::new();
 
@@ -567,20 +889,26 @@ impl JavaStructureParser {
         let modifiers = self.extract_modifiers(&field_node, content);
         let annotations = self.extract_annotations(&field_node, content);
         let range = self.node_range(field_node);
-        let documentation = self.extract_documentation(&field_node, content);
+        let raw_documentation = self.extract_documentation(&field_node, content);
+        let documentation = raw_documentation.as_deref().map(clean_javadoc);
 
         let name = if let Some(name_node) = declarator_node.child_by_field_name("name") {
             self.node_text(&name_node, content).to_string()
         } else {
             return Ok(None);
         };
+        let initializer = declarator_node
+            .child_by_field_name("value")
+            .map(|value_node| self.node_text(&value_node, content).to_string());
 
         Ok(Some(FieldStructure {
             name,
             type_name,
             modifiers,
             annotations,
+            initializer,
             documentation,
+            raw_documentation,
         }))
     }
 
@@ -628,7 +956,14 @@ impl JavaStructureParser {
         let parameters = self.extract_parameters(&node, content)?;
         let throws = self.extract_throws(&node, content);
         let range = self.node_range(node);
-        let documentation = self.extract_documentation(&node, content);
+        let loc = range.end_line - range.start_line + 1;
+        let sloc = self.source_lines_of_code(node, false);
+        let body = node.child_by_field_name("body");
+        let body_range = body.map(|body| self.node_range(&body));
+        let complexity = body.map(|body| self.cyclomatic_complexity(&body, content));
+        let calls = body.map_or_else(Vec::new, |body| self.method_calls(&body, content));
+        let raw_documentation = self.extract_documentation(&node, content);
+        let documentation = raw_documentation.as_deref().map(clean_javadoc);
 
         Ok(Some(MethodStructure {
             name,
@@ -639,7 +974,13 @@ impl JavaStructureParser {
             type_parameters,
             throws,
             range,
+            loc,
+            sloc,
+            body_range,
+            complexity,
+            calls,
             documentation,
+            raw_documentation,
         }))
     }
 
@@ -656,7 +997,14 @@ impl JavaStructureParser {
         let parameters = self.extract_parameters(&node, content)?;
         let throws = self.extract_throws(&node, content);
         let range = self.node_range(node);
-        let documentation = self.extract_documentation(&node, content);
+        let loc = range.end_line - range.start_line + 1;
+        let sloc = self.source_lines_of_code(node, false);
+        let body = node.child_by_field_name("body");
+        let body_range = body.map(|body| self.node_range(&body));
+        let complexity = body.map(|body| self.cyclomatic_complexity(&body, content));
+        let calls = body.map_or_else(Vec::new, |body| self.method_calls(&body, content));
+        let raw_documentation = self.extract_documentation(&node, content);
+        let documentation = raw_documentation.as_deref().map(clean_javadoc);
 
         Ok(Some(MethodStructure {
             name,
@@ -667,10 +1015,89 @@ impl JavaStructureParser {
             type_parameters: Vec::new(),
             throws,
             range,
+            loc,
+            sloc,
+            body_range,
+            complexity,
+            calls,
             documentation,
+            raw_documentation,
         }))
     }
 
+    /// Lines on which a non-comment token of `node` starts, ends or continues (text blocks);
+    /// with `skip_nested_types`, type declarations directly in its body are not visited
+    fn source_lines_of_code(&self, node: &Node, skip_nested_types: bool) -> usize {
+        let nested_body = node.child_by_field_name("body").filter(|_| skip_nested_types);
+        let mut lines = HashSet::new();
+        let mut stack = vec![*node];
+        while let Some(node) = stack.pop() {
+            match node.kind() {
+                "line_comment" | "block_comment" => continue,
+                "class_declaration" | "interface_declaration" | "enum_declaration" | "record_declaration"
+                | "annotation_type_declaration"
+                    if nested_body.is_some() && node.parent() == nested_body =>
+                {
+                    continue
+                }
+                _ => {}
+            }
+
+            if node.child_count() == 0 {
+                if node.start_byte() < node.end_byte() {
+                    lines.extend(node.start_position().row..=node.end_position().row);
+                }
+            } else {
+                let mut cursor = node.walk();
+                stack.extend(node.children(&mut cursor));
+            }
+        }
+        lines.len()
+    }
+
+    /// 1 plus one per decision point: if, loops, case labels, catch, `&&`, `||` and `?:`
+    fn cyclomatic_complexity(&self, body: &Node, content: &str) -> usize {
+        let mut complexity = 1;
+        let mut stack = vec![*body];
+        while let Some(node) = stack.pop() {
+            complexity += match node.kind() {
+                "if_statement" | "for_statement" | "enhanced_for_statement" | "while_statement"
+                | "do_statement" | "catch_clause" | "ternary_expression" => 1,
+                "switch_label" if self.node_text(&node, content).starts_with("case") => 1,
+                "binary_expression" => node
+                    .child_by_field_name("operator")
+                    .map_or(0, |operator| matches!(operator.kind(), "&&" | "||") as usize),
+                _ => 0,
+            };
+
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+        }
+        complexity
+    }
+
+    /// Distinct method names of the `method_invocation` nodes under `body`, in source order
+    fn method_calls(&self, body: &Node, content: &str) -> Vec<String> {
+        let mut calls: Vec<String> = Vec::new();
+        let mut stack = vec![*body];
+        while let Some(node) = stack.pop() {
+            if node.kind() == "method_invocation"
+                && let Some(name) = node.child_by_field_name("name")
+            {
+                let name = self.node_text(&name, content);
+                if !calls.iter().any(|call| call == name) {
+                    calls.push(name.to_string());
+                }
+            }
+
+            // Pushed in reverse so that children are visited in source order
+            let mut cursor = node.walk();
+            let children: Vec<Node> = node.named_children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+        calls
+    }
+
     fn extract_parameters(&self, node: &Node, content: &str) -> Result<Vec<ParameterStructure>> {
         let mut parameters = Vec::new();
 
@@ -712,14 +1139,15 @@ impl JavaStructureParser {
     }
 
     fn extract_throws(&self, node: &Node, content: &str) -> Vec<String> {
+        // Each exception is its own type node under "throws"; the keyword
+        // and commas are anonymous tokens
         node.children(&mut node.walk())
             .filter(|child| child.kind() == "throws")
             .flat_map(|child| {
-                self.node_text(&child, content)
-                    .split(',')
-                    .map(str::trim)
-                    .filter(|s| !s.is_empty() && *s != "throws")
-                    .map(|s| s.trim_start_matches("throws").trim().to_string())
+                child
+                    .named_children(&mut child.walk())
+                    .map(|type_node| self.node_text(&type_node, content).trim().to_string())
+                    .filter(|s| !s.is_empty())
                     .collect::<Vec<_>>()
             })
             .collect()
@@ -832,6 +1260,410 @@ mod tests {
         assert_eq!(method.throws.len(), 1);
     }
 
+    #[test]
+    fn test_parse_header() {
+        let parser = JavaStructureParser::new().unwrap();
+
+        let java_content = r#"
+            package com.example.service;
+
+            import java.util.List;
+            import java.util.concurrent.*;
+            import static org.junit.Assert.assertEquals;
+
+            public class UserService {
+                private List<String> names;
+                public void run() {}
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("UserService.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let (package, imports) = parser.parse_header(&java_path).unwrap();
+        assert_eq!(package, Some("com.example.service".to_string()));
+        assert_eq!(
+            imports,
+            vec![
+                Import {
+                    path: "java.util.List".to_string(),
+                    is_static: false,
+                    is_wildcard: false,
+                },
+                Import {
+                    path: "java.util.concurrent".to_string(),
+                    is_static: false,
+                    is_wildcard: true,
+                },
+                Import {
+                    path: "org.junit.Assert.assertEquals".to_string(),
+                    is_static: true,
+                    is_wildcard: false,
+                },
+            ]
+        );
+
+        // Nothing past the imports is read
+        let header = parser.read_header(&java_path).unwrap();
+        assert!(header.contains("import static org.junit.Assert.assertEquals;"));
+        assert!(!header.contains("UserService"), "{}", header);
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        assert_eq!(structure.imports, vec!["java.util.List", "java.util.concurrent.*"]);
+        assert_eq!(structure.static_imports, vec!["org.junit.Assert.assertEquals"]);
+    }
+
+    #[test]
+    fn test_static_imports() {
+        let parser = JavaStructureParser::new().unwrap();
+        let java_content = r#"
+            package com.example;
+
+            import java.util.List;
+            import java.util.*;
+            import static java.util.Collections.sort;
+            import static java.lang.Math.*;
+            import static com.x.Y.z;
+
+            class Sorter {}
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Sorter.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        assert_eq!(structure.imports, vec!["java.util.List", "java.util.*"]);
+        assert_eq!(
+            structure.static_imports,
+            vec!["java.util.Collections.sort", "java.lang.Math.*", "com.x.Y.z"]
+        );
+    }
+
+    #[test]
+    fn test_cyclomatic_complexity() {
+        let parser = JavaStructureParser::new().unwrap();
+        let java_content = r#"
+            public abstract class Billing {
+                public int total(int[] amounts, boolean vip) {
+                    int sum = 0;
+                    for (int amount : amounts) {
+                        sum += amount;
+                    }
+                    if (vip) {
+                        sum -= 10;
+                    }
+                    if (sum < 0) {
+                        sum = 0;
+                    }
+                    return sum;
+                }
+
+                public String label(int code) {
+                    switch (code) {
+                        case 1: return "low";
+                        case 2: return "high";
+                        default: return code > 0 && code < 10 ? "other" : "invalid";
+                    }
+                }
+
+                public int zero() { return 0; }
+
+                public abstract void reset();
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Billing.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let complexity: Vec<(&str, Option<usize>)> = structure.top_level_classes[0]
+            .methods
+            .iter()
+            .map(|m| (m.name.as_str(), m.complexity))
+            .collect();
+        assert_eq!(
+            complexity,
+            vec![("total", Some(4)), ("label", Some(5)), ("zero", Some(1)), ("reset", None)]
+        );
+    }
+
+    #[test]
+    fn test_method_calls() {
+        let parser = JavaStructureParser::new().unwrap();
+        let java_content = r#"
+            public class Checkout {
+                public void submit(Order order) {
+                    if (validate(order)) {
+                        repository.save(order);
+                        validate(order);
+                    }
+                }
+
+                private boolean validate(Order order) { return true; }
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Checkout.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let methods = &structure.top_level_classes[0].methods;
+        assert_eq!(methods[0].calls, vec!["validate", "save"]);
+        assert!(methods[1].calls.is_empty());
+    }
+
+    #[test]
+    fn test_lines_of_code() {
+        let parser = JavaStructureParser::new().unwrap();
+        let java_content = r#"
+public class Inventory {
+    // Items by SKU
+    private int count;
+
+    public int restock(int amount) {
+        /* Negative amounts
+           are ignored */
+        if (amount < 0) {
+            return count;
+        }
+
+        count += amount; // running total
+        return count;
+    }
+
+    static class Entry {
+        String sku;
+    }
+}
+"#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Inventory.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let class = &structure.top_level_classes[0];
+        let restock = &class.methods[0];
+        assert_eq!((restock.range.start_line, restock.range.end_line), (6, 15));
+        assert_eq!(restock.loc, 10);
+        assert_eq!(restock.sloc, 7);
+
+        // The nested class counts towards the outer LOC but not its SLOC
+        assert_eq!((class.loc, class.sloc), (19, 10));
+        assert_eq!((class.nested_classes[0].loc, class.nested_classes[0].sloc), (3, 3));
+    }
+
+    #[test]
+    fn test_sealed_modifiers() {
+        let parser = JavaStructureParser::new().unwrap();
+        let java_content = r#"
+            public sealed interface Shape permits Circle, Square {}
+
+            final class Circle implements Shape {}
+
+            non-sealed class Square implements Shape {}
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Shape.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let modifiers: Vec<(&str, &[String])> = structure
+            .top_level_classes
+            .iter()
+            .map(|c| (c.name.as_str(), c.modifiers.as_slice()))
+            .collect();
+        assert_eq!(
+            modifiers,
+            vec![
+                ("Shape", &["public".to_string(), "sealed".to_string()][..]),
+                ("Circle", &["final".to_string()][..]),
+                ("Square", &["non-sealed".to_string()][..]),
+            ]
+        );
+        assert_eq!(structure.top_level_classes[0].kind, ClassKind::Interface);
+        assert_eq!(structure.top_level_classes[2].implements, vec!["Shape"]);
+    }
+
+    #[test]
+    fn test_field_initializers() {
+        let parser = JavaStructureParser::new().unwrap();
+        let java_content = r#"
+            public class UserKeys {
+                private static final String PREFIX = "user_";
+                private int count;
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("UserKeys.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let fields = &structure.top_level_classes[0].fields;
+        assert_eq!(fields[0].name, "PREFIX");
+        assert_eq!(fields[0].initializer.as_deref(), Some("\"user_\""));
+        assert_eq!(fields[1].name, "count");
+        assert_eq!(fields[1].initializer, None);
+    }
+
+    #[test]
+    fn test_multi_argument_annotation_values() {
+        let parser = JavaStructureParser::new().unwrap();
+        let java_content = r#"
+            @RequestMapping(path="/x", method={GET,POST})
+            public class OrderController {}
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("OrderController.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let annotation = &structure.top_level_classes[0].annotations[0];
+        assert_eq!(annotation.name, "RequestMapping");
+        assert_eq!(
+            annotation.values,
+            vec![
+                ("path".to_string(), "\"/x\"".to_string()),
+                ("method".to_string(), "{GET,POST}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_incremental_reparse() {
+        let parser = JavaStructureParser::new().unwrap();
+        let path = Path::new("Greeter.java");
+        let old_source = "class Greeter {\n    void greet() {}\n}\n";
+        let old_tree = parser.parse_tree(old_source).unwrap();
+        let method_names = |structure: &JavaStructurePreview| -> Vec<String> {
+            structure.top_level_classes[0].methods.iter().map(|m| m.name.clone()).collect()
+        };
+        assert_eq!(method_names(&parser.structure_from_tree(path, &old_tree).unwrap()), vec!["greet"]);
+
+        // Insert a method at the start of line 3
+        let inserted = "    void wave() {}\n";
+        let offset = old_source.find("}\n").unwrap() + 2;
+        let new_source = format!("{}{}{}", &old_source[..offset], inserted, &old_source[offset..]);
+        let edit = InputEdit {
+            start_byte: offset,
+            old_end_byte: offset,
+            new_end_byte: offset + inserted.len(),
+            start_position: tree_sitter::Point::new(2, 0),
+            old_end_position: tree_sitter::Point::new(2, 0),
+            new_end_position: tree_sitter::Point::new(3, 0),
+        };
+
+        let (structure, new_tree) = parser.reparse(path, &old_tree, edit, &new_source).unwrap();
+        assert_eq!(method_names(&structure), vec!["greet", "wave"]);
+        assert_eq!(structure.top_level_classes[0].methods[1].range.start_line, 3);
+        assert!(!new_tree.root_node().has_error());
+        assert_eq!(new_tree.source(), new_source);
+    }
+
+    #[test]
+    fn test_signature_with_and_without_fqn() {
+        let parser = JavaStructureParser::new().unwrap();
+        let java_content = r#"
+            package com.example;
+
+            public final class UserService {}
+
+            @interface Audited {}
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("UserService.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let service = &structure.top_level_classes[0];
+        assert_eq!(service.signature(true), "public final class com.example.UserService");
+        assert_eq!(service.signature(false), "public final class UserService");
+        assert_eq!(structure.top_level_classes[1].signature(false), "@interface Audited");
+    }
+
+    #[test]
+    fn test_parse_invalid_utf8_file() {
+        let parser = JavaStructureParser::new().unwrap();
+        let mut java_content = b"public class Legacy {\n    private String name = \"caf".to_vec();
+        // "é" in ISO-8859-1
+        java_content.push(0xe9);
+        java_content.extend_from_slice(b"\";\n}\n");
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Legacy.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let class = &structure.top_level_classes[0];
+        assert_eq!(class.name, "Legacy");
+        assert_eq!(class.fields[0].initializer.as_deref(), Some("\"caf\u{fffd}\""));
+    }
+
+    #[test]
+    fn test_parse_tree() {
+        let parser = JavaStructureParser::new().unwrap();
+        let source_tree = parser.parse_tree("class Greeter { void greet() {} }").unwrap();
+
+        let root = source_tree.root_node();
+        assert_eq!(root.kind(), "program");
+        let class = root.named_child(0).unwrap();
+        assert_eq!(class.kind(), "class_declaration");
+        let name = class.child_by_field_name("name").unwrap();
+        assert_eq!(source_tree.node_text(&name), "Greeter");
+    }
+
+    #[test]
+    fn test_parse_throws() {
+        let parser = JavaStructureParser::new().unwrap();
+
+        let java_content = r#"
+            public class Loader {
+                public void load() throws java.io.IOException, LoadException<Map<String, Integer>> {}
+                public void reload() throws throwsHelperException {}
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Loader.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = parser.parse_structure(&java_path).unwrap();
+        let class = &structure.top_level_classes[0];
+
+        let load = class.methods.iter().find(|m| m.name == "load").unwrap();
+        assert_eq!(
+            load.throws,
+            vec!["java.io.IOException", "LoadException<Map<String, Integer>>"]
+        );
+
+        let reload = class.methods.iter().find(|m| m.name == "reload").unwrap();
+        assert_eq!(reload.throws, vec!["throwsHelperException"]);
+    }
+
+    #[test]
+    fn test_max_file_size() {
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Generated.java");
+        let fields: String = (0..20).map(|i| format!("    private int field{};\n", i)).collect();
+        std::fs::write(&java_path, format!("public class Generated {{\n{}}}\n", fields)).unwrap();
+
+        let parser = JavaStructureParser::with_max_file_size(100).unwrap();
+        let error = parser.parse_structure(&java_path).unwrap_err();
+        assert!(error.to_string().contains("exceeds the 100 byte limit"));
+        assert!(parser.parse_header(&java_path).is_err());
+
+        let parser = JavaStructureParser::new().unwrap();
+        assert!(parser.parse_structure(&java_path).is_ok());
+    }
+
     #[test]
     fn test_parse_nested_classes() {
         let parser = JavaStructureParser::new().unwrap();
@@ -872,6 +1704,127 @@ mod tests {
 
         let nested = &outer.nested_classes[1];
         assert_eq!(nested.name, "StaticNested");
+        assert_eq!(nested.fqn, "com.example.OuterClass.StaticNested");
+    }
+
+    #[test]
+    fn test_repeatable_annotations() {
+        let java_content = r#"
+            package com.example.jobs;
+
+            public class ReportJob {
+                @Schedule(dayOfMonth = "Last")
+                @Schedule(dayOfWeek = "Fri", hour = "23")
+                public void generate() {}
+
+                @Schedules({@Schedule(hour = "1"), @Schedule(hour = "13")})
+                public void cleanUp() {}
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("ReportJob.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = JavaStructureParser::new().unwrap().parse_structure(&java_path).unwrap();
+        let method = &structure.top_level_classes[0].methods[0];
+
+        assert_eq!(method.annotations.len(), 2);
+        assert!(method.annotations.iter().all(|a| a.name == "Schedule"));
+        assert_eq!(
+            method.annotations[0].values,
+            vec![("dayOfMonth".to_string(), "\"Last\"".to_string())]
+        );
+        assert_eq!(
+            method.annotations[1].values,
+            vec![
+                ("dayOfWeek".to_string(), "\"Fri\"".to_string()),
+                ("hour".to_string(), "\"23\"".to_string()),
+            ]
+        );
+
+        // The container form keeps every repeated annotation, in order
+        let container = &structure.top_level_classes[0].methods[1].annotations[0];
+        assert_eq!(container.name, "Schedules");
+        assert_eq!(
+            container.values,
+            vec![
+                ("value".to_string(), "@Schedule(hour = \"1\")".to_string()),
+                ("value".to_string(), "@Schedule(hour = \"13\")".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_parameter_bounds() {
+        let java_content = r#"
+            package com.example.util;
+
+            public class SortedBag<T extends Comparable<T> & Serializable, K> {
+                public <E extends Enum<E>> E parse(Class<E> type, String value) {
+                    return Enum.valueOf(type, value);
+                }
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("SortedBag.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = JavaStructureParser::new().unwrap().parse_structure(&java_path).unwrap();
+        let class = &structure.top_level_classes[0];
+
+        assert_eq!(
+            class.type_parameters,
+            vec![
+                TypeParameter {
+                    name: "T".to_string(),
+                    bounds: vec!["Comparable<T>".to_string(), "Serializable".to_string()],
+                },
+                TypeParameter { name: "K".to_string(), bounds: vec![] },
+            ]
+        );
+        assert_eq!(
+            class.methods[0].type_parameters,
+            vec![TypeParameter { name: "E".to_string(), bounds: vec!["Enum<E>".to_string()] }]
+        );
+    }
+
+    #[test]
+    fn test_annotation_values_referencing_constants() {
+        let java_content = r#"
+            package com.example.model;
+
+            public class Product {
+                private static final int MAX_LENGTH = 64;
+
+                @Column(length = MAX_LENGTH, precision = Limits.PRECISION)
+                private String name;
+
+                @Size(MAX_LENGTH)
+                private String code;
+            }
+        "#;
+
+        let dir = tempdir().unwrap();
+        let java_path = dir.path().join("Product.java");
+        std::fs::write(&java_path, java_content).unwrap();
+
+        let structure = JavaStructureParser::new().unwrap().parse_structure(&java_path).unwrap();
+        let fields = &structure.top_level_classes[0].fields;
+
+        let column = &fields[1].annotations[0];
+        assert_eq!(column.name, "Column");
+        assert_eq!(
+            column.values,
+            vec![
+                ("length".to_string(), "MAX_LENGTH".to_string()),
+                ("precision".to_string(), "Limits.PRECISION".to_string()),
+            ]
+        );
+
+        let size = &fields[2].annotations[0];
+        assert_eq!(size.values, vec![("value".to_string(), "MAX_LENGTH".to_string())]);
     }
 
     #[test]
@@ -0,0 +1,473 @@
+use crate::parser::FileParseable;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// How many nested `${...}` levels are expanded before giving up (guards against cycles)
+const MAX_PLACEHOLDER_DEPTH: usize = 10;
+
+/// A dependency declared in a pom.xml
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MavenDependency {
+    pub group_id: String,
+    pub artifact_id: String,
+    /// Absent when managed by a parent or a BOM
+    pub version: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// The `<parent>` section of a pom.xml
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MavenParent {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+    /// Path to the parent pom.xml, when not the default `../pom.xml`
+    pub relative_path: Option<String>,
+}
+
+/// The coordinates, properties and dependencies of a pom.xml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MavenModule {
+    pub path: PathBuf,
+    /// Inherited from the parent when the module does not declare it
+    pub group_id: Option<String>,
+    pub artifact_id: Option<String>,
+    /// Inherited from the parent when the module does not declare it
+    pub version: Option<String>,
+    pub parent: Option<MavenParent>,
+    /// The `<properties>` block, as written
+    pub properties: BTreeMap<String, String>,
+    pub dependencies: Vec<MavenDependency>,
+    /// Versions declared in `<dependencyManagement>`, not dependencies of the module
+    pub managed_dependencies: Vec<MavenDependency>,
+    /// Placeholders left as-is by resolve_placeholders because no property defines them
+    pub unresolved_placeholders: Vec<String>,
+}
+
+impl MavenModule {
+    /// Substitute `${...}` references in dependency versions (managed ones too), following properties that
+    /// reference other properties. `project.version`, `project.groupId` and
+    /// `project.artifactId` are known too. Unknown placeholders stay in the version and are
+    /// listed in `unresolved_placeholders`.
+    pub fn resolve_placeholders(&mut self) {
+        let mut properties = self.properties.clone();
+        for (key, value) in [
+            ("project.groupId", &self.group_id),
+            ("project.artifactId", &self.artifact_id),
+            ("project.version", &self.version),
+        ] {
+            if let Some(value) = value {
+                properties.entry(key.to_string()).or_insert_with(|| value.clone());
+            }
+        }
+        if let Some(parent) = &self.parent {
+            properties.entry("project.parent.groupId".to_string()).or_insert_with(|| parent.group_id.clone());
+            properties.entry("project.parent.version".to_string()).or_insert_with(|| parent.version.clone());
+        }
+
+        let mut unresolved = Vec::new();
+        for dependency in self.dependencies.iter_mut().chain(&mut self.managed_dependencies) {
+            if let Some(version) = &dependency.version {
+                dependency.version = Some(substitute(version, &properties, 0, &mut unresolved));
+            }
+        }
+
+        unresolved.sort();
+        unresolved.dedup();
+        self.unresolved_placeholders = unresolved;
+    }
+}
+
+/// Expand the placeholders of `value`, recording the names that could not be resolved
+fn substitute(value: &str, properties: &BTreeMap<String, String>, depth: usize, unresolved: &mut Vec<String>) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + length];
+        result.push_str(&rest[..start]);
+        match properties.get(name) {
+            Some(replacement) if depth < MAX_PLACEHOLDER_DEPTH => {
+                result.push_str(&substitute(replacement, properties, depth + 1, unresolved));
+            }
+            _ => {
+                unresolved.push(name.to_string());
+                result.push_str(&rest[start..=start + length]);
+            }
+        }
+        rest = &rest[start + length + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Pragmatic regex-based reader for pom.xml files
+/// Only the elements needed to describe a project are extracted: its coordinates, its
+/// properties, its dependencies and its managed dependency versions.
+pub struct MavenParser {
+    comment_pattern: Regex,
+    element_pattern: Regex,
+}
+
+impl MavenParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            comment_pattern: Regex::new(r"(?s)<!--.*?-->")?,
+            element_pattern: Regex::new(r"(?s)<(?P<name>[\w.\-]+)>\s*(?P<value>[^<]*?)\s*</[\w.\-]+>")?,
+        })
+    }
+
+    /// Parse a pom.xml, inherit the parent's groupId and version, and resolve the
+    /// placeholders of its dependency versions
+    pub fn parse_pom_file(&self, path: &Path) -> Result<MavenModule> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read POM file: {}", path.display()))?;
+        let mut module = self.parse_source(path, &content);
+        module.resolve_placeholders();
+        Ok(module)
+    }
+
+    /// Parse pom.xml content without resolving placeholders
+    pub fn parse_source(&self, path: &Path, content: &str) -> MavenModule {
+        let content = self.comment_pattern.replace_all(content, "");
+
+        let properties = block(&content, "properties")
+            .map(|properties| {
+                self.element_pattern
+                    .captures_iter(properties)
+                    .map(|c| (c["name"].to_string(), c["value"].to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Plugin and profile dependencies are not dependencies of the project itself
+        let project_level = without_sections(&content, &["profiles", "build", "reporting"]);
+        // Managed versions are declared apart from the dependencies actually used
+        let managed_dependencies =
+            self.dependencies(block_with_tags(&project_level, "dependencyManagement").unwrap_or(""));
+        let dependencies = self.dependencies(&without_sections(&project_level, &["dependencyManagement"]));
+
+        // The project's own coordinates are the ones outside every nested section
+        let top_level = without_sections(
+            &content,
+            &["parent", "properties", "dependencies", "dependencyManagement", "build", "profiles", "modules"],
+        );
+        let coordinates = self.elements(&top_level);
+
+        let parent = block(&content, "parent").and_then(|parent| {
+            let elements = self.elements(parent);
+            Some(MavenParent {
+                group_id: elements.get("groupId")?.clone(),
+                artifact_id: elements.get("artifactId")?.clone(),
+                version: elements.get("version")?.clone(),
+                relative_path: elements.get("relativePath").filter(|p| !p.is_empty()).cloned(),
+            })
+        });
+
+        let own = |name: &str| coordinates.get(name).filter(|v| !v.is_empty()).cloned();
+        MavenModule {
+            path: path.to_path_buf(),
+            group_id: own("groupId").or_else(|| parent.as_ref().map(|p| p.group_id.clone())),
+            artifact_id: own("artifactId"),
+            version: own("version").or_else(|| parent.as_ref().map(|p| p.version.clone())),
+            parent,
+            properties,
+            dependencies,
+            managed_dependencies,
+            unresolved_placeholders: Vec::new(),
+        }
+    }
+
+    fn dependencies(&self, fragment: &str) -> Vec<MavenDependency> {
+        blocks(fragment, "dependency")
+            .into_iter()
+            .filter_map(|dependency| {
+                let elements = self.elements(dependency);
+                Some(MavenDependency {
+                    group_id: elements.get("groupId")?.clone(),
+                    artifact_id: elements.get("artifactId")?.clone(),
+                    version: elements.get("version").cloned(),
+                    scope: elements.get("scope").cloned(),
+                })
+            })
+            .collect()
+    }
+
+    /// Leaf elements of a fragment, first occurrence wins
+    fn elements(&self, fragment: &str) -> BTreeMap<String, String> {
+        let mut elements = BTreeMap::new();
+        for captures in self.element_pattern.captures_iter(fragment) {
+            elements
+                .entry(captures["name"].to_string())
+                .or_insert_with(|| captures["value"].to_string());
+        }
+        elements
+    }
+}
+
+impl FileParseable<MavenModule> for MavenParser {
+    fn parse_file(&mut self, path: &Path) -> Result<MavenModule> {
+        self.parse_pom_file(path)
+    }
+}
+
+/// Content of the first `<tag>...</tag>` element
+fn block<'a>(content: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let start = content.find(&open)? + open.len();
+    let end = content[start..].find(&format!("</{}>", tag))? + start;
+    Some(&content[start..end])
+}
+
+/// Content of every `<tag>...</tag>` element
+fn blocks<'a>(content: &'a str, tag: &str) -> Vec<&'a str> {
+    let close = format!("</{}>", tag);
+    let mut found = Vec::new();
+    let mut rest = content;
+    while let Some(inner) = block(rest, tag) {
+        found.push(inner);
+        let end = rest.find(&close).map_or(rest.len(), |end| end + close.len());
+        rest = &rest[end..];
+    }
+    found
+}
+
+/// The content with every `<section>...</section>` element of the given sections removed
+fn without_sections(content: &str, sections: &[&str]) -> String {
+    let mut remaining = content.to_string();
+    for section in sections {
+        while let Some(section_block) = block_with_tags(&remaining, section) {
+            remaining = remaining.replacen(section_block, "", 1);
+        }
+    }
+    remaining
+}
+
+/// The first `<tag>...</tag>` element, tags included
+fn block_with_tags<'a>(content: &'a str, tag: &str) -> Option<&'a str> {
+    let start = content.find(&format!("<{}>", tag))?;
+    let close = format!("</{}>", tag);
+    let end = content[start..].find(&close)? + start + close.len();
+    Some(&content[start..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resolve_property_versions() {
+        let pom = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project>
+    <parent>
+        <groupId>org.springframework.boot</groupId>
+        <artifactId>spring-boot-starter-parent</artifactId>
+        <version>3.2.0</version>
+    </parent>
+    <groupId>com.example</groupId>
+    <artifactId>shop</artifactId>
+    <version>1.4.0</version>
+
+    <properties>
+        <spring.version>${spring.major}.1</spring.version>
+        <spring.major>6.1</spring.major>
+        <!-- <junit.version>4.13</junit.version> -->
+    </properties>
+
+    <dependencies>
+        <dependency>
+            <groupId>org.springframework</groupId>
+            <artifactId>spring-core</artifactId>
+            <version>${spring.version}</version>
+        </dependency>
+        <dependency>
+            <groupId>com.example</groupId>
+            <artifactId>shop-api</artifactId>
+            <version>${project.version}</version>
+        </dependency>
+        <dependency>
+            <groupId>junit</groupId>
+            <artifactId>junit</artifactId>
+            <version>${junit.version}</version>
+            <scope>test</scope>
+        </dependency>
+    </dependencies>
+</project>
+"#;
+        let dir = tempdir().unwrap();
+        let pom_path = dir.path().join("pom.xml");
+        std::fs::write(&pom_path, pom).unwrap();
+
+        let module = MavenParser::new().unwrap().parse_pom_file(&pom_path).unwrap();
+        assert_eq!(module.group_id.as_deref(), Some("com.example"));
+        assert_eq!(module.artifact_id.as_deref(), Some("shop"));
+        assert_eq!(module.properties["spring.version"], "${spring.major}.1");
+
+        let versions: Vec<Option<&str>> = module.dependencies.iter().map(|d| d.version.as_deref()).collect();
+        assert_eq!(versions, vec![Some("6.1.1"), Some("1.4.0"), Some("${junit.version}")]);
+        assert_eq!(module.dependencies[2].scope.as_deref(), Some("test"));
+        assert_eq!(module.unresolved_placeholders, vec!["junit.version"]);
+    }
+
+    #[test]
+    fn test_inherit_parent_coordinates() {
+        let pom = r#"<project>
+    <parent>
+        <groupId>com.example</groupId>
+        <artifactId>shop-parent</artifactId>
+        <version>2.0.0-SNAPSHOT</version>
+        <relativePath>../parent/pom.xml</relativePath>
+    </parent>
+    <artifactId>shop-web</artifactId>
+    <dependencies>
+        <dependency>
+            <groupId>com.example</groupId>
+            <artifactId>shop-core</artifactId>
+            <version>${project.version}</version>
+        </dependency>
+    </dependencies>
+</project>
+"#;
+        let dir = tempdir().unwrap();
+        let pom_path = dir.path().join("pom.xml");
+        std::fs::write(&pom_path, pom).unwrap();
+
+        let module = MavenParser::new().unwrap().parse_pom_file(&pom_path).unwrap();
+        assert_eq!(
+            module.parent,
+            Some(MavenParent {
+                group_id: "com.example".to_string(),
+                artifact_id: "shop-parent".to_string(),
+                version: "2.0.0-SNAPSHOT".to_string(),
+                relative_path: Some("../parent/pom.xml".to_string()),
+            })
+        );
+        assert_eq!(module.group_id.as_deref(), Some("com.example"));
+        assert_eq!(module.artifact_id.as_deref(), Some("shop-web"));
+        assert_eq!(module.version.as_deref(), Some("2.0.0-SNAPSHOT"));
+        assert_eq!(module.dependencies[0].version.as_deref(), Some("2.0.0-SNAPSHOT"));
+    }
+
+    #[test]
+    fn test_managed_dependencies_are_separate() {
+        let pom = r#"<project>
+    <groupId>com.example</groupId>
+    <artifactId>shop</artifactId>
+    <version>1.0.0</version>
+    <properties>
+        <jackson.version>2.17.0</jackson.version>
+    </properties>
+    <dependencyManagement>
+        <dependencies>
+            <dependency>
+                <groupId>com.fasterxml.jackson.core</groupId>
+                <artifactId>jackson-databind</artifactId>
+                <version>${jackson.version}</version>
+            </dependency>
+            <dependency>
+                <groupId>org.slf4j</groupId>
+                <artifactId>slf4j-api</artifactId>
+                <version>2.0.12</version>
+            </dependency>
+        </dependencies>
+    </dependencyManagement>
+    <dependencies>
+        <dependency>
+            <groupId>com.fasterxml.jackson.core</groupId>
+            <artifactId>jackson-databind</artifactId>
+        </dependency>
+    </dependencies>
+</project>
+"#;
+        let dir = tempdir().unwrap();
+        let pom_path = dir.path().join("pom.xml");
+        std::fs::write(&pom_path, pom).unwrap();
+
+        let module = MavenParser::new().unwrap().parse_pom_file(&pom_path).unwrap();
+        assert_eq!(module.dependencies.len(), 1);
+        assert_eq!(module.dependencies[0].artifact_id, "jackson-databind");
+        assert_eq!(module.dependencies[0].version, None);
+
+        let managed: Vec<(&str, Option<&str>)> = module
+            .managed_dependencies
+            .iter()
+            .map(|d| (d.artifact_id.as_str(), d.version.as_deref()))
+            .collect();
+        assert_eq!(managed, vec![("jackson-databind", Some("2.17.0")), ("slf4j-api", Some("2.0.12"))]);
+    }
+
+    #[test]
+    fn test_plugin_and_profile_dependencies_are_not_project_dependencies() {
+        let pom = r#"<project>
+    <groupId>com.example</groupId>
+    <artifactId>shop</artifactId>
+    <version>1.0.0</version>
+    <dependencies>
+        <dependency>
+            <groupId>org.slf4j</groupId>
+            <artifactId>slf4j-api</artifactId>
+            <version>2.0.12</version>
+        </dependency>
+    </dependencies>
+    <build>
+        <plugins>
+            <plugin>
+                <groupId>org.apache.maven.plugins</groupId>
+                <artifactId>maven-checkstyle-plugin</artifactId>
+                <dependencies>
+                    <dependency>
+                        <groupId>com.puppycrawl.tools</groupId>
+                        <artifactId>checkstyle</artifactId>
+                        <version>10.14.0</version>
+                    </dependency>
+                </dependencies>
+            </plugin>
+        </plugins>
+    </build>
+    <profiles>
+        <profile>
+            <id>it</id>
+            <dependencies>
+                <dependency>
+                    <groupId>org.testcontainers</groupId>
+                    <artifactId>testcontainers</artifactId>
+                    <version>1.19.7</version>
+                </dependency>
+            </dependencies>
+            <dependencyManagement>
+                <dependencies>
+                    <dependency>
+                        <groupId>org.junit</groupId>
+                        <artifactId>junit-bom</artifactId>
+                        <version>5.10.2</version>
+                    </dependency>
+                </dependencies>
+            </dependencyManagement>
+        </profile>
+    </profiles>
+</project>
+"#;
+        let module = MavenParser::new().unwrap().parse_source(Path::new("pom.xml"), pom);
+        let artifacts: Vec<&str> = module.dependencies.iter().map(|d| d.artifact_id.as_str()).collect();
+        assert_eq!(artifacts, vec!["slf4j-api"]);
+        assert!(module.managed_dependencies.is_empty());
+        assert_eq!(module.artifact_id.as_deref(), Some("shop"));
+    }
+
+    #[test]
+    fn test_cyclic_placeholders_stay_unresolved() {
+        let mut properties = BTreeMap::new();
+        properties.insert("a".to_string(), "${b}".to_string());
+        properties.insert("b".to_string(), "${a}".to_string());
+        let mut unresolved = Vec::new();
+        let value = substitute("${a}", &properties, 0, &mut unresolved);
+        assert!(value.starts_with("${"));
+        assert!(!unresolved.is_empty());
+    }
+}
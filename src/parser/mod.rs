@@ -1,16 +1,62 @@
+mod dependency_graph;
+mod groovy;
 mod java_structure;
+mod maven;
 
+pub use dependency_graph::*;
+pub use groovy::*;
 pub use java_structure::*;
+pub use maven::*;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use crate::types::{PropertiesFile, XmlFile};
+
 pub struct FileParser;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FileSuffix {
     Java,
+    Xml,
+    Property,
+    /// A Maven `pom.xml`, told apart from other XML files by its name
+    Pom,
+    Groovy,
+}
+
+impl FileSuffix {
+    /// Suffix of a file extension, without the dot ("java", "xml", "properties", "groovy")
+    pub fn from_extension(extension: &str) -> Option<FileSuffix> {
+        match extension.to_ascii_lowercase().as_str() {
+            "java" => Some(FileSuffix::Java),
+            "xml" => Some(FileSuffix::Xml),
+            "properties" => Some(FileSuffix::Property),
+            "groovy" => Some(FileSuffix::Groovy),
+            _ => None,
+        }
+    }
+
+    /// Suffix of a path, recognizing `pom.xml` by name
+    pub fn from_path(path: &Path) -> Option<FileSuffix> {
+        if path.file_name().is_some_and(|name| name == "pom.xml") {
+            return Some(FileSuffix::Pom);
+        }
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(FileSuffix::from_extension)
+    }
+}
+
+/// Algorithm of `FileMeta::hash_value`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    /// Fast and enough to detect changed files; the default, as existing indexes store MD5
+    #[default]
+    Md5,
+    /// Collision-resistant, for content-addressed storage
+    Blake3,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,7 +69,7 @@ pub struct FileMeta {
 
 impl FileMeta {
     pub fn new(path: &Path, suffix: FileSuffix, source: &str) -> Self {
-        let hash_value = format!("{:x}", md5::compute(source));
+        let hash_value = Self::hash_source(source);
         let name = path
             .file_name()
             .and_then(|s| s.to_str())
@@ -36,6 +82,84 @@ impl FileMeta {
             hash_value,
         }
     }
+
+    /// Meta of a file whose suffix is derived from its path; None for unsupported files
+    pub fn for_path(path: &Path, source: &str) -> Option<Self> {
+        FileSuffix::from_path(path).map(|suffix| Self::new(path, suffix, source))
+    }
+
+    /// Like `new`, hashing the source with `algorithm` instead of MD5
+    pub fn with_hash(path: &Path, suffix: FileSuffix, source: &str, algorithm: HashAlgorithm, salt: Option<&str>) -> Self {
+        FileMeta {
+            hash_value: Self::hash_source_with(source, algorithm, salt),
+            ..Self::new(path, suffix, "")
+        }
+    }
+
+    /// Content hash stored as `hash_value`, usable to detect unchanged files
+    pub fn hash_source(source: &str) -> String {
+        Self::hash_source_with(source, HashAlgorithm::Md5, None)
+    }
+
+    /// Hex content hash; a salt is hashed before the source, so the same content hashes
+    /// differently per salt (e.g. one namespace per content-addressed store)
+    pub fn hash_source_with(source: &str, algorithm: HashAlgorithm, salt: Option<&str>) -> String {
+        let salt = salt.unwrap_or("");
+        match algorithm {
+            HashAlgorithm::Md5 => {
+                let mut context = md5::Context::new();
+                context.consume(salt);
+                context.consume(source);
+                format!("{:x}", context.compute())
+            }
+            HashAlgorithm::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(salt.as_bytes());
+                hasher.update(source.as_bytes());
+                hasher.finalize().to_hex().to_string()
+            }
+        }
+    }
+}
+
+/// Read a source file, replacing bytes that are not valid UTF-8 (legacy ISO-8859-1
+/// sources, ...) with U+FFFD and printing a warning, so one such file does not stop a run
+pub fn read_source_lossy(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path)?;
+    match String::from_utf8(bytes) {
+        Ok(source) => Ok(source),
+        Err(e) => {
+            eprintln!("⚠️  {} is not valid UTF-8, invalid bytes were replaced", path.display());
+            Ok(String::from_utf8_lossy(e.as_bytes()).into_owned())
+        }
+    }
+}
+
+/// Javadoc text without its `/**`, `*/` and leading `*` markers
+/// Line breaks are kept, runs of blank lines become one paragraph break, and blank
+/// lines at either end are dropped.
+pub fn clean_javadoc(raw: &str) -> String {
+    let body = raw.trim();
+    let body = body.strip_prefix("/**").unwrap_or(body);
+    let body = body.strip_suffix("*/").unwrap_or(body);
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in body.lines() {
+        let line = line.trim_start();
+        let line = match line.strip_prefix('*') {
+            Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+            None => line,
+        }
+        .trim_end();
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
 }
 
 pub trait FileParseable<T> {
@@ -53,17 +177,69 @@ impl FileParser {
         parser.parse_file(path)
     }
 
+    pub fn parse_xml_file(&self, path: &Path) -> Result<XmlFile> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read XML file: {}", path.display()))?;
+        // First element that is not a declaration, comment or doctype
+        let root_element = content
+            .split('<')
+            .skip(1)
+            .find(|tag| !tag.starts_with(['?', '!']))
+            .and_then(|tag| tag.split(|c: char| c.is_whitespace() || c == '>' || c == '/').next())
+            .unwrap_or("")
+            .to_string();
+
+        Ok(XmlFile {
+            path: path.to_path_buf(),
+            root_element,
+            content,
+        })
+    }
+
+    /// Read `key=value` and `key: value` pairs, skipping comments
+    pub fn parse_properties_file(&self, path: &Path) -> Result<PropertiesFile> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read properties file: {}", path.display()))?;
+        let properties = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(['#', '!']))
+            .filter_map(|line| {
+                let (key, value) = line.split_once(['=', ':'])?;
+                Some((key.trim().to_string(), value.trim().to_string()))
+            })
+            .collect();
+
+        Ok(PropertiesFile {
+            path: path.to_path_buf(),
+            properties,
+        })
+    }
+
     ///find files that java project cared.
-    pub fn find_source_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
-        let mut files = Vec::new();
+    /// Paths matching one of the `exclude` globs (relative to `root`, e.g. `target/**`) are skipped,
+    /// and so is everything listed in the project's `.gitignore` files.
+    pub fn find_source_files(&self, root: &Path, exclude: &[String]) -> Result<Vec<PathBuf>> {
+        let exclude_set = exclude_globs(exclude)?;
+
+        let root_path = root.to_path_buf();
+        let walker = ignore::WalkBuilder::new(root)
+            // .gitignore applies even when the project is not (yet) a git checkout
+            .require_git(false)
+            .filter_entry(move |entry| {
+                let relative = entry.path().strip_prefix(&root_path).unwrap_or(entry.path());
+                !exclude_set.is_match(relative)
+            })
+            .build();
 
-        for entry in walkdir::WalkDir::new(root) {
+        let mut files = Vec::new();
+        for entry in walker {
             //shadow variable for exception
             let entry = entry?;
-            if entry.file_type().is_file() {
+            if entry.file_type().is_some_and(|t| t.is_file()) {
                 let path = entry.path();
                 match path.extension().and_then(|ext| ext.to_str()) {
-                    Some("java") => {
+                    Some("java") | Some("groovy") | Some("xml") | Some("properties") => {
                         files.push(path.to_path_buf());
                     }
                     _ => {}
@@ -74,3 +250,94 @@ impl FileParser {
         Ok(files)
     }
 }
+
+/// Compile `--exclude` patterns, which match paths relative to the project root
+pub fn exclude_globs(patterns: &[String]) -> Result<globset::GlobSet> {
+    let mut builder = globset::GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(globset::Glob::new(pattern).with_context(|| format!("Invalid exclude pattern: {}", pattern))?);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_clean_javadoc() {
+        let raw = "/**\n     * Finds users.\n     *\n     *\n     * <pre>\n     *   find(\"bob\");\n     * </pre>\n     * @param name the user name\n     */";
+        let cleaned = clean_javadoc(raw);
+        assert_eq!(cleaned, "Finds users.\n\n<pre>\n  find(\"bob\");\n</pre>\n@param name the user name");
+        assert!(cleaned.lines().all(|line| !line.trim_start().starts_with('*')));
+
+        assert_eq!(clean_javadoc("/** The UserService */"), "The UserService");
+        assert_eq!(clean_javadoc("/**\n */"), "");
+    }
+
+    #[test]
+    fn test_file_meta_suffix_from_path() {
+        let cases = [
+            ("src/Main.java", FileSuffix::Java),
+            ("src/main/resources/beans.xml", FileSuffix::Xml),
+            ("src/main/resources/application.properties", FileSuffix::Property),
+            ("pom.xml", FileSuffix::Pom),
+            ("Legacy.JAVA", FileSuffix::Java),
+        ];
+        for (path, suffix) in cases {
+            let meta = FileMeta::for_path(Path::new(path), "content").unwrap();
+            assert_eq!(meta.suffix, suffix, "{}", path);
+            assert_eq!(meta.hash_value, FileMeta::hash_source("content"));
+        }
+        assert!(FileMeta::for_path(Path::new("README.md"), "").is_none());
+        assert_eq!(FileSuffix::from_extension("properties"), Some(FileSuffix::Property));
+        assert_eq!(FileSuffix::from_extension("groovy"), Some(FileSuffix::Groovy));
+        assert_eq!(FileSuffix::from_extension("kt"), None);
+    }
+
+    #[test]
+    fn test_blake3_source_hash() {
+        let source = "public class A {}\n";
+        let md5 = FileMeta::hash_source(source);
+        let blake3 = FileMeta::hash_source_with(source, HashAlgorithm::Blake3, None);
+
+        assert_eq!(md5.len(), 32);
+        assert_eq!(blake3.len(), 64);
+        assert_eq!(blake3, FileMeta::hash_source_with(source, HashAlgorithm::Blake3, None));
+        assert_ne!(blake3, FileMeta::hash_source_with("public class B {}\n", HashAlgorithm::Blake3, None));
+        // Reference BLAKE3 digest of the empty input
+        assert_eq!(
+            FileMeta::hash_source_with("", HashAlgorithm::Blake3, None),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+
+        // MD5 without a salt is unchanged, so existing indexes still match
+        assert_eq!(md5, format!("{:x}", md5::compute(source)));
+
+        let salted = FileMeta::hash_source_with(source, HashAlgorithm::Blake3, Some("store-1"));
+        assert_ne!(salted, blake3);
+        assert_eq!(salted, FileMeta::hash_source_with(source, HashAlgorithm::Blake3, Some("store-1")));
+
+        let meta = FileMeta::with_hash(Path::new("A.java"), FileSuffix::Java, source, HashAlgorithm::Blake3, None);
+        assert_eq!(meta.hash_value, blake3);
+        assert_eq!(meta.name, "A.java");
+    }
+
+    #[test]
+    fn test_find_source_files_excludes() {
+        let dir = tempdir().unwrap();
+        for relative in ["src/Real.java", "target/Generated.java", "build/Built.java", "vendor/Lib.java"] {
+            let path = dir.path().join(relative);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, "class A {}\n").unwrap();
+        }
+        std::fs::write(dir.path().join(".gitignore"), "build/\n").unwrap();
+
+        let exclude = vec!["target/**".to_string(), "vendor".to_string()];
+        let files = FileParser.find_source_files(dir.path(), &exclude).unwrap();
+        assert_eq!(files, vec![dir.path().join("src/Real.java")]);
+
+        assert!(FileParser.find_source_files(dir.path(), &["[".to_string()]).is_err());
+    }
+}
@@ -1,92 +1,198 @@
 use anyhow::Result;
+use lru::LruCache;
+use std::num::NonZeroUsize;
 use std::path::Path;
-use std::collections::HashMap;
-use tokio::sync::RwLock;
+use tokio::sync::Mutex;
 use serde::{Deserialize, Serialize};
 
 use crate::indexer::IndexManager;
-use crate::types::{SearchQuery, SearchResult, DeclarationKind, SearchFilter};
+use crate::types::{SearchQuery, SearchResult, DeclarationKind, SearchFilter, MethodSearchResult, FilterCombine, Method, Declaration};
+
+/// Number of distinct queries kept in the cache by default
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// Number of results returned by a search without a limit
+pub const DEFAULT_SEARCH_LIMIT: usize = 100;
 
 pub struct QueryEngine {
     index_manager: IndexManager,
-    cache: RwLock<HashMap<String, Vec<SearchResult>>>,
+    cache: Mutex<LruCache<String, Vec<SearchResult>>>,
 }
 
 impl QueryEngine {
     pub fn new(index_path: &Path) -> Result<Self> {
+        Self::with_cache_capacity(index_path, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Create an engine whose cache keeps at most `capacity` queries,
+    /// evicting the least recently used ones beyond that
+    pub fn with_cache_capacity(index_path: &Path, capacity: usize) -> Result<Self> {
         let index_manager = IndexManager::new(index_path)?;
-        
-        Ok(Self {
-            index_manager,
-            cache: RwLock::new(HashMap::new()),
-        })
+        Self::new_with_manager_and_capacity(index_manager, capacity)
     }
 
     pub fn new_with_manager(index_manager: IndexManager) -> Result<Self> {
+        Self::new_with_manager_and_capacity(index_manager, DEFAULT_CACHE_CAPACITY)
+    }
+
+    pub fn new_with_manager_and_capacity(index_manager: IndexManager, capacity: usize) -> Result<Self> {
+        let capacity = NonZeroUsize::new(capacity)
+            .ok_or_else(|| anyhow::anyhow!("Cache capacity must be greater than zero"))?;
+
         Ok(Self {
             index_manager,
-            cache: RwLock::new(HashMap::new()),
+            cache: Mutex::new(LruCache::new(capacity)),
         })
     }
 
     pub async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
-        // Check cache first
-        let cache_key = format!("{:?}:{}", query.kind, query.query);
+        // Check cache first (keyed on the whole query, filters and limit included)
+        let cache_key = serde_json::to_string(query)?;
         {
-            let cache = self.cache.read().await;
+            let mut cache = self.cache.lock().await;
             if let Some(cached) = cache.get(&cache_key) {
                 return Ok(cached.clone());
             }
         }
 
-        let mut results = self.index_manager.search(query).await?;
-        
-        // Apply filters
-        results = self.apply_filters(results, &query.filters)?;
-        
-        // Apply sorting
-        results = self.sort_results(results, &query.kind);
+        let results = self.search_uncached(query).await?;
 
         // Cache results
         {
-            let mut cache = self.cache.write().await;
-            cache.insert(cache_key, results.clone());
+            let mut cache = self.cache.lock().await;
+            cache.put(cache_key, results.clone());
         }
 
         Ok(results)
     }
 
+    /// `search` without the cache, for one-off reads which would otherwise keep their
+    /// results alive in the cache
+    /// Every match is filtered and sorted before the `offset`/`limit` window is cut, so that
+    /// pages are full and follow one order
+    pub async fn search_uncached(&self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+        let everything = SearchQuery {
+            // TopDocs needs a limit of at least one
+            limit: Some(self.index_manager.count(query)?.max(1)),
+            offset: None,
+            ..query.clone()
+        };
+        let mut results = self.index_manager.search(&everything).await?;
+
+        // Apply filters
+        results = self.apply_filters(results, &query.filters, query.filter_combine)?;
+
+        // Apply sorting
+        results = self.sort_results(results, &query.kind);
+
+        Ok(results
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+            .collect())
+    }
+
+    /// Every result of `query`, whatever its limit and offset, without the cache
+    pub async fn search_all(&self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+        let everything = SearchQuery {
+            limit: Some(usize::MAX),
+            offset: None,
+            ..query.clone()
+        };
+        self.search_uncached(&everything).await
+    }
+
+    /// One page of results in a stable order, resuming after `cursor`
+    /// Pages are ordered by (name, file, line) rather than score, so declarations added
+    /// or removed between two calls never shift the following pages: nothing is skipped
+    /// or repeated. `query.limit` is the page size and `query.offset` is ignored.
+    /// Each page walks the index in name order from the cursor on, so only the declarations
+    /// around the page are loaded
+    pub async fn search_page(&self, query: &SearchQuery, cursor: Option<&str>) -> Result<SearchPage> {
+        let after = cursor.map(PageCursor::decode).transpose()?;
+        let page_size = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+
+        // One result past the page tells whether there is a next one
+        let mut results = self.index_manager.search_in_name_order(
+            query,
+            after.as_ref().map(|after| after.name.as_str()),
+            page_size + 1,
+            |result| {
+                after.as_ref().is_none_or(|after| PageCursor::of(result) > *after)
+                    && self.matches_filters(result, &query.filters, query.filter_combine)
+            },
+        )?;
+        let next_cursor = if results.len() > page_size {
+            results.truncate(page_size);
+            results.last().map(|last| PageCursor::of(last).encode()).transpose()?
+        } else {
+            None
+        };
+
+        Ok(SearchPage { results, next_cursor })
+    }
+
+    /// All declarations of a kind, or the first `limit` of them
     pub async fn search_by_kind(&self, kind: DeclarationKind, limit: Option<usize>) -> Result<Vec<SearchResult>> {
         let query = SearchQuery {
-            query: format!("{:?}", kind),
+            query: "*".to_string(),
             kind: crate::types::SearchKind::Exact,
             filters: vec![SearchFilter::Kind(kind)],
-            limit,
+            limit: None,
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
         };
         
-        self.search(&query).await
+        self.search_all_or_page(query, limit).await
     }
 
+    /// All declarations with an annotation, or the first `limit` of them
     pub async fn search_by_annotation(&self, annotation: &str, limit: Option<usize>) -> Result<Vec<SearchResult>> {
         let query = SearchQuery {
             query: annotation.to_string(),
             kind: crate::types::SearchKind::Exact,
             filters: vec![SearchFilter::Annotation(annotation.to_string())],
-            limit,
+            limit: None,
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
         };
         
+        self.search_all_or_page(query, limit).await
+    }
+
+    /// Number of results of `search` without a limit. Queries without filters, or with only a
+    /// kind filter, are counted in the index without loading documents.
+    pub async fn count(&self, query: &SearchQuery) -> Result<usize> {
+        match query.filters.as_slice() {
+            [] | [SearchFilter::Kind(_)] => self.index_manager.count(query),
+            _ => {
+                let mut everything = query.clone();
+                everything.limit = None;
+                everything.offset = None;
+                Ok(self.search_all_or_page(everything, None).await?.len())
+            }
+        }
+    }
+
+    /// Search with the given limit, or for every match when there is none (rather than
+    /// DEFAULT_SEARCH_LIMIT)
+    async fn search_all_or_page(&self, mut query: SearchQuery, limit: Option<usize>) -> Result<Vec<SearchResult>> {
+        query.limit = Some(limit.unwrap_or(usize::MAX));
         self.search(&query).await
     }
 
+    /// Declarations of a package and its subpackages
     pub async fn search_by_package(&self, package: &str, limit: Option<usize>) -> Result<Vec<SearchResult>> {
         let query = SearchQuery {
-            query: package.to_string(),
+            query: "*".to_string(),
             kind: crate::types::SearchKind::Exact,
             filters: vec![SearchFilter::Package(package.to_string())],
-            limit,
+            limit: None,
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
         };
         
-        self.search(&query).await
+        self.search_all_or_page(query, limit).await
     }
 
     pub async fn fuzzy_search(&self, query: &str, limit: Option<usize>) -> Result<Vec<SearchResult>> {
@@ -95,6 +201,8 @@ impl QueryEngine {
             kind: crate::types::SearchKind::Fuzzy,
             filters: vec![],
             limit,
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
         };
         
         self.search(&search_query).await
@@ -106,6 +214,8 @@ impl QueryEngine {
             kind: crate::types::SearchKind::Exact,
             filters: vec![],
             limit,
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
         };
         
         self.search(&search_query).await
@@ -117,45 +227,134 @@ impl QueryEngine {
             kind: crate::types::SearchKind::Regex,
             filters: vec![],
             limit,
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
         };
         
         self.search(&search_query).await
     }
 
-    fn apply_filters(&self, mut results: Vec<SearchResult>, filters: &[SearchFilter]) -> Result<Vec<SearchResult>> {
-        for filter in filters {
-            results = match filter {
-                SearchFilter::Kind(kind) => {
-                    results.into_iter()
-                        .filter(|r| r.declaration.kind == *kind)
-                        .collect()
-                }
-                SearchFilter::Annotation(annotation) => {
-                    results.into_iter()
-                        .filter(|r| {
-                            r.declaration.annotations.iter()
-                                .any(|a| a.name.contains(annotation))
-                        })
-                        .collect()
-                }
-                SearchFilter::Package(package) => {
-                    results.into_iter()
-                        .filter(|r| {
-                            r.file_path.to_string_lossy().contains(package)
-                        })
-                        .collect()
-                }
-                SearchFilter::Module(module) => {
-                    results.into_iter()
-                        .filter(|r| {
-                            r.file_path.to_string_lossy().contains(module)
-                        })
-                        .collect()
-                }
+    /// Find methods by name; the index must have been built with method documents
+    pub async fn search_methods(&self, name: &str, limit: Option<usize>) -> Result<Vec<MethodSearchResult>> {
+        self.index_manager.search_methods(name, limit).await
+    }
+
+    /// Find the @Entity classes mapped to a database table
+    pub async fn search_entity_by_table(&self, table: &str, limit: Option<usize>) -> Result<Vec<SearchResult>> {
+        self.index_manager.find_entities_by_table(table, limit).await
+    }
+
+    /// Find the Spring XML beans and properties values naming this class
+    pub async fn find_config_references(&self, class_fqn: &str) -> Result<Vec<SearchResult>> {
+        self.index_manager.find_config_references(class_fqn).await
+    }
+
+    /// Find declarations whose signature contains the fragment (case-insensitive)
+    pub async fn search_by_signature(&self, fragment: &str, limit: Option<usize>) -> Result<Vec<SearchResult>> {
+        let search_query = SearchQuery {
+            query: fragment.to_string(),
+            kind: crate::types::SearchKind::Substring,
+            filters: vec![],
+            limit,
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        
+        self.search(&search_query).await
+    }
+
+    /// Every indexed class declaration, paired with its fully qualified name
+    pub fn all_declarations(&self) -> Result<Vec<(String, Declaration)>> {
+        self.index_manager.all_declarations()
+    }
+
+    /// The `n` longest methods by line span as (owner fqn, method, lines), longest first
+    /// Methods of equal length are ordered by name
+    pub async fn largest_methods(&self, n: usize) -> Result<Vec<(String, Method, usize)>> {
+        let mut methods: Vec<(String, Method, usize)> = self
+            .index_manager
+            .all_declarations()?
+            .into_iter()
+            .flat_map(|(owner, declaration)| {
+                declaration.methods.into_iter().map(move |method| {
+                    let lines = method.range.end_line.saturating_sub(method.range.start_line) + 1;
+                    (owner.clone(), method, lines)
+                })
+            })
+            .collect();
+
+        methods.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.1.name.cmp(&b.1.name)));
+        methods.truncate(n);
+        Ok(methods)
+    }
+
+    fn apply_filters(
+        &self,
+        mut results: Vec<SearchResult>,
+        filters: &[SearchFilter],
+        combine: FilterCombine,
+    ) -> Result<Vec<SearchResult>> {
+        results.retain(|result| self.matches_filters(result, filters, combine));
+        Ok(results)
+    }
+
+    /// Whether a result passes the filters
+    fn matches_filters(&self, result: &SearchResult, filters: &[SearchFilter], combine: FilterCombine) -> bool {
+        let declaration = &result.declaration;
+
+        // Annotation filters are combined as a group, the others all apply
+        let annotations: Vec<&String> = filters
+            .iter()
+            .filter_map(|f| match f {
+                SearchFilter::Annotation(annotation) => Some(annotation),
+                _ => None,
+            })
+            .collect();
+        if !annotations.is_empty() {
+            let has_annotation = |annotation: &&String| {
+                declaration.annotations.iter().any(|a| a.name.contains(annotation.as_str()))
             };
+            let annotated = match combine {
+                FilterCombine::Any => annotations.iter().any(has_annotation),
+                FilterCombine::All => annotations.iter().all(has_annotation),
+            };
+            if !annotated {
+                return false;
+            }
         }
 
-        Ok(results)
+        filters.iter().all(|filter| match filter {
+            SearchFilter::Kind(kind) => declaration.kind == *kind,
+            SearchFilter::Annotation(_) => true,
+            SearchFilter::Package(package) => in_package(&declaration.package, package),
+            SearchFilter::Module(module) => result.file_path.to_string_lossy().contains(module),
+            SearchFilter::Modifier(modifier) => declaration.modifiers.contains(modifier),
+            SearchFilter::FieldType(type_name) => {
+                let wanted = Self::type_erasure(type_name);
+                declaration.fields.iter().any(|f| Self::type_erasure(&f.type_name) == wanted)
+            }
+            SearchFilter::Visibility(visibility) => declaration.visibility() == *visibility,
+            SearchFilter::Throws(exception) => declaration
+                .methods
+                .iter()
+                .flat_map(|m| &m.throws)
+                .any(|declared| Self::same_exception(declared, exception)),
+        })
+    }
+
+    /// Compare exception names, by simple name unless both are qualified
+    fn same_exception(declared: &str, wanted: &str) -> bool {
+        let (declared, wanted) = (Self::type_erasure(declared), Self::type_erasure(wanted));
+        if declared.contains('.') && wanted.contains('.') {
+            return declared == wanted;
+        }
+        let simple_name = |name: &'_ str| name.rsplit('.').next().unwrap_or(name).to_string();
+        simple_name(declared) == simple_name(wanted)
+    }
+
+    /// Drop generic arguments from a type name ("Map<K, V>" -> "Map")
+    fn type_erasure(type_name: &str) -> &str {
+        type_name.split('<').next().unwrap_or(type_name).trim()
     }
 
     fn sort_results(&self, mut results: Vec<SearchResult>, kind: &crate::types::SearchKind) -> Vec<SearchResult> {
@@ -165,7 +364,13 @@ impl QueryEngine {
                 results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
             }
             crate::types::SearchKind::Exact => {
-                // Sort by name for exact matches
+                // Sort by score so that field boosts decide the ranking, then by name
+                results.sort_by(|a, b| {
+                    b.score.total_cmp(&a.score).then_with(|| a.declaration.name.cmp(&b.declaration.name))
+                });
+            }
+            crate::types::SearchKind::Substring => {
+                // Sort by name for substring matches
                 results.sort_by(|a, b| a.declaration.name.cmp(&b.declaration.name));
             }
             crate::types::SearchKind::Regex => {
@@ -177,30 +382,88 @@ impl QueryEngine {
     }
 
     pub async fn get_statistics(&self) -> Result<QueryStatistics> {
-        let (total_docs, _) = self.index_manager.stats()?;
-        
-        // Simple approach: return counts based on total
-        let class_count = if total_docs > 0 { 2 } else { 0 }; // User and UserService
-        let interface_count = if total_docs > 0 { 1 } else { 0 }; // UserRepository
-        
+        let counts = self.index_manager.count_by_kind()?;
+        let count = |kind: DeclarationKind| counts.get(&kind).copied().unwrap_or(0);
+
         Ok(QueryStatistics {
-            total_declarations: total_docs,
-            class_count,
-            interface_count,
-            enum_count: 0,
-            record_count: 0,
-            annotation_count: 0,
+            total_declarations: counts.values().sum(),
+            class_count: count(DeclarationKind::Class),
+            interface_count: count(DeclarationKind::Interface),
+            enum_count: count(DeclarationKind::Enum),
+            record_count: count(DeclarationKind::Record),
+            annotation_count: count(DeclarationKind::Annotation),
         })
     }
 
     pub async fn clear_cache(&self) {
-        let mut cache = self.cache.write().await;
+        let mut cache = self.cache.lock().await;
         cache.clear();
     }
 
-    pub async fn get_cache_stats(&self) -> (usize, usize) {
-        let cache = self.cache.read().await;
-        (cache.len(), cache.values().map(|v| v.len()).sum())
+    /// Returns (cached queries, cached results, cache capacity)
+    pub async fn get_cache_stats(&self) -> (usize, usize, usize) {
+        let cache = self.cache.lock().await;
+        (
+            cache.len(),
+            cache.iter().map(|(_, v)| v.len()).sum(),
+            cache.cap().get(),
+        )
+    }
+}
+
+/// Whether `package` is `prefix` or one of its subpackages ("com.example.users" is in
+/// "com.example", "com.examples" is not)
+fn in_package(package: &str, prefix: &str) -> bool {
+    package
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Page size of search_page when the query has no limit
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// A page of search results and the cursor to fetch the next one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// None on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Sort key of the last result of a page, carried between calls as an opaque hex string
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct PageCursor {
+    name: String,
+    file_path: String,
+    start_line: usize,
+    start_column: usize,
+}
+
+impl PageCursor {
+    fn of(result: &SearchResult) -> Self {
+        Self {
+            name: result.declaration.name.clone(),
+            file_path: result.file_path.to_string_lossy().to_string(),
+            start_line: result.declaration.range.start_line,
+            start_column: result.declaration.range.start_column,
+        }
+    }
+
+    fn encode(&self) -> Result<String> {
+        Ok(serde_json::to_vec(self)?.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid page cursor: {}", cursor);
+        if !cursor.len().is_multiple_of(2) || !cursor.is_ascii() {
+            return Err(invalid());
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| invalid())?;
+        serde_json::from_slice(&bytes).map_err(|_| invalid())
     }
 }
 
@@ -217,6 +480,8 @@ pub struct QueryStatistics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::index_sources;
+    use crate::types::Visibility;
     use tempfile::tempdir;
 
     #[tokio::test]
@@ -231,6 +496,567 @@ mod tests {
         assert_eq!(results.len(), 0);
     }
 
+    fn sample_structure(class_name: &str, kind: crate::parser::ClassKind) -> crate::parser::JavaStructurePreview {
+        crate::parser::JavaStructurePreview {
+            file_meta: crate::parser::FileMeta {
+                path: std::path::PathBuf::from(format!("/test/{}.java", class_name)),
+                name: format!("{}.java", class_name),
+                suffix: crate::parser::FileSuffix::Java,
+                hash_value: format!("hash-{}", class_name),
+            },
+            package: Some("com.example".to_string()),
+            imports: vec![],
+            static_imports: vec![],
+            top_level_classes: vec![
+                crate::parser::ClassStructure {
+                    name: class_name.to_string(),
+                    fqn: format!("com.example.{}", class_name),
+                    kind,
+                    modifiers: vec!["public".to_string()],
+                    annotations: vec![],
+                    extends: None,
+                    implements: vec![],
+                    type_parameters: vec![],
+                    fields: vec![],
+                    methods: vec![],
+                    nested_classes: vec![],
+                    range: crate::parser::SourceRange {
+                        start_line: 1,
+                        start_column: 1,
+                        end_line: 10,
+                        end_column: 1,
+                    },
+                    loc: 0,
+                    sloc: 0,
+                    documentation: Some("Handles user data".to_string()),
+                    raw_documentation: None,
+                },
+            ],
+            file_annotations: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_key_includes_filters() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+        index_manager.index_java_file(&sample_structure("UserService", crate::parser::ClassKind::Class)).await.unwrap();
+        index_manager.index_java_file(&sample_structure("UserRepository", crate::parser::ClassKind::Interface)).await.unwrap();
+        let mut orders = sample_structure("OrderRepository", crate::parser::ClassKind::Interface);
+        orders.top_level_classes[0].documentation = Some("Stores orders".to_string());
+        index_manager.index_java_file(&orders).await.unwrap();
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+
+        let mut query = SearchQuery {
+            query: "user".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let unfiltered = query_engine.search(&query).await.unwrap();
+        assert_eq!(unfiltered.len(), 2);
+
+        query.filters = vec![SearchFilter::Kind(DeclarationKind::Interface)];
+        let filtered = query_engine.search(&query).await.unwrap();
+        // OrderRepository is an interface too, but doesn't match the text
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].declaration.name, "UserRepository");
+        assert_eq!(query_engine.count(&query).await.unwrap(), 1);
+
+        let (cache_entries, _, _) = query_engine.get_cache_stats().await;
+        assert_eq!(cache_entries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_cache_eviction() {
+        let dir = tempdir().unwrap();
+        let index_path = dir.path().join("test_index");
+
+        let query_engine = QueryEngine::with_cache_capacity(&index_path, 2).unwrap();
+
+        for query in ["first", "second", "third"] {
+            query_engine.exact_search(query, Some(10)).await.unwrap();
+        }
+
+        let (cache_entries, _, capacity) = query_engine.get_cache_stats().await;
+        assert_eq!(capacity, 2);
+        assert_eq!(cache_entries, 2);
+
+        assert!(QueryEngine::with_cache_capacity(&dir.path().join("other_index"), 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_pagination() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+        // Indexed in reverse, so that the index order is not the name order; even numbers are interfaces
+        for i in (1..=10).rev() {
+            let kind = if i % 2 == 0 { crate::parser::ClassKind::Interface } else { crate::parser::ClassKind::Class };
+            index_manager.index_java_file(&sample_structure(&format!("Service{:02}", i), kind)).await.unwrap();
+        }
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+        let names = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.declaration.name).collect()
+        };
+
+        let mut query = SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(3),
+            offset: Some(3),
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = query_engine.search(&query).await.unwrap();
+        assert_eq!(names(results), vec!["Service04", "Service05", "Service06"]);
+
+        // A kind filter that is not first only applies once the documents are loaded,
+        // yet every page is full and the pages follow each other
+        query.filters = vec![SearchFilter::Modifier("public".to_string()), SearchFilter::Kind(DeclarationKind::Interface)];
+        query.limit = Some(2);
+        let mut pages = Vec::new();
+        for offset in [0, 2, 4] {
+            query.offset = Some(offset);
+            pages.push(names(query_engine.search(&query).await.unwrap()));
+        }
+        assert_eq!(pages, vec![
+            vec!["Service02", "Service04"],
+            vec!["Service06", "Service08"],
+            vec!["Service10"],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_modifier_filter() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let mut abstract_class = sample_structure("AbstractHandler", crate::parser::ClassKind::Class);
+        abstract_class.top_level_classes[0].modifiers = vec!["public".to_string(), "abstract".to_string()];
+        let mut final_class = sample_structure("FinalHandler", crate::parser::ClassKind::Class);
+        final_class.top_level_classes[0].modifiers = vec!["public".to_string(), "final".to_string()];
+        index_manager.index_java_files(&[abstract_class, final_class]).await.unwrap();
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+
+        let query = SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![SearchFilter::Modifier("abstract".to_string())],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = query_engine.search(&query).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "AbstractHandler");
+    }
+
+    #[tokio::test]
+    async fn test_largest_methods() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let method = |name: &str, start_line: usize, end_line: usize| crate::parser::MethodStructure {
+            name: name.to_string(),
+            return_type: "void".to_string(),
+            parameters: vec![],
+            modifiers: vec!["public".to_string()],
+            annotations: vec![],
+            type_parameters: vec![],
+            throws: vec![],
+            range: crate::parser::SourceRange { start_line, start_column: 5, end_line, end_column: 5 },
+            loc: 0,
+            sloc: 0,
+            body_range: None,
+            complexity: None,
+            calls: vec![],
+            documentation: None,
+            raw_documentation: None,
+        };
+        let mut orders = sample_structure("OrderService", crate::parser::ClassKind::Class);
+        orders.top_level_classes[0].methods = vec![method("place", 2, 4), method("reconcile", 5, 60)];
+        let mut users = sample_structure("UserService", crate::parser::ClassKind::Class);
+        users.top_level_classes[0].methods = vec![method("find", 2, 4), method("register", 5, 30)];
+        index_manager.index_java_files(&[orders, users]).await.unwrap();
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+        let largest = query_engine.largest_methods(3).await.unwrap();
+
+        let ranked: Vec<(&str, &str, usize)> = largest
+            .iter()
+            .map(|(owner, method, lines)| (owner.as_str(), method.name.as_str(), *lines))
+            .collect();
+        assert_eq!(ranked, vec![
+            ("com.example.OrderService", "reconcile", 56),
+            ("com.example.UserService", "register", 26),
+            ("com.example.UserService", "find", 3),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_statistics_counts_by_kind() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+        index_manager.index_java_files(&[
+            sample_structure("User", crate::parser::ClassKind::Class),
+            sample_structure("UserService", crate::parser::ClassKind::Class),
+            sample_structure("UserRepository", crate::parser::ClassKind::Interface),
+            sample_structure("UserStatus", crate::parser::ClassKind::Enum),
+        ]).await.unwrap();
+        // Re-indexing a file replaces its documents, which must not be counted twice
+        index_manager.index_java_file(&sample_structure("User", crate::parser::ClassKind::Class)).await.unwrap();
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+        let stats = query_engine.get_statistics().await.unwrap();
+
+        assert_eq!(stats.total_declarations, 4);
+        assert_eq!(stats.class_count, 2);
+        assert_eq!(stats.interface_count, 1);
+        assert_eq!(stats.enum_count, 1);
+        assert_eq!(stats.record_count, 0);
+        assert_eq!(stats.annotation_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_field_type_filter() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let field = |name: &str, type_name: &str| crate::parser::FieldStructure {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            modifiers: vec!["private".to_string()],
+            annotations: vec![],
+            initializer: None,
+            documentation: None,
+            raw_documentation: None,
+        };
+        let mut service = sample_structure("UserService", crate::parser::ClassKind::Class);
+        service.top_level_classes[0].fields = vec![
+            field("userRepository", "UserRepository"),
+            field("cache", "Map<Long, User>"),
+        ];
+        let mut controller = sample_structure("UserController", crate::parser::ClassKind::Class);
+        controller.top_level_classes[0].fields = vec![field("userService", "UserService")];
+        index_manager.index_java_files(&[service, controller]).await.unwrap();
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+        let search = |type_name: &str| SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![SearchFilter::FieldType(type_name.to_string())],
+            limit: Some(10),
+            offset: None,
+            filter_combine: FilterCombine::Any,
+        };
+
+        let results = query_engine.search(&search("UserRepository")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "UserService");
+
+        let results = query_engine.search(&search("Map<String, Object>")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "UserService");
+    }
+
+    #[tokio::test]
+    async fn test_visibility() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+
+        let mut helper = sample_structure("OrderHelper", crate::parser::ClassKind::Class);
+        helper.top_level_classes[0].modifiers = vec!["final".to_string()];
+        helper.top_level_classes[0].fields = vec![crate::parser::FieldStructure {
+            name: "secret".to_string(),
+            type_name: "String".to_string(),
+            modifiers: vec!["private".to_string()],
+            annotations: vec![],
+            initializer: None,
+            documentation: None,
+            raw_documentation: None,
+        }];
+        helper.top_level_classes[0].methods = vec![crate::parser::MethodStructure {
+            name: "recalculate".to_string(),
+            return_type: "void".to_string(),
+            parameters: vec![],
+            modifiers: vec!["static".to_string()],
+            annotations: vec![],
+            type_parameters: vec![],
+            throws: vec![],
+            range: crate::parser::SourceRange { start_line: 3, start_column: 5, end_line: 5, end_column: 5 },
+            loc: 0,
+            sloc: 0,
+            body_range: None,
+            complexity: None,
+            calls: vec![],
+            documentation: None,
+            raw_documentation: None,
+        }];
+        let service = sample_structure("OrderService", crate::parser::ClassKind::Class);
+        index_manager.index_java_files(&[helper, service]).await.unwrap();
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+        let query = SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![SearchFilter::Visibility(Visibility::PackagePrivate)],
+            limit: Some(10),
+            offset: None,
+            filter_combine: FilterCombine::Any,
+        };
+        let results = query_engine.search(&query).await.unwrap();
+        assert_eq!(results.len(), 1);
+
+        let helper = &results[0].declaration;
+        assert_eq!(helper.name, "OrderHelper");
+        assert_eq!(helper.methods[0].visibility(helper.kind), Visibility::PackagePrivate);
+        assert_eq!(helper.fields[0].visibility(helper.kind), Visibility::Private);
+    }
+
+    #[tokio::test]
+    async fn test_throws_filter() {
+        let dir = tempdir().unwrap();
+        let sources = [
+            ("FileLoader.java", "import java.io.IOException;\npublic class FileLoader {\n    public byte[] load(String path) throws IOException { return null; }\n}\n"),
+            ("SqlLoader.java", "public class SqlLoader {\n    public void load() throws java.sql.SQLException {}\n}\n"),
+            ("SafeLoader.java", "public class SafeLoader {\n    public void load() {}\n}\n"),
+        ];
+        let index_manager = index_sources(dir.path(), &sources).await;
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+        let throwing = |exception: &str| SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![SearchFilter::Throws(exception.to_string())],
+            limit: Some(10),
+            offset: None,
+            filter_combine: FilterCombine::Any,
+        };
+
+        for exception in ["IOException", "java.io.IOException"] {
+            let results = query_engine.search(&throwing(exception)).await.unwrap();
+            assert_eq!(results.len(), 1, "{}", exception);
+            assert_eq!(results[0].declaration.name, "FileLoader");
+            assert_eq!(results[0].declaration.methods[0].throws, vec!["IOException"]);
+        }
+
+        let results = query_engine.search(&throwing("SQLException")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "SqlLoader");
+        assert!(query_engine.search(&throwing("java.io.SQLException")).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_helpers_return_everything() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+        let structures: Vec<_> = (0..120)
+            .map(|i| sample_structure(&format!("Service{}", i), crate::parser::ClassKind::Class))
+            .collect();
+        index_manager.index_java_files(&structures).await.unwrap();
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+
+        // No limit means every match, not the index's default page of 100
+        assert_eq!(query_engine.search_by_kind(DeclarationKind::Class, None).await.unwrap().len(), 120);
+        assert_eq!(query_engine.search_by_kind(DeclarationKind::Class, Some(10)).await.unwrap().len(), 10);
+        assert!(query_engine.search_by_kind(DeclarationKind::Enum, None).await.unwrap().is_empty());
+
+        let classes = SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![SearchFilter::Kind(DeclarationKind::Class)],
+            limit: Some(5),
+            offset: None,
+            filter_combine: FilterCombine::Any,
+        };
+        assert_eq!(query_engine.count(&classes).await.unwrap(), 120);
+        assert_eq!(query_engine.get_statistics().await.unwrap().class_count, 120);
+    }
+
+    #[tokio::test]
+    async fn test_annotation_filter_on_indexed_results() {
+        let dir = tempdir().unwrap();
+        let index_manager = index_sources(
+            dir.path(),
+            &[
+                ("UserService.java", "@Service\npublic class UserService {}\n"),
+                ("UserHelper.java", "public class UserHelper {}\n"),
+            ],
+        )
+        .await;
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+
+        // Annotations are read back from the index, so the filter sees them
+        let query = SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![SearchFilter::Annotation("Service".to_string())],
+            limit: Some(10),
+            offset: None,
+            filter_combine: FilterCombine::Any,
+        };
+        let names: Vec<String> = query_engine
+            .search(&query)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.declaration.name)
+            .collect();
+        assert_eq!(names, vec!["UserService"]);
+    }
+
+    #[tokio::test]
+    async fn test_package_filter_uses_declared_package() {
+        let dir = tempdir().unwrap();
+        // Directory layouts that do not match the declared packages
+        let index_manager = index_sources(
+            dir.path(),
+            &[
+                ("legacy/UserService.java", "package com.example.users;\n\npublic class UserService {}\n"),
+                ("com/example/Importer.java", "package org.tools;\n\npublic class Importer {}\n"),
+                ("misc/Examples.java", "package com.examples;\n\npublic class Examples {}\n"),
+            ],
+        )
+        .await;
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+
+        let names = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.declaration.name).collect()
+        };
+        let results = query_engine.search_by_package("com.example", None).await.unwrap();
+        assert_eq!(results[0].declaration.package, "com.example.users");
+        assert_eq!(names(results), vec!["UserService"]);
+        assert_eq!(names(query_engine.search_by_package("org.tools", None).await.unwrap()), vec!["Importer"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_page_cursors() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+        let names: Vec<String> = (0..7).map(|i| format!("Service{}", i)).collect();
+        let structures: Vec<_> = names
+            .iter()
+            .map(|name| sample_structure(name, crate::parser::ClassKind::Class))
+            .collect();
+        index_manager.index_java_files(&structures).await.unwrap();
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+
+        let query = SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(3),
+            offset: None,
+            filter_combine: FilterCombine::Any,
+        };
+
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        let mut pages = 0;
+        loop {
+            let page = query_engine.search_page(&query, cursor.as_deref()).await.unwrap();
+            pages += 1;
+            seen.extend(page.results.iter().map(|r| r.declaration.name.clone()));
+
+            // A declaration indexed mid-paging sorts before the cursor and does not shift the next page
+            if pages == 1 {
+                query_engine
+                    .index_manager
+                    .index_java_file(&sample_structure("AaaService", crate::parser::ClassKind::Class))
+                    .await
+                    .unwrap();
+            }
+
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 3);
+        assert_eq!(seen, names);
+        assert!(query_engine.search_page(&query, Some("not a cursor")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_page_filters_across_segments() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+        // One commit per file, in reverse name order, so names are spread over several segments
+        for i in (0..9).rev() {
+            let kind = if i % 3 == 0 { crate::parser::ClassKind::Class } else { crate::parser::ClassKind::Interface };
+            index_manager.index_java_file(&sample_structure(&format!("Port{}", i), kind)).await.unwrap();
+        }
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+
+        // The kind filter is not first, so it only applies after the documents are loaded
+        let query = SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![SearchFilter::Modifier("public".to_string()), SearchFilter::Kind(DeclarationKind::Interface)],
+            limit: Some(4),
+            offset: None,
+            filter_combine: FilterCombine::Any,
+        };
+        let names = |page: &SearchPage| -> Vec<String> {
+            page.results.iter().map(|r| r.declaration.name.clone()).collect()
+        };
+
+        let first = query_engine.search_page(&query, None).await.unwrap();
+        assert_eq!(names(&first), vec!["Port1", "Port2", "Port4", "Port5"]);
+        let second = query_engine.search_page(&query, first.next_cursor.as_deref()).await.unwrap();
+        assert_eq!(names(&second), vec!["Port7", "Port8"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_find_config_references() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("test_index")).unwrap();
+        index_manager.index_java_file(&sample_structure("UserService", crate::parser::ClassKind::Class)).await.unwrap();
+
+        let xml_path = dir.path().join("beans.xml");
+        let xml_content = r#"<beans>
+    <bean id="userService" class="com.example.UserService"/>
+    <bean id="orderService" class="com.example.OrderService"/>
+</beans>
+"#;
+        std::fs::write(&xml_path, xml_content).unwrap();
+        let properties_path = dir.path().join("application.properties");
+        std::fs::write(&properties_path, "# handlers\napp.handler=com.example.UserService\napp.name=Demo\n").unwrap();
+
+        let file_parser = crate::parser::FileParser::new().unwrap();
+        index_manager.index_xml_file(&file_parser.parse_xml_file(&xml_path).unwrap()).await.unwrap();
+        index_manager
+            .index_properties_file(&file_parser.parse_properties_file(&properties_path).unwrap())
+            .await
+            .unwrap();
+
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+        let references = query_engine.find_config_references("com.example.UserService").await.unwrap();
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[0].file_path, properties_path);
+        assert_eq!(references[0].declaration.range.start_line, 2);
+        assert_eq!(references[1].file_path, xml_path);
+        assert_eq!(references[1].declaration.signature, r#"<bean id="userService" class="com.example.UserService"/>"#);
+
+        // Config documents stay out of declaration searches
+        let all = query_engine.search(&SearchQuery {
+            query: "*".to_string(),
+            kind: crate::types::SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: FilterCombine::Any,
+        }).await.unwrap();
+        assert_eq!(all.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_search_with_filters() {
         let dir = tempdir().unwrap();
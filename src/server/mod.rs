@@ -0,0 +1,104 @@
+//! HTTP server mode: search, statistics and export over a small JSON REST API
+
+use anyhow::Result;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::llm::{LlmExporter, LlmRequest, LlmResponse};
+use crate::query::QueryStatistics;
+use crate::types::{FilterCombine, SearchKind, SearchQuery, SearchResult};
+
+/// Query string of `GET /search`
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    /// exact, fuzzy, regex or substring (default: exact)
+    pub kind: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// An error answered as `{"error": "..."}`
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: String) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(serde_json::json!({ "error": self.message }))).into_response()
+    }
+}
+
+/// Routes of the API, sharing one exporter (and through it one QueryEngine and its cache)
+pub fn router(exporter: Arc<LlmExporter>) -> Router {
+    Router::new()
+        .route("/search", get(search))
+        .route("/stats", get(stats))
+        .route("/export", post(export))
+        .with_state(exporter)
+}
+
+/// Serve the API on an already bound listener until the process stops
+pub async fn serve(listener: TcpListener, exporter: LlmExporter) -> Result<()> {
+    axum::serve(listener, router(Arc::new(exporter))).await?;
+    Ok(())
+}
+
+async fn search(
+    State(exporter): State<Arc<LlmExporter>>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<Vec<SearchResult>>, ApiError> {
+    let kind = match params.kind.as_deref().unwrap_or("exact") {
+        "exact" => SearchKind::Exact,
+        "fuzzy" => SearchKind::Fuzzy,
+        "regex" => SearchKind::Regex,
+        "substring" => SearchKind::Substring,
+        other => return Err(ApiError::bad_request(format!("Unknown search kind: {}", other))),
+    };
+
+    let query = SearchQuery {
+        query: params.q,
+        kind,
+        filters: vec![],
+        limit: params.limit,
+        offset: params.offset,
+        filter_combine: FilterCombine::Any,
+    };
+    Ok(Json(exporter.query_engine().search(&query).await?))
+}
+
+async fn stats(State(exporter): State<Arc<LlmExporter>>) -> Result<Json<QueryStatistics>, ApiError> {
+    Ok(Json(exporter.query_engine().get_statistics().await?))
+}
+
+async fn export(
+    State(exporter): State<Arc<LlmExporter>>,
+    Json(request): Json<LlmRequest>,
+) -> Result<Json<LlmResponse>, ApiError> {
+    Ok(Json(exporter.export(request).await?))
+}
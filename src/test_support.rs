@@ -0,0 +1,27 @@
+//! Fixtures shared by the unit tests of several modules
+
+use crate::indexer::IndexManager;
+use crate::parser::{JavaStructureParser, JavaStructurePreview};
+use std::path::Path;
+
+/// Write each (path relative to `root`, source) pair to disk and parse it
+pub(crate) fn parse_sources(root: &Path, sources: &[(&str, &str)]) -> Vec<JavaStructurePreview> {
+    let parser = JavaStructureParser::new().unwrap();
+    sources
+        .iter()
+        .map(|(path, source)| {
+            let java_path = root.join(path);
+            std::fs::create_dir_all(java_path.parent().unwrap()).unwrap();
+            std::fs::write(&java_path, source).unwrap();
+            parser.parse_structure(&java_path).unwrap()
+        })
+        .collect()
+}
+
+/// Parse the sources as parse_sources does and index them, in one commit, in a new
+/// index under `root`
+pub(crate) async fn index_sources(root: &Path, sources: &[(&str, &str)]) -> IndexManager {
+    let index_manager = IndexManager::new(&root.join("test_index")).unwrap();
+    index_manager.index_java_files(&parse_sources(root, sources)).await.unwrap();
+    index_manager
+}
@@ -0,0 +1,278 @@
+//! Interactive terminal UI: a search box, the matching declarations and the details of the selected one
+
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::query::QueryEngine;
+use crate::types::{SearchKind, SearchQuery, SearchResult};
+
+/// Maximum number of results fetched for one query
+const RESULT_LIMIT: usize = 100;
+
+/// How long to wait for a key before redrawing
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// What the event loop should do after a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppAction {
+    None,
+    /// The query changed, results must be fetched again
+    Search,
+    Quit,
+}
+
+/// State of the TUI, independent of the terminal so it can be driven by tests
+pub struct App {
+    pub query: String,
+    pub results: Vec<SearchResult>,
+    pub list_state: ListState,
+    project_root: PathBuf,
+}
+
+impl App {
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            query: String::new(),
+            results: Vec::new(),
+            list_state: ListState::default(),
+            project_root: project_root.to_path_buf(),
+        }
+    }
+
+    /// Typing edits the query, arrows move the selection; Esc, Ctrl+C, or `q` on an
+    /// empty search box quit
+    pub fn handle_key(&mut self, key: KeyEvent) -> AppAction {
+        if key.kind == KeyEventKind::Release {
+            return AppAction::None;
+        }
+
+        match key.code {
+            KeyCode::Esc => AppAction::Quit,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => AppAction::Quit,
+            KeyCode::Char('q') if self.query.is_empty() => AppAction::Quit,
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                AppAction::Search
+            }
+            KeyCode::Backspace => {
+                if self.query.pop().is_some() {
+                    AppAction::Search
+                } else {
+                    AppAction::None
+                }
+            }
+            KeyCode::Down => {
+                self.move_selection(1);
+                AppAction::None
+            }
+            KeyCode::Up => {
+                self.move_selection(-1);
+                AppAction::None
+            }
+            KeyCode::PageDown => {
+                self.move_selection(10);
+                AppAction::None
+            }
+            KeyCode::PageUp => {
+                self.move_selection(-10);
+                AppAction::None
+            }
+            _ => AppAction::None,
+        }
+    }
+
+    /// Run the current query; an empty query lists everything
+    pub async fn refresh(&mut self, query_engine: &QueryEngine) -> Result<()> {
+        let (query, kind) = if self.query.trim().is_empty() {
+            ("*".to_string(), SearchKind::Exact)
+        } else {
+            (self.query.trim().to_string(), SearchKind::Substring)
+        };
+
+        self.results = query_engine
+            .search(&SearchQuery {
+                query,
+                kind,
+                filters: vec![],
+                limit: Some(RESULT_LIMIT),
+                offset: None,
+                filter_combine: crate::types::FilterCombine::Any,
+            })
+            .await?;
+        self.list_state
+            .select(if self.results.is_empty() { None } else { Some(0) });
+        Ok(())
+    }
+
+    pub fn selected(&self) -> Option<&SearchResult> {
+        self.list_state.selected().and_then(|i| self.results.get(i))
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.results.is_empty() {
+            return;
+        }
+        let last = self.results.len() - 1;
+        let current = self.list_state.selected().unwrap_or(0);
+        let next = current.saturating_add_signed(delta).min(last);
+        self.list_state.select(Some(next));
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(frame.size());
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(rows[1]);
+
+        let search = Paragraph::new(self.query.as_str())
+            .block(Block::default().borders(Borders::ALL).title("Search (Esc to quit)"));
+        frame.render_widget(search, rows[0]);
+
+        let items: Vec<ListItem> = self
+            .results
+            .iter()
+            .map(|r| ListItem::new(format!("{:?} {}", r.declaration.kind, r.declaration.name)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!("Results ({})", self.results.len())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, columns[0], &mut self.list_state);
+
+        let details = Paragraph::new(self.detail_lines())
+            .block(Block::default().borders(Borders::ALL).title("Details"))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, columns[1]);
+    }
+
+    fn detail_lines(&self) -> Vec<Line<'static>> {
+        let Some(result) = self.selected() else {
+            return vec![Line::from("No declaration selected")];
+        };
+
+        let declaration = &result.declaration;
+        let location = result
+            .file_path
+            .strip_prefix(&self.project_root)
+            .unwrap_or(&result.file_path)
+            .display();
+
+        let mut lines = vec![
+            Line::styled(declaration.signature.clone(), Style::default().add_modifier(Modifier::BOLD)),
+            Line::from(format!("{}:{}", location, declaration.range.start_line)),
+            Line::from(""),
+        ];
+        match &declaration.documentation {
+            Some(documentation) => lines.extend(documentation.lines().map(|l| Line::from(l.to_string()))),
+            None => lines.push(Line::from("(no documentation)")),
+        }
+        lines
+    }
+}
+
+/// Restores the terminal even when the event loop fails
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+/// Run the TUI until the user quits
+pub async fn run(query_engine: QueryEngine, project_root: &Path) -> Result<()> {
+    enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+
+    let mut app = App::new(project_root);
+    app.refresh(&query_engine).await?;
+    event_loop(&mut terminal, &mut app, &query_engine).await
+}
+
+async fn event_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, query_engine: &QueryEngine) -> Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if !event::poll(POLL_INTERVAL)? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) => match app.handle_key(key) {
+                AppAction::Quit => return Ok(()),
+                AppAction::Search => app.refresh(query_engine).await?,
+                AppAction::None => {}
+            },
+            // The next draw picks up the new size
+            Event::Resize(_, _) => terminal.autoresize()?,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::IndexManager;
+    use crate::parser::JavaStructureParser;
+    use ratatui::backend::TestBackend;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_app_live_filter_and_navigation() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let parser = JavaStructureParser::new().unwrap();
+        let mut structures = Vec::new();
+        for name in ["UserService", "UserRepository", "OrderService"] {
+            let java_path = dir.path().join(format!("{}.java", name));
+            std::fs::write(&java_path, format!("/** The {} */\npublic class {} {{}}\n", name, name)).unwrap();
+            structures.push(parser.parse_structure(&java_path).unwrap());
+        }
+        index_manager.index_java_files(&structures).await.unwrap();
+        let query_engine = QueryEngine::new_with_manager(index_manager).unwrap();
+
+        let mut app = App::new(dir.path());
+        app.refresh(&query_engine).await.unwrap();
+        assert_eq!(app.results.len(), 3);
+
+        for c in "user".chars() {
+            let action = app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+            assert_eq!(action, AppAction::Search);
+        }
+        app.refresh(&query_engine).await.unwrap();
+        assert_eq!(app.results.len(), 2);
+        assert!(app.results.iter().all(|r| r.declaration.name.starts_with("User")));
+
+        app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(app.list_state.selected(), Some(1));
+        let selected = app.selected().unwrap().declaration.name.clone();
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains(&format!("{}.java:2", selected)), "{}", screen);
+        assert!(screen.contains("Results (2)"));
+
+        // `q` is part of the query while the search box has text
+        assert_eq!(app.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)), AppAction::Search);
+        assert_eq!(app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), AppAction::Quit);
+    }
+}
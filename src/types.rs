@@ -1,4 +1,6 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 
@@ -28,11 +30,14 @@ pub struct Declaration {
     pub range: SourceRange,
     /// JavaDoc comments above this declaration
     pub documentation: Option<String>,
+    /// Package declared by the source file, like "com.example" ("" for the default package)
+    #[serde(default)]
+    pub package: String,
 }
 
 /// Different types of Java declarations you can find
 /// Think: "Is this a class? An interface? An enum?"
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 pub enum DeclarationKind {
     /// A regular class like "public class UserService"
     Class,
@@ -44,6 +49,77 @@ pub enum DeclarationKind {
     Record,
     /// An annotation like "@interface MyAnnotation"
     Annotation,
+    /// A method, only used for the nodes of the call graph
+    Method,
+}
+
+/// Access level of a declaration, normalized from its modifiers
+/// No access modifier at all means package-private, except for members of interfaces
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    Public,
+    Protected,
+    PackagePrivate,
+    Private,
+}
+
+impl Visibility {
+    /// Classify raw modifiers; `enclosing` is the kind of the declaration a member belongs to
+    /// (None for top-level types), since members of interfaces and annotations are
+    /// implicitly public
+    pub fn from_modifiers(modifiers: &[String], enclosing: Option<DeclarationKind>) -> Self {
+        let has = |modifier: &str| modifiers.iter().any(|m| m == modifier);
+        if has("public") {
+            Visibility::Public
+        } else if has("protected") {
+            Visibility::Protected
+        } else if has("private") {
+            Visibility::Private
+        } else if matches!(enclosing, Some(DeclarationKind::Interface | DeclarationKind::Annotation)) {
+            Visibility::Public
+        } else {
+            Visibility::PackagePrivate
+        }
+    }
+
+    /// Term stored in the index ("public", "protected", "package-private", "private")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Protected => "protected",
+            Visibility::PackagePrivate => "package-private",
+            Visibility::Private => "private",
+        }
+    }
+}
+
+impl Declaration {
+    pub fn visibility(&self) -> Visibility {
+        Visibility::from_modifiers(&self.modifiers, None)
+    }
+
+    /// "com.example.UserService", or the bare name in the default package
+    pub fn fqn(&self) -> String {
+        if self.package.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.package, self.name)
+        }
+    }
+}
+
+impl Field {
+    /// Visibility of the field in a declaration of kind `owner`
+    pub fn visibility(&self, owner: DeclarationKind) -> Visibility {
+        Visibility::from_modifiers(&self.modifiers, Some(owner))
+    }
+}
+
+impl Method {
+    /// Visibility of the method in a declaration of kind `owner`
+    pub fn visibility(&self, owner: DeclarationKind) -> Visibility {
+        Visibility::from_modifiers(&self.modifiers, Some(owner))
+    }
 }
 
 /// A field (variable) inside a Java class
@@ -74,10 +150,16 @@ pub struct Method {
     pub modifiers: Vec<String>,
     /// Annotations like @GetMapping, @Transactional
     pub annotations: Vec<Annotation>,
+    /// Declared exceptions as written (e.g., "IOException", "java.sql.SQLException")
+    #[serde(default)]
+    pub throws: Vec<String>,
     /// Where the method signature appears in file
     pub range: SourceRange,
     /// Where the method body starts and ends
     pub body_range: Option<SourceRange>,
+    /// McCabe cyclomatic complexity, when the method has a body
+    #[serde(default)]
+    pub complexity: Option<usize>,
 }
 
 /// A parameter in a method
@@ -148,6 +230,22 @@ pub struct SearchQuery {
     pub filters: Vec<SearchFilter>,
     /// Maximum number of results to return
     pub limit: Option<usize>,
+    /// Number of top results to skip, for paging through results
+    #[serde(default)]
+    pub offset: Option<usize>,
+    /// How several annotation filters combine (other filters must all match)
+    #[serde(default)]
+    pub filter_combine: FilterCombine,
+}
+
+/// How multiple annotation filters are combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum FilterCombine {
+    /// Match declarations with at least one of the annotations
+    #[default]
+    Any,
+    /// Match only declarations with every annotation
+    All,
 }
 
 /// Different ways to search for code
@@ -159,6 +257,8 @@ pub enum SearchKind {
     Fuzzy,
     /// Regular expression match
     Regex,
+    /// Substring of the signature ("findBy" matches "findByEmail(String email)")
+    Substring,
 }
 
 /// Ways to filter search results
@@ -172,6 +272,16 @@ pub enum SearchFilter {
     Package(String),
     /// Only in specific module
     Module(String),
+    /// Only declarations with a specific modifier (e.g., "abstract")
+    Modifier(String),
+    /// Only declarations having a field of this type, compared on erasure
+    /// ("List" and "List<String>" both match a "List<User>" field)
+    FieldType(String),
+    /// Only declarations with this access level
+    Visibility(Visibility),
+    /// Only declarations with a method declaring this exception; a simple name
+    /// ("IOException") matches any package, a qualified one its own package only
+    Throws(String),
 }
 
 /// Search result from the index
@@ -188,9 +298,27 @@ pub struct SearchResult {
     pub preview: String,
 }
 
+/// A method found by method search
+/// "findByEmail is declared in UserRepository, here's where"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodSearchResult {
+    /// Method name (e.g., "findByEmail")
+    pub name: String,
+    /// Fully qualified name of the declaring class
+    pub owner: String,
+    /// Method signature (e.g., "public User findByEmail(String email)")
+    pub signature: String,
+    /// Which file it was found in
+    pub file_path: PathBuf,
+    /// Where the method appears in the file
+    pub range: SourceRange,
+    /// How well it matches the search
+    pub score: f32,
+}
+
 /// Data exported for AI/LLM systems
 /// Clean, structured format for AI tools to consume
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LlmExport {
     /// Name of the class/method/etc.
     pub name: String,
@@ -206,6 +334,38 @@ pub struct LlmExport {
     pub file_path: String,
     /// Line numbers (start, end)
     pub line_range: (usize, usize),
+    /// Stereotype flags like "is_service" derived from annotations
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub facets: BTreeMap<String, bool>,
+    /// `code` split on method boundaries, when a chunk token limit was requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<CodeChunk>,
+    /// Supertype chain, nearest first, when requested with LlmRequest::include_ancestors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ancestors: Vec<String>,
+    /// Size metrics, when requested with LlmRequest::include_metrics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<ExportMetrics>,
+}
+
+/// Size and complexity "heat" of an exported declaration, e.g. for a treemap
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExportMetrics {
+    /// Lines spanned by the declaration
+    pub loc: usize,
+    /// Methods declared directly in it (0 for a method)
+    pub method_count: usize,
+    /// Sum of the cyclomatic complexity of its methods, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cyclomatic_sum: Option<usize>,
+}
+
+/// A piece of a declaration's source code
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CodeChunk {
+    pub content: String,
+    /// Line numbers (start, end) in the file
+    pub line_range: (usize, usize),
 }
 
 /// Relationship graph between classes
@@ -229,6 +389,9 @@ pub struct GraphNode {
     pub kind: DeclarationKind,
     /// Where this file is located
     pub file_path: PathBuf,
+    /// A type referenced by the project but not declared in it (e.g., a library class)
+    #[serde(default)]
+    pub external: bool,
 }
 
 /// A relationship between two classes
@@ -256,4 +419,56 @@ pub enum RelationshipType {
     References,
     /// Dependency relationship
     DependsOn,
-}
\ No newline at end of file
+    /// Sealed type permitting a subtype ("permits")
+    Permits,
+    /// Enclosing type containing a nested type
+    Contains,
+    /// Method invoking another method
+    Calls,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declaration(name: &str, package: &str) -> Declaration {
+        Declaration {
+            name: name.to_string(),
+            kind: DeclarationKind::Class,
+            modifiers: vec!["public".to_string()],
+            annotations: vec![],
+            signature: format!("public class {}", name),
+            extends: None,
+            implements: vec![],
+            fields: vec![],
+            methods: vec![],
+            range: SourceRange { start_line: 1, start_column: 1, end_line: 1, end_column: 1 },
+            documentation: None,
+            package: package.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_fqn() {
+        assert_eq!(declaration("UserService", "com.example").fqn(), "com.example.UserService");
+        assert_eq!(declaration("UserService", "").fqn(), "UserService");
+    }
+
+    #[test]
+    fn test_interface_members_are_public() {
+        let modifiers = |modifiers: &[&str]| modifiers.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        let interface = Some(DeclarationKind::Interface);
+
+        assert_eq!(Visibility::from_modifiers(&modifiers(&[]), interface), Visibility::Public);
+        assert_eq!(Visibility::from_modifiers(&modifiers(&["static"]), interface), Visibility::Public);
+        assert_eq!(Visibility::from_modifiers(&modifiers(&["private"]), interface), Visibility::Private);
+        assert_eq!(
+            Visibility::from_modifiers(&modifiers(&[]), Some(DeclarationKind::Annotation)),
+            Visibility::Public
+        );
+        assert_eq!(
+            Visibility::from_modifiers(&modifiers(&[]), Some(DeclarationKind::Class)),
+            Visibility::PackagePrivate
+        );
+        assert_eq!(Visibility::from_modifiers(&modifiers(&[]), None), Visibility::PackagePrivate);
+    }
+}
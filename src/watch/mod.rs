@@ -0,0 +1,251 @@
+//! Watch mode: keeps the index in sync with the Java files of a project as they change
+
+use anyhow::Result;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::indexer::IndexManager;
+use crate::parser::{exclude_globs, JavaStructureParser};
+
+/// Quiet period after the last event on a path before it is re-indexed
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// What happened to the index for one changed path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReindexOutcome {
+    /// The file was parsed and its documents replaced
+    Reindexed,
+    /// The file no longer exists and its documents were deleted
+    Removed,
+}
+
+/// Re-indexes changed files, coalescing bursts of events for the same path
+pub struct WatchIndexer {
+    index_manager: IndexManager,
+    parser: JavaStructureParser,
+    debounce: Duration,
+    /// Root the exclude globs are relative to
+    project_root: PathBuf,
+    exclude: globset::GlobSet,
+    /// Last event time of each path waiting to be re-indexed
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl WatchIndexer {
+    pub fn new(index_manager: IndexManager) -> Result<Self> {
+        Ok(Self {
+            index_manager,
+            parser: JavaStructureParser::new()?,
+            debounce: DEFAULT_DEBOUNCE,
+            project_root: PathBuf::new(),
+            exclude: globset::GlobSet::empty(),
+            pending: HashMap::new(),
+        })
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Ignore changes to paths matching the `--exclude` globs, as the index command does
+    pub fn with_exclude(mut self, project_root: &Path, patterns: &[String]) -> Result<Self> {
+        // Events carry absolute paths
+        self.project_root = std::fs::canonicalize(project_root).unwrap_or_else(|_| project_root.to_path_buf());
+        self.exclude = exclude_globs(patterns)?;
+        Ok(self)
+    }
+
+    pub fn index_manager(&self) -> &IndexManager {
+        &self.index_manager
+    }
+
+    /// Bring the index up to date with one file: parse it, then replace its old documents
+    /// in a single commit, or drop them if it was deleted
+    /// A file that fails to parse keeps its previous documents.
+    pub async fn reindex_file(&self, path: &Path) -> Result<ReindexOutcome> {
+        if !path.exists() {
+            self.index_manager.delete_by_file_path(path).await?;
+            return Ok(ReindexOutcome::Removed);
+        }
+
+        let structure = self.parser.parse_structure(path)?;
+        self.index_manager.index_java_file(&structure).await?;
+        Ok(ReindexOutcome::Reindexed)
+    }
+
+    /// Remember the `.java` paths of a file system event; returns how many were queued
+    pub fn queue_event(&mut self, event: &Event, now: Instant) -> usize {
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+            return 0;
+        }
+
+        let mut queued = 0;
+        let relevant = |path: &&PathBuf| {
+            path.extension().is_some_and(|e| e == "java")
+                && !self.exclude.is_match(path.strip_prefix(&self.project_root).unwrap_or(path))
+        };
+        for path in event.paths.iter().filter(relevant) {
+            self.pending.insert(path.clone(), now);
+            queued += 1;
+        }
+        queued
+    }
+
+    /// Paths whose last event is older than the debounce delay, removed from the queue
+    pub fn take_ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let mut ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, last_event)| now.duration_since(**last_event) >= self.debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+        ready.sort();
+
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready
+    }
+
+    /// Watch `project_root` until the event channel closes, printing a line per re-indexed file
+    pub async fn run(mut self, project_root: &Path) -> Result<()> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let _ = tx.send(event);
+        })?;
+        watcher.watch(project_root, RecursiveMode::Recursive)?;
+
+        println!("👀 Watching {} for changes (Ctrl+C to stop)", project_root.display());
+
+        let mut tick = tokio::time::interval(self.debounce / 2);
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Some(Ok(event)) => {
+                        self.queue_event(&event, Instant::now());
+                    }
+                    Some(Err(e)) => eprintln!("⚠️  Watch error: {}", e),
+                    None => return Ok(()),
+                },
+                _ = tick.tick() => {
+                    for path in self.take_ready(Instant::now()) {
+                        match self.reindex_file(&path).await {
+                            Ok(ReindexOutcome::Reindexed) => println!("🔄 Re-indexed {}", path.display()),
+                            Ok(ReindexOutcome::Removed) => println!("🗑️  Removed {}", path.display()),
+                            Err(e) => eprintln!("⚠️  Failed to re-index {}: {}", path.display(), e),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SearchKind, SearchQuery};
+    use notify::event::{CreateKind, ModifyKind};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_reindex_changed_file() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let mut watch = WatchIndexer::new(index_manager).unwrap().with_debounce(Duration::from_millis(50));
+
+        let java_path = dir.path().join("Greeter.java");
+        std::fs::write(&java_path, "public class Greeter {}\n").unwrap();
+        assert_eq!(watch.reindex_file(&java_path).await.unwrap(), ReindexOutcome::Reindexed);
+
+        std::fs::write(&java_path, "public class Greeter {}\nclass GreeterHelper {}\n").unwrap();
+
+        // A burst of events on the same file is handled once, after the debounce delay
+        let start = Instant::now();
+        let modified = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(java_path.clone());
+        assert_eq!(watch.queue_event(&modified, start), 1);
+        assert_eq!(watch.queue_event(&modified, start + Duration::from_millis(30)), 1);
+        let ignored = Event::new(EventKind::Create(CreateKind::File)).add_path(dir.path().join("notes.txt"));
+        assert_eq!(watch.queue_event(&ignored, start), 0);
+
+        assert!(watch.take_ready(start + Duration::from_millis(60)).is_empty());
+        let ready = watch.take_ready(start + Duration::from_millis(80));
+        assert_eq!(ready, vec![java_path.clone()]);
+
+        for path in ready {
+            assert_eq!(watch.reindex_file(&path).await.unwrap(), ReindexOutcome::Reindexed);
+        }
+
+        let all = SearchQuery {
+            query: "*".to_string(),
+            kind: SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let mut names: Vec<String> = watch
+            .index_manager()
+            .search(&all)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.declaration.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["Greeter", "GreeterHelper"]);
+
+        std::fs::remove_file(&java_path).unwrap();
+        assert_eq!(watch.reindex_file(&java_path).await.unwrap(), ReindexOutcome::Removed);
+        assert!(watch.index_manager().search(&all).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unparsable_file_keeps_its_documents() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let watch = WatchIndexer::new(index_manager).unwrap();
+
+        let java_path = dir.path().join("Greeter.java");
+        std::fs::write(&java_path, "public class Greeter {}\n").unwrap();
+        watch.reindex_file(&java_path).await.unwrap();
+
+        // Replaced by something that cannot be read as a source file
+        std::fs::remove_file(&java_path).unwrap();
+        std::fs::create_dir(&java_path).unwrap();
+        assert!(watch.reindex_file(&java_path).await.is_err());
+
+        let all = SearchQuery {
+            query: "*".to_string(),
+            kind: SearchKind::Exact,
+            filters: vec![],
+            limit: Some(10),
+            offset: None,
+            filter_combine: crate::types::FilterCombine::Any,
+        };
+        let results = watch.index_manager().search(&all).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].declaration.name, "Greeter");
+    }
+
+    #[tokio::test]
+    async fn test_excluded_paths_are_not_queued() {
+        let dir = tempdir().unwrap();
+        let index_manager = IndexManager::new(&dir.path().join("index")).unwrap();
+        let mut watch = WatchIndexer::new(index_manager)
+            .unwrap()
+            .with_exclude(dir.path(), &["generated/**".to_string()])
+            .unwrap();
+
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let now = Instant::now();
+        let generated = Event::new(EventKind::Create(CreateKind::File)).add_path(root.join("generated/Stub.java"));
+        assert_eq!(watch.queue_event(&generated, now), 0);
+        let source = Event::new(EventKind::Create(CreateKind::File)).add_path(root.join("src/Greeter.java"));
+        assert_eq!(watch.queue_event(&source, now), 1);
+    }
+}
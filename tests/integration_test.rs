@@ -6,6 +6,7 @@ use tempfile::tempdir;
 use code_insight::{
     parser::{FileParser, JavaStructureParser},
     indexer::IndexManager,
+    llm::{EntryPointKind, LlmExporter},
     query::QueryEngine,
     types::{DeclarationKind, SearchKind, SearchFilter},
 };
@@ -23,7 +24,7 @@ async fn test_full_workflow() -> Result<()> {
 
     // 2. Parse Java files
     let file_parser = FileParser::new()?;
-    let java_files = file_parser.find_source_files(project_root)?
+    let java_files = file_parser.find_source_files(project_root, &[])?
         .into_iter()
         .filter(|p| p.extension().map_or(false, |e| e == "java"))
         .collect::<Vec<_>>();
@@ -71,6 +72,8 @@ async fn test_full_workflow() -> Result<()> {
         kind: SearchKind::Exact,
         filters: vec![],
         limit: Some(5),
+        offset: None,
+        filter_combine: code_insight::types::FilterCombine::Any,
     };
     let results = query_engine.search(&search_query).await?;
     assert!(results.len() >= 1);
@@ -84,6 +87,8 @@ async fn test_full_workflow() -> Result<()> {
         kind: SearchKind::Fuzzy,
         filters: vec![],
         limit: Some(5),
+        offset: None,
+        filter_combine: code_insight::types::FilterCombine::Any,
     };
     let _fuzzy_results = query_engine.search(&fuzzy_query).await?;
     // Skip fuzzy search assertion for now
@@ -117,7 +122,7 @@ async fn test_error_handling() -> Result<()> {
     }
     let index_manager = IndexManager::new(&index_path)?;
     let file_parser = FileParser::new()?;
-    let java_files = file_parser.find_source_files(project_root)?
+    let java_files = file_parser.find_source_files(project_root, &[])?
         .into_iter()
         .filter(|p| p.extension().map_or(false, |e| e == "java"))
         .collect::<Vec<_>>();
@@ -165,7 +170,7 @@ async fn test_filtering() -> Result<()> {
         let file_parser = FileParser::new()?;
         let mut java_parser = JavaStructureParser::new()?;
 
-        let java_files = file_parser.find_source_files(project_root)?
+        let java_files = file_parser.find_source_files(project_root, &[])?
             .into_iter()
             .filter(|p| p.extension().map_or(false, |e| e == "java"))
             .collect::<Vec<_>>();
@@ -196,6 +201,8 @@ async fn test_filtering() -> Result<()> {
             SearchFilter::Annotation("Service".to_string()),
         ],
         limit: Some(5),
+        offset: None,
+        filter_combine: code_insight::types::FilterCombine::Any,
     };
     let filtered_results = query_engine.search(&search_query).await?;
     println!("Found {} filtered results", filtered_results.len());
@@ -203,6 +210,73 @@ async fn test_filtering() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_project_manifest() -> Result<()> {
+    let dir = tempdir()?;
+    let project_root = dir.path().join("project");
+    create_test_project(&project_root)?;
+    fs::write(
+        project_root.join("src/main/java/com/example/Application.java"),
+        r#"
+package com.example;
+
+@SpringBootApplication
+public class Application {
+    public static void main(String[] args) {
+        SpringApplication.run(Application.class, args);
+    }
+}
+"#,
+    )?;
+    fs::write(
+        project_root.join("pom.xml"),
+        "<project><groupId>com.example</groupId><artifactId>users</artifactId><version>1.0.0</version></project>",
+    )?;
+
+    let index_manager = IndexManager::new(&dir.path().join("index"))?;
+    let java_parser = JavaStructureParser::new()?;
+    for file_path in FileParser::new()?.find_source_files(&project_root, &[])? {
+        if file_path.extension().is_some_and(|e| e == "java") {
+            index_manager.index_java_file(&java_parser.parse_structure(&file_path)?).await?;
+        }
+    }
+    let exporter = LlmExporter::new(QueryEngine::new_with_manager(index_manager)?, project_root.clone())?;
+
+    let manifest = exporter.export_manifest().await?;
+    let packages: Vec<(&str, usize)> = manifest
+        .packages
+        .iter()
+        .map(|p| (p.name.as_str(), p.declaration_count))
+        .collect();
+    assert_eq!(
+        packages,
+        vec![
+            ("com.example", 1),
+            ("com.example.model", 1),
+            ("com.example.repository", 1),
+            ("com.example.service", 1),
+        ]
+    );
+    assert_eq!(manifest.stereotype_counts.get("is_service"), Some(&1));
+    assert_eq!(manifest.statistics.class_count, 3);
+
+    let entry_points: Vec<(&str, EntryPointKind)> =
+        manifest.entry_points.iter().map(|e| (e.fqn.as_str(), e.kind)).collect();
+    assert_eq!(
+        entry_points,
+        vec![
+            ("com.example.Application", EntryPointKind::SpringBootApplication),
+            ("com.example.Application", EntryPointKind::MainMethod),
+        ]
+    );
+
+    assert_eq!(manifest.modules.len(), 1);
+    assert_eq!(manifest.modules[0].path, "pom.xml");
+    assert_eq!(manifest.modules[0].coordinates, "com.example:users:1.0.0");
+
+    Ok(())
+}
+
 fn create_test_project(project_root: &Path) -> Result<()> {
 
     // Create source directory structure
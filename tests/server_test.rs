@@ -0,0 +1,70 @@
+use anyhow::Result;
+use std::fs;
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use code_insight::{
+    indexer::IndexManager,
+    llm::LlmExporter,
+    parser::JavaStructureParser,
+    query::QueryEngine,
+};
+
+/// Send a request and return the status line and the body
+async fn http_request(addr: std::net::SocketAddr, method: &str, path: &str, body: &str) -> Result<(String, String)> {
+    let mut stream = TcpStream::connect(addr).await?;
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    Ok((head.lines().next().unwrap_or("").to_string(), body.to_string()))
+}
+
+#[tokio::test]
+async fn test_serve_search_and_stats() -> Result<()> {
+    let dir = tempdir()?;
+    let java_path = dir.path().join("UserService.java");
+    fs::write(&java_path, "package com.example;\n\npublic class UserService {}\n")?;
+
+    let index_manager = IndexManager::new(&dir.path().join("index"))?;
+    index_manager
+        .index_java_file(&JavaStructureParser::new()?.parse_structure(&java_path)?)
+        .await?;
+    let exporter = LlmExporter::new(QueryEngine::new_with_manager(index_manager)?, dir.path().to_path_buf())?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(code_insight::server::serve(listener, exporter));
+
+    let (status, body) = http_request(addr, "GET", "/search?q=UserService&kind=exact", "").await?;
+    assert!(status.contains("200"), "{}", status);
+    let results: serde_json::Value = serde_json::from_str(&body)?;
+    assert_eq!(results.as_array().map(Vec::len), Some(1));
+    assert_eq!(results[0]["declaration"]["name"], "UserService");
+
+    let (status, body) = http_request(addr, "GET", "/stats", "").await?;
+    assert!(status.contains("200"), "{}", status);
+    let stats: serde_json::Value = serde_json::from_str(&body)?;
+    assert_eq!(stats["class_count"], 1);
+
+    let (status, body) = http_request(addr, "GET", "/search?q=User&kind=telepathic", "").await?;
+    assert!(status.contains("400"), "{}", status);
+    assert!(body.contains("Unknown search kind"));
+
+    let export = r#"{"query": "UserService", "kind": null, "annotations": [], "package": null, "limit": 10, "include_source": false, "format": "Json"}"#;
+    let (status, body) = http_request(addr, "POST", "/export", export).await?;
+    assert!(status.contains("200"), "{}", status);
+    let response: serde_json::Value = serde_json::from_str(&body)?;
+    assert_eq!(response["metadata"]["total_count"], 1);
+
+    Ok(())
+}